@@ -1,7 +1,7 @@
 use anyhow::Result;
 use portfolio_api::{
     config::Config,
-    services::{db, rss::FeedService},
+    services::{db, link_checker::LinkChecker, projects::ProjectService, rss::FeedService},
 };
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
@@ -24,12 +24,61 @@ async fn main() -> Result<()> {
         .await?
         .database("portfolio");
 
+    match std::env::args().nth(1).as_deref() {
+        None | Some("sync") => sync(db, config).await,
+        Some("check-links") => check_links(db, &config).await,
+        Some("sync-projects") => sync_projects(db, config).await,
+        Some(other) => {
+            anyhow::bail!("Unknown command {other}, expected sync, check-links or sync-projects")
+        }
+    }
+}
+
+/// Synchronise les articles RSS
+async fn sync(db: mongodb::Database, config: Config) -> Result<()> {
+    let image_metadata = config.rss_image_metadata;
     let feed_service = FeedService::new(db, config);
 
     // Synchroniser les articles
     tracing::info!("Début de la synchronisation des articles RSS");
-    feed_service.store_items().await?;
-    tracing::info!("Synchronisation terminée avec succès");
+    let report = feed_service.store_items().await?;
+    tracing::info!(
+        "Synchronisation terminée avec succès : {} article(s) lus, {} stockés, {} filtrés",
+        report.fetched,
+        report.stored,
+        report.dropped()
+    );
+
+    if image_metadata {
+        let updated = feed_service.enrich_images().await?;
+        tracing::info!(
+            "Métadonnées d'image enregistrées pour {} article(s)",
+            updated
+        );
+    }
+
+    Ok(())
+}
+
+/// Vérifie les liens et les images des articles stockés
+async fn check_links(db: mongodb::Database, config: &Config) -> Result<()> {
+    tracing::info!("Début de la vérification des liens");
+    let report = LinkChecker::new(db, config).run().await?;
+    tracing::info!(
+        "Vérification terminée : {} URL(s) vérifiées, {} lien(s) mort(s), {} image(s) morte(s)",
+        report.checked,
+        report.dead_links,
+        report.dead_images
+    );
+
+    Ok(())
+}
+
+/// Synchronise les projets GitHub
+async fn sync_projects(db: mongodb::Database, config: Config) -> Result<()> {
+    tracing::info!("Début de la synchronisation des projets GitHub");
+    let count = ProjectService::new(db, config).sync().await?;
+    tracing::info!("Synchronisation terminée : {} projet(s)", count);
 
     Ok(())
 }
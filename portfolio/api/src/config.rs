@@ -1,3 +1,4 @@
+use crate::services::ranking::RankingConfig;
 use serde::Deserialize;
 use std::env;
 
@@ -8,6 +9,8 @@ use std::env;
 /// - `PORT`: Server port (default: 8080)
 /// - `BREVO_API_KEY`: Email service API key (required)
 /// - `FRONTEND_URL`: CORS allowed origin (required)
+/// - `PUBLIC_API_URL`: Public base URL of the API (default: `http://HOST:PORT`)
+/// - `ADMIN_API_TOKEN`: Bearer token for administration routes (optional, disabled if unset)
 #[derive(Debug, Clone, Deserialize)]
 pub struct Config {
     /// MongoDB connection URL with authentication source
@@ -18,6 +21,8 @@ pub struct Config {
     pub brevo_api_key: String,
     /// Allowed frontend origin for CORS
     pub frontend_url: String,
+    /// Public base URL of this API, used in URLs handed to the frontend
+    pub public_api_url: String,
     pub host: String,
     pub rss_cache_duration: u64,
     pub recipient_email: String,
@@ -26,6 +31,119 @@ pub struct Config {
     pub rss_source_url: String,
     pub rss_source_db: String,
     pub rss_source_collection: String,
+    /// Optional JSON file with include/exclude rules applied to every feed
+    pub rss_global_rules_path: Option<String>,
+    /// Fetches article pages to fill missing images and descriptions
+    #[serde(default)]
+    pub rss_og_enrichment: bool,
+    /// Downloads article images to record their size, dominant color and blurhash
+    #[serde(default)]
+    pub rss_image_metadata: bool,
+    /// Directory of the image proxy cache, the proxy is disabled when unset
+    pub image_proxy_cache_dir: Option<String>,
+    /// Maximum size of the image proxy cache, in megabytes
+    pub image_proxy_cache_max_mb: u64,
+    /// Interval between background link checks, in hours (0 disables them)
+    pub link_check_interval_hours: u64,
+    /// Delay between two checks on the same host, in milliseconds
+    pub link_check_host_delay_ms: u64,
+    /// Base URL of the GitHub REST API (GitHub releases sources)
+    pub github_api_url: String,
+    /// GitHub user whose public repositories make up the projects showcase
+    pub github_username: Option<String>,
+    /// Optional GitHub token, raising the API rate limit
+    pub github_token: Option<String>,
+    /// Interval between projects syncs, in hours
+    pub github_sync_interval_hours: u64,
+    /// Locales served by `/api/i18n`, the first one being the default
+    pub i18n_locales: Vec<String>,
+    /// Attempts before an outgoing email is dead-lettered
+    pub email_max_attempts: u32,
+    /// Delay before the first retry of an email, in seconds, doubled after each failure
+    pub email_retry_base_secs: u64,
+    /// Transport of the outgoing emails: `brevo`, `smtp`, `file` or `log`
+    pub email_transport: String,
+    /// Base URL of the Brevo API
+    pub brevo_api_url: String,
+    /// SMTP server of the `smtp` transport
+    pub smtp_host: Option<String>,
+    /// SMTP port, the usual port of `smtp_tls` when unset
+    pub smtp_port: Option<u16>,
+    /// SMTP encryption: `starttls`, `tls` or `none`
+    pub smtp_tls: String,
+    /// SMTP login, the connection is not authenticated when unset
+    pub smtp_username: Option<String>,
+    pub smtp_password: Option<String>,
+    /// Maildir of the `file` transport
+    pub email_file_dir: String,
+    /// Directory of email templates overriding the built-in ones
+    pub email_templates_dir: Option<String>,
+    /// Locale of the email templates used when the requested one has no variant
+    pub email_fallback_locale: String,
+    /// Fails to render an email template with a missing value
+    pub email_templates_strict: bool,
+    /// Reloads the email templates directory when it changes (development)
+    #[serde(default)]
+    pub email_templates_hot_reload: bool,
+    /// Acknowledges contact messages to their sender
+    #[serde(default)]
+    pub contact_auto_reply: bool,
+    /// Minimum delay between two acknowledgments to the same address, in hours
+    pub contact_auto_reply_window_hours: u64,
+    /// Secret signing the contact form tokens, random per instance when unset
+    pub contact_token_secret: Option<String>,
+    /// Minimum time to fill the contact form, in seconds
+    pub contact_min_fill_secs: u64,
+    /// Lifetime of a contact form token, in seconds
+    pub contact_token_max_age_secs: u64,
+    /// Optional JSON file with the spam keywords, domain lists and weights
+    pub contact_spam_rules_path: Option<String>,
+    /// Spam score from which a contact message is quarantined
+    pub contact_spam_threshold: f64,
+    /// Leading zero bits of the proof of work under normal load
+    pub contact_pow_difficulty: u32,
+    /// Highest difficulty reached during a spike of contact messages
+    pub contact_pow_max_difficulty: u32,
+    /// Contact messages per window from which the difficulty is raised
+    pub contact_pow_spike_threshold: u64,
+    /// Window over which the contact volume is measured, in seconds
+    pub contact_pow_window_secs: u64,
+    /// Lifetime of a proof-of-work challenge, in seconds
+    pub contact_pow_ttl_secs: u64,
+    /// Optional file of disposable email domains, one per line
+    pub contact_disposable_domains_path: Option<String>,
+    /// Refuses contact addresses whose domain has no MX or A/AAAA records
+    pub contact_mx_check: bool,
+    /// Comma-separated DNS servers (`ip[:port]`), the system ones when unset
+    pub contact_dns_servers: Option<String>,
+    /// Timeout of a DNS lookup, in milliseconds
+    pub contact_dns_timeout_ms: u64,
+    /// Lifetime of the cached DNS answers, in seconds
+    pub contact_dns_cache_secs: u64,
+    /// Contact messages allowed per IP and window, 0 for no limit
+    pub contact_limit_per_ip: u64,
+    pub contact_limit_ip_window_secs: u64,
+    /// Contact messages allowed per sender address and window, 0 for no limit
+    pub contact_limit_per_email: u64,
+    pub contact_limit_email_window_secs: u64,
+    /// Contact messages allowed overall per window, 0 for no limit
+    pub contact_limit_global: u64,
+    pub contact_limit_global_window_secs: u64,
+    /// Bearer token protecting the administration routes
+    pub admin_api_token: Option<String>,
+    /// Ordering of the aggregated RSS listing (see [`RankingConfig::from_env`])
+    #[serde(default)]
+    pub ranking: RankingConfig,
+}
+
+/// Whether a URL points to the local machine, as in development
+fn is_local_url(url: &str) -> bool {
+    url::Url::parse(url).is_ok_and(|url| match url.host() {
+        Some(url::Host::Domain(domain)) => domain == "localhost",
+        Some(url::Host::Ipv4(ip)) => ip.is_loopback(),
+        Some(url::Host::Ipv6(ip)) => ip.is_loopback(),
+        None => false,
+    })
 }
 
 impl Config {
@@ -46,8 +164,45 @@ impl Config {
     /// - `RSS_SOURCE_URL`
     /// - `RSS_SOURCE_DB`
     /// - `RSS_SOURCE_COLLECTION`
+    /// - `CONTACT_TOKEN_SECRET`, hors développement (`FRONTEND_URL` sur `localhost`)
     ///
     /// La variable `RSS_CACHE_DURATION` est optionnelle et vaut 3600 par défaut.
+    /// Les variables `RSS_GLOBAL_RULES_PATH` et `ADMIN_API_TOKEN` sont optionnelles.
+    /// Les variables `RSS_OG_ENRICHMENT` et `RSS_IMAGE_METADATA` sont optionnelles et valent `false` par défaut.
+    /// La variable `IMAGE_PROXY_CACHE_DIR` est optionnelle, `IMAGE_PROXY_CACHE_MAX_MB` vaut 256 par défaut.
+    /// Les variables `LINK_CHECK_INTERVAL_HOURS` (0 par défaut, désactivé) et
+    /// `LINK_CHECK_HOST_DELAY_MS` (1000 par défaut) sont optionnelles.
+    /// La variable `GITHUB_API_URL` est optionnelle et vaut `https://api.github.com` par défaut.
+    /// Les variables `GITHUB_USERNAME` et `GITHUB_TOKEN` sont optionnelles,
+    /// `GITHUB_SYNC_INTERVAL_HOURS` vaut 6 par défaut.
+    /// La variable `I18N_LOCALES` est optionnelle et vaut `fr,en` par défaut (la première est la langue par défaut).
+    /// Les variables `EMAIL_MAX_ATTEMPTS` (8 par défaut) et `EMAIL_RETRY_BASE_SECS` (30 par défaut) sont optionnelles.
+    /// La variable `EMAIL_TRANSPORT` est optionnelle et vaut `brevo` par défaut (`smtp`, `file` ou `log`),
+    /// `BREVO_API_URL` vaut `https://api.brevo.com`, `SMTP_TLS` vaut `starttls` et `EMAIL_FILE_DIR` vaut `mail`.
+    /// Les variables `SMTP_HOST` (requise par le transport `smtp`), `SMTP_PORT`, `SMTP_USERNAME`
+    /// et `SMTP_PASSWORD` sont optionnelles.
+    /// La variable `EMAIL_TEMPLATES_DIR` est optionnelle, `EMAIL_FALLBACK_LOCALE` vaut `en`,
+    /// `EMAIL_TEMPLATES_STRICT` vaut `true` et `EMAIL_TEMPLATES_HOT_RELOAD` vaut `false` par défaut.
+    /// La variable `CONTACT_AUTO_REPLY` est optionnelle et vaut `false` par défaut,
+    /// `CONTACT_AUTO_REPLY_WINDOW_HOURS` vaut 24.
+    /// La variable `CONTACT_TOKEN_SECRET` est requise en production : elle signe les jetons
+    /// et les défis du formulaire, et doit être la même sur toutes les instances. En
+    /// développement, un secret aléatoire par instance est utilisé à défaut.
+    /// `CONTACT_MIN_FILL_SECS` vaut 3 et `CONTACT_TOKEN_MAX_AGE_SECS` vaut 7200 par défaut.
+    /// La variable `CONTACT_SPAM_RULES_PATH` est optionnelle, `CONTACT_SPAM_THRESHOLD` vaut 5 par défaut.
+    /// Les variables `CONTACT_POW_DIFFICULTY` (16 par défaut), `CONTACT_POW_MAX_DIFFICULTY` (22),
+    /// `CONTACT_POW_SPIKE_THRESHOLD` (20), `CONTACT_POW_WINDOW_SECS` (600) et
+    /// `CONTACT_POW_TTL_SECS` (600) sont optionnelles.
+    /// La variable `CONTACT_DISPOSABLE_DOMAINS_PATH` est optionnelle (liste intégrée à défaut),
+    /// `CONTACT_MX_CHECK` vaut `true`, `CONTACT_DNS_TIMEOUT_MS` vaut 2000 et
+    /// `CONTACT_DNS_CACHE_SECS` vaut 3600 par défaut. La variable `CONTACT_DNS_SERVERS`
+    /// est optionnelle (résolveur du système à défaut).
+    /// Les limites d'envoi du formulaire de contact sont optionnelles (0 les désactive) :
+    /// `CONTACT_LIMIT_PER_IP` vaut 3 par `CONTACT_LIMIT_IP_WINDOW_SECS` (3600),
+    /// `CONTACT_LIMIT_PER_EMAIL` vaut 5 par `CONTACT_LIMIT_EMAIL_WINDOW_SECS` (86400) et
+    /// `CONTACT_LIMIT_GLOBAL` vaut 200 par `CONTACT_LIMIT_GLOBAL_WINDOW_SECS` (86400).
+    /// La variable `PUBLIC_API_URL` est optionnelle et vaut `http://HOST:PORT` par défaut.
+    /// Les variables `RSS_RANKING_*` sont décrites dans [`RankingConfig::from_env`].
     #[must_use]
     pub fn new() -> Self {
         // Charger les variables d'environnement depuis le fichier .env approprié
@@ -76,10 +231,165 @@ impl Config {
         let sender_name = env::var("SENDER_NAME").expect("SENDER_NAME must be set");
         let sender_email = env::var("SENDER_EMAIL").expect("SENDER_EMAIL must be set");
         let frontend_url = env::var("FRONTEND_URL").expect("FRONTEND_URL must be set");
+        let public_api_url =
+            env::var("PUBLIC_API_URL").unwrap_or_else(|_| format!("http://{host}:{port}"));
         let rss_source_url = env::var("RSS_SOURCE_URL").expect("RSS_SOURCE_URL must be set");
         let rss_source_db = env::var("RSS_SOURCE_DB").expect("RSS_SOURCE_DB must be set");
         let rss_source_collection =
             env::var("RSS_SOURCE_COLLECTION").expect("RSS_SOURCE_COLLECTION must be set");
+        let rss_global_rules_path = env::var("RSS_GLOBAL_RULES_PATH").ok();
+        let rss_og_enrichment = env::var("RSS_OG_ENRICHMENT")
+            .map(|v| v == "true" || v == "1")
+            .unwrap_or(false);
+        let rss_image_metadata = env::var("RSS_IMAGE_METADATA")
+            .map(|v| v == "true" || v == "1")
+            .unwrap_or(false);
+        let image_proxy_cache_dir = env::var("IMAGE_PROXY_CACHE_DIR").ok();
+        let image_proxy_cache_max_mb = env::var("IMAGE_PROXY_CACHE_MAX_MB")
+            .unwrap_or_else(|_| "256".to_string())
+            .parse()
+            .expect("IMAGE_PROXY_CACHE_MAX_MB must be a number");
+        let link_check_interval_hours = env::var("LINK_CHECK_INTERVAL_HOURS")
+            .unwrap_or_else(|_| "0".to_string())
+            .parse()
+            .expect("LINK_CHECK_INTERVAL_HOURS must be a number");
+        let link_check_host_delay_ms = env::var("LINK_CHECK_HOST_DELAY_MS")
+            .unwrap_or_else(|_| "1000".to_string())
+            .parse()
+            .expect("LINK_CHECK_HOST_DELAY_MS must be a number");
+        let github_api_url =
+            env::var("GITHUB_API_URL").unwrap_or_else(|_| "https://api.github.com".to_string());
+        let github_username = env::var("GITHUB_USERNAME").ok();
+        let github_token = env::var("GITHUB_TOKEN").ok();
+        let github_sync_interval_hours = env::var("GITHUB_SYNC_INTERVAL_HOURS")
+            .unwrap_or_else(|_| "6".to_string())
+            .parse()
+            .expect("GITHUB_SYNC_INTERVAL_HOURS must be a number");
+        let i18n_locales = env::var("I18N_LOCALES")
+            .unwrap_or_else(|_| "fr,en".to_string())
+            .split(',')
+            .map(|locale| locale.trim().to_lowercase())
+            .filter(|locale| !locale.is_empty())
+            .collect::<Vec<_>>();
+        assert!(
+            !i18n_locales.is_empty(),
+            "I18N_LOCALES must list at least one locale"
+        );
+        let email_max_attempts = env::var("EMAIL_MAX_ATTEMPTS")
+            .unwrap_or_else(|_| "8".to_string())
+            .parse()
+            .expect("EMAIL_MAX_ATTEMPTS must be a number");
+        let email_retry_base_secs = env::var("EMAIL_RETRY_BASE_SECS")
+            .unwrap_or_else(|_| "30".to_string())
+            .parse()
+            .expect("EMAIL_RETRY_BASE_SECS must be a number");
+        let email_transport = env::var("EMAIL_TRANSPORT").unwrap_or_else(|_| "brevo".to_string());
+        let brevo_api_url =
+            env::var("BREVO_API_URL").unwrap_or_else(|_| "https://api.brevo.com".to_string());
+        let smtp_host = env::var("SMTP_HOST").ok();
+        let smtp_port = env::var("SMTP_PORT")
+            .ok()
+            .map(|port| port.parse().expect("SMTP_PORT must be a number"));
+        let smtp_tls = env::var("SMTP_TLS").unwrap_or_else(|_| "starttls".to_string());
+        let smtp_username = env::var("SMTP_USERNAME").ok();
+        let smtp_password = env::var("SMTP_PASSWORD").ok();
+        let email_file_dir = env::var("EMAIL_FILE_DIR").unwrap_or_else(|_| "mail".to_string());
+        let email_templates_dir = env::var("EMAIL_TEMPLATES_DIR").ok();
+        let email_fallback_locale = env::var("EMAIL_FALLBACK_LOCALE")
+            .map(|locale| locale.trim().to_lowercase())
+            .unwrap_or_else(|_| "en".to_string());
+        let email_templates_strict = env::var("EMAIL_TEMPLATES_STRICT")
+            .map(|v| v == "true" || v == "1")
+            .unwrap_or(true);
+        let email_templates_hot_reload = env::var("EMAIL_TEMPLATES_HOT_RELOAD")
+            .map(|v| v == "true" || v == "1")
+            .unwrap_or(false);
+        let contact_auto_reply = env::var("CONTACT_AUTO_REPLY")
+            .map(|v| v == "true" || v == "1")
+            .unwrap_or(false);
+        let contact_auto_reply_window_hours = env::var("CONTACT_AUTO_REPLY_WINDOW_HOURS")
+            .unwrap_or_else(|_| "24".to_string())
+            .parse()
+            .expect("CONTACT_AUTO_REPLY_WINDOW_HOURS must be a number");
+        let contact_token_secret = env::var("CONTACT_TOKEN_SECRET")
+            .ok()
+            .filter(|secret| !secret.is_empty());
+        assert!(
+            contact_token_secret.is_some() || is_local_url(&frontend_url),
+            "CONTACT_TOKEN_SECRET must be set in production"
+        );
+        let contact_min_fill_secs = env::var("CONTACT_MIN_FILL_SECS")
+            .unwrap_or_else(|_| "3".to_string())
+            .parse()
+            .expect("CONTACT_MIN_FILL_SECS must be a number");
+        let contact_token_max_age_secs = env::var("CONTACT_TOKEN_MAX_AGE_SECS")
+            .unwrap_or_else(|_| "7200".to_string())
+            .parse()
+            .expect("CONTACT_TOKEN_MAX_AGE_SECS must be a number");
+        let contact_spam_rules_path = env::var("CONTACT_SPAM_RULES_PATH").ok();
+        let contact_spam_threshold = env::var("CONTACT_SPAM_THRESHOLD")
+            .unwrap_or_else(|_| "5".to_string())
+            .parse()
+            .expect("CONTACT_SPAM_THRESHOLD must be a number");
+        let contact_pow_difficulty = env::var("CONTACT_POW_DIFFICULTY")
+            .unwrap_or_else(|_| "16".to_string())
+            .parse()
+            .expect("CONTACT_POW_DIFFICULTY must be a number");
+        let contact_pow_max_difficulty = env::var("CONTACT_POW_MAX_DIFFICULTY")
+            .unwrap_or_else(|_| "22".to_string())
+            .parse()
+            .expect("CONTACT_POW_MAX_DIFFICULTY must be a number");
+        let contact_pow_spike_threshold = env::var("CONTACT_POW_SPIKE_THRESHOLD")
+            .unwrap_or_else(|_| "20".to_string())
+            .parse()
+            .expect("CONTACT_POW_SPIKE_THRESHOLD must be a number");
+        let contact_pow_window_secs = env::var("CONTACT_POW_WINDOW_SECS")
+            .unwrap_or_else(|_| "600".to_string())
+            .parse()
+            .expect("CONTACT_POW_WINDOW_SECS must be a number");
+        let contact_pow_ttl_secs = env::var("CONTACT_POW_TTL_SECS")
+            .unwrap_or_else(|_| "600".to_string())
+            .parse()
+            .expect("CONTACT_POW_TTL_SECS must be a number");
+        let contact_disposable_domains_path = env::var("CONTACT_DISPOSABLE_DOMAINS_PATH").ok();
+        let contact_mx_check = env::var("CONTACT_MX_CHECK")
+            .map(|v| v == "true" || v == "1")
+            .unwrap_or(true);
+        let contact_dns_servers = env::var("CONTACT_DNS_SERVERS").ok();
+        let contact_dns_timeout_ms = env::var("CONTACT_DNS_TIMEOUT_MS")
+            .unwrap_or_else(|_| "2000".to_string())
+            .parse()
+            .expect("CONTACT_DNS_TIMEOUT_MS must be a number");
+        let contact_dns_cache_secs = env::var("CONTACT_DNS_CACHE_SECS")
+            .unwrap_or_else(|_| "3600".to_string())
+            .parse()
+            .expect("CONTACT_DNS_CACHE_SECS must be a number");
+        let contact_limit_per_ip = env::var("CONTACT_LIMIT_PER_IP")
+            .unwrap_or_else(|_| "3".to_string())
+            .parse()
+            .expect("CONTACT_LIMIT_PER_IP must be a number");
+        let contact_limit_ip_window_secs = env::var("CONTACT_LIMIT_IP_WINDOW_SECS")
+            .unwrap_or_else(|_| "3600".to_string())
+            .parse()
+            .expect("CONTACT_LIMIT_IP_WINDOW_SECS must be a number");
+        let contact_limit_per_email = env::var("CONTACT_LIMIT_PER_EMAIL")
+            .unwrap_or_else(|_| "5".to_string())
+            .parse()
+            .expect("CONTACT_LIMIT_PER_EMAIL must be a number");
+        let contact_limit_email_window_secs = env::var("CONTACT_LIMIT_EMAIL_WINDOW_SECS")
+            .unwrap_or_else(|_| "86400".to_string())
+            .parse()
+            .expect("CONTACT_LIMIT_EMAIL_WINDOW_SECS must be a number");
+        let contact_limit_global = env::var("CONTACT_LIMIT_GLOBAL")
+            .unwrap_or_else(|_| "200".to_string())
+            .parse()
+            .expect("CONTACT_LIMIT_GLOBAL must be a number");
+        let contact_limit_global_window_secs = env::var("CONTACT_LIMIT_GLOBAL_WINDOW_SECS")
+            .unwrap_or_else(|_| "86400".to_string())
+            .parse()
+            .expect("CONTACT_LIMIT_GLOBAL_WINDOW_SECS must be a number");
+        let admin_api_token = env::var("ADMIN_API_TOKEN").ok();
+        let ranking = RankingConfig::from_env();
 
         Self {
             mongo_url,
@@ -91,9 +401,61 @@ impl Config {
             sender_name,
             sender_email,
             frontend_url,
+            public_api_url,
             rss_source_url,
             rss_source_db,
             rss_source_collection,
+            rss_global_rules_path,
+            rss_og_enrichment,
+            rss_image_metadata,
+            image_proxy_cache_dir,
+            image_proxy_cache_max_mb,
+            link_check_interval_hours,
+            link_check_host_delay_ms,
+            github_api_url,
+            github_username,
+            github_token,
+            github_sync_interval_hours,
+            i18n_locales,
+            email_max_attempts,
+            email_retry_base_secs,
+            email_transport,
+            brevo_api_url,
+            smtp_host,
+            smtp_port,
+            smtp_tls,
+            smtp_username,
+            smtp_password,
+            email_file_dir,
+            email_templates_dir,
+            email_fallback_locale,
+            email_templates_strict,
+            email_templates_hot_reload,
+            contact_auto_reply,
+            contact_auto_reply_window_hours,
+            contact_token_secret,
+            contact_min_fill_secs,
+            contact_token_max_age_secs,
+            contact_spam_rules_path,
+            contact_spam_threshold,
+            contact_pow_difficulty,
+            contact_pow_max_difficulty,
+            contact_pow_spike_threshold,
+            contact_pow_window_secs,
+            contact_pow_ttl_secs,
+            contact_disposable_domains_path,
+            contact_mx_check,
+            contact_dns_servers,
+            contact_dns_timeout_ms,
+            contact_dns_cache_secs,
+            contact_limit_per_ip,
+            contact_limit_ip_window_secs,
+            contact_limit_per_email,
+            contact_limit_email_window_secs,
+            contact_limit_global,
+            contact_limit_global_window_secs,
+            admin_api_token,
+            ranking,
         }
     }
 
@@ -126,9 +488,61 @@ impl Config {
             sender_name: String::from("Test Sender"),
             sender_email: String::from("test@sender.com"),
             frontend_url: String::from("http://localhost:3000"),
+            public_api_url: String::from("http://127.0.0.1:3001"),
             rss_source_url: String::from("http://example.com/rss"),
             rss_source_db: String::from("rss_source"),
             rss_source_collection: String::from("rss_items"),
+            rss_global_rules_path: None,
+            rss_og_enrichment: false,
+            rss_image_metadata: false,
+            image_proxy_cache_dir: None,
+            image_proxy_cache_max_mb: 16,
+            link_check_interval_hours: 0,
+            link_check_host_delay_ms: 0,
+            github_api_url: String::from("https://api.github.com"),
+            github_username: None,
+            github_token: None,
+            github_sync_interval_hours: 6,
+            i18n_locales: vec![String::from("fr"), String::from("en")],
+            email_max_attempts: 8,
+            email_retry_base_secs: 30,
+            email_transport: String::from("brevo"),
+            brevo_api_url: String::from("https://api.brevo.com"),
+            smtp_host: None,
+            smtp_port: None,
+            smtp_tls: String::from("starttls"),
+            smtp_username: None,
+            smtp_password: None,
+            email_file_dir: String::from("mail"),
+            email_templates_dir: None,
+            email_fallback_locale: String::from("en"),
+            email_templates_strict: true,
+            email_templates_hot_reload: false,
+            contact_auto_reply: false,
+            contact_auto_reply_window_hours: 24,
+            contact_token_secret: Some(String::from("test_contact_secret")),
+            contact_min_fill_secs: 3,
+            contact_token_max_age_secs: 7200,
+            contact_spam_rules_path: None,
+            contact_spam_threshold: 5.0,
+            contact_pow_difficulty: 4,
+            contact_pow_max_difficulty: 8,
+            contact_pow_spike_threshold: 20,
+            contact_pow_window_secs: 600,
+            contact_pow_ttl_secs: 600,
+            contact_disposable_domains_path: None,
+            contact_mx_check: false,
+            contact_dns_servers: None,
+            contact_dns_timeout_ms: 2000,
+            contact_dns_cache_secs: 3600,
+            contact_limit_per_ip: 3,
+            contact_limit_ip_window_secs: 3600,
+            contact_limit_per_email: 5,
+            contact_limit_email_window_secs: 86400,
+            contact_limit_global: 200,
+            contact_limit_global_window_secs: 86400,
+            admin_api_token: Some(String::from("test_admin_token")),
+            ranking: RankingConfig::default(),
         }
     }
 }
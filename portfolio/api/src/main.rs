@@ -8,16 +8,57 @@
 
 use axum::http::HeaderValue;
 use axum::{
+    extract::State,
     http::{HeaderName, Method},
+    routing::delete,
     routing::get,
+    routing::patch,
     routing::post,
+    routing::put,
     Router,
 };
 use portfolio_api::{
     config::Config,
-    middleware::{MongoSanitizer, RateLimiter},
-    routes::{contact::handle_message, health::check, rss::get_feeds},
-    services::{contact::MessageService, db, rss::FeedService},
+    middleware::{AdminAuth, MongoSanitizer, RateLimiter},
+    routes::{
+        analytics::{get_stats, record_event},
+        case_studies::{
+            create_case_study, delete_case_study, get_case_study, list_case_studies,
+            update_case_study,
+        },
+        contact::{
+            get_disposable_domains, handle_message, issue_contact_challenge, issue_contact_token,
+            label_message, list_quarantine, reject_quarantined, release_quarantined,
+            reload_disposable_domains,
+        },
+        email_templates::{list_email_templates, preview_email_template},
+        health::check,
+        i18n::{
+            delete_translation, get_negotiated_translations, get_translations, update_translation,
+        },
+        image_proxy::get_image,
+        placeholder::get_placeholder,
+        posts::{
+            create_post, delete_post, get_highlight_css, get_post, list_post_tags, list_posts,
+            update_post,
+        },
+        projects::{get_projects, update_project},
+        rss::{get_featured, get_feeds, get_related, moderate_item},
+    },
+    services::{
+        analytics::AnalyticsService,
+        case_studies::CaseStudyService,
+        contact::MessageService,
+        db,
+        email_queue::{EmailOutbox, EmailWorker, RetryPolicy},
+        email_transports,
+        i18n::I18nService,
+        image_proxy::ImageProxy,
+        link_checker::LinkChecker,
+        posts::PostService,
+        projects::ProjectService,
+        rss::FeedService,
+    },
     AppState,
 };
 use std::sync::Arc;
@@ -25,6 +66,12 @@ use std::{net::SocketAddr, time::Duration};
 use tower_http::{cors::CorsLayer, trace::TraceLayer};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
+/// Content Security Policy of the responses
+const CONTENT_SECURITY_POLICY: &str = "default-src 'self'; script-src 'self' 'unsafe-inline'; style-src 'self' 'unsafe-inline'; img-src 'self' data: https:; connect-src 'self'";
+
+/// Content Security Policy when article images are served by the image proxy
+const CONTENT_SECURITY_POLICY_IMAGE_PROXY: &str = "default-src 'self'; script-src 'self' 'unsafe-inline'; style-src 'self' 'unsafe-inline'; img-src 'self' data:; connect-src 'self'";
+
 /// Adds security headers to HTTP responses for enhanced protection
 ///
 /// # Security Headers
@@ -34,11 +81,15 @@ use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 /// - `Content-Security-Policy` - Restricts resources to trusted sources
 ///
 /// # Arguments
+/// * `csp` - Content Security Policy, narrowed to local images with the image proxy
 /// * `response` - Original Axum response to modify
 ///
 /// # Returns
 /// Modified response with security headers
-async fn add_security_headers(mut response: axum::response::Response) -> axum::response::Response {
+async fn add_security_headers(
+    State(csp): State<&'static str>,
+    mut response: axum::response::Response,
+) -> axum::response::Response {
     let headers = response.headers_mut();
     headers.insert("X-Frame-Options", HeaderValue::from_static("DENY"));
     headers.insert(
@@ -49,12 +100,7 @@ async fn add_security_headers(mut response: axum::response::Response) -> axum::r
         "X-XSS-Protection",
         HeaderValue::from_static("1; mode=block"),
     );
-    headers.insert(
-        "Content-Security-Policy",
-        HeaderValue::from_static(
-            "default-src 'self'; script-src 'self' 'unsafe-inline'; style-src 'self' 'unsafe-inline'; img-src 'self' data: https:; connect-src 'self'",
-        ),
-    );
+    headers.insert("Content-Security-Policy", HeaderValue::from_static(csp));
     response
 }
 
@@ -88,12 +134,23 @@ async fn main() {
             frontend_url.parse().unwrap(),
             format!("{frontend_url}/").parse().unwrap(),
         ])
-        .allow_methods([Method::GET, Method::POST, Method::OPTIONS])
+        .allow_methods([
+            Method::GET,
+            Method::POST,
+            Method::PUT,
+            Method::PATCH,
+            Method::DELETE,
+            Method::OPTIONS,
+        ])
         .allow_headers([
             HeaderName::from_static("content-type"),
             HeaderName::from_static("authorization"),
             HeaderName::from_static("x-requested-with"),
             HeaderName::from_static("accept"),
+            HeaderName::from_static("accept-language"),
+            HeaderName::from_static("if-none-match"),
+            HeaderName::from_static("dnt"),
+            HeaderName::from_static("sec-gpc"),
             HeaderName::from_static("origin"),
             HeaderName::from_static("referer"),
             HeaderName::from_static("user-agent"),
@@ -114,7 +171,43 @@ async fn main() {
     let rate_limiter = RateLimiter::new(100, Duration::from_secs(60));
 
     let feed_service = Arc::new(FeedService::new(db.clone(), config.clone()));
-    let message_service = Arc::new(MessageService::new(db, config.clone()));
+    let message_service = Arc::new(MessageService::new(db.clone(), config.clone()));
+    let email_templates = message_service.templates();
+
+    // Contact emails are delivered in the background from the MongoDB outbox
+    let outbox = Arc::new(EmailOutbox::new(
+        db.clone(),
+        RetryPolicy::from_config(&config),
+    ));
+    let email_transport =
+        email_transports::from_config(&config).expect("Failed to configure the email transport");
+    tracing::info!("Email transport: {}", email_transport.name());
+    tokio::spawn(EmailWorker::new(outbox, email_transport).run());
+
+    // Background dead link and image checks
+    if config.link_check_interval_hours > 0 {
+        let checker = LinkChecker::new(db.clone(), &config);
+        tokio::spawn(
+            checker.run_periodically(Duration::from_secs(config.link_check_interval_hours * 3600)),
+        );
+    }
+
+    // GitHub projects showcase, synced in the background when a user is configured
+    let project_service = Arc::new(ProjectService::new(db.clone(), config.clone()));
+    if config.github_username.is_some() {
+        tokio::spawn(
+            project_service
+                .clone()
+                .run_periodically(Duration::from_secs(
+                    config.github_sync_interval_hours.max(1) * 3600,
+                )),
+        );
+    }
+
+    let case_study_service = Arc::new(CaseStudyService::new(db.clone()));
+    let post_service = Arc::new(PostService::new(db.clone()));
+    let i18n_service = Arc::new(I18nService::new(db.clone(), config.i18n_locales.clone()));
+    let analytics_service = Arc::new(AnalyticsService::new(db.clone(), &config.frontend_url));
 
     // Configure logging
     let trace_layer = TraceLayer::new_for_http()
@@ -142,19 +235,160 @@ async fn main() {
         message_service: message_service.clone(),
     });
 
+    // Administration routes, protected by the ADMIN_API_TOKEN bearer token
+    let admin_auth = AdminAuth::new(config.admin_api_token.clone());
+    let admin_routes = Router::new()
+        .route(
+            "/rss/items/{id}",
+            patch(moderate_item).with_state(feed_service.clone()),
+        )
+        .route(
+            "/projects/{name}",
+            patch(update_project).with_state(project_service.clone()),
+        )
+        .route(
+            "/case-studies",
+            post(create_case_study).with_state(case_study_service.clone()),
+        )
+        .route(
+            "/case-studies/{slug}",
+            put(update_case_study)
+                .delete(delete_case_study)
+                .with_state(case_study_service.clone()),
+        )
+        .route("/posts", post(create_post).with_state(post_service.clone()))
+        .route(
+            "/posts/{slug}",
+            put(update_post)
+                .delete(delete_post)
+                .with_state(post_service.clone()),
+        )
+        .route(
+            "/i18n/{locale}/{namespace}/{key}",
+            put(update_translation)
+                .delete(delete_translation)
+                .with_state(i18n_service.clone()),
+        )
+        .route(
+            "/analytics/stats",
+            get(get_stats).with_state(analytics_service.clone()),
+        )
+        .route(
+            "/email-templates",
+            get(list_email_templates).with_state(email_templates.clone()),
+        )
+        .route(
+            "/email-templates/{name}/preview",
+            get(preview_email_template).with_state(email_templates),
+        )
+        .route(
+            "/contact/quarantine",
+            get(list_quarantine).with_state(message_service.clone()),
+        )
+        .route(
+            "/contact/quarantine/{id}",
+            delete(reject_quarantined).with_state(message_service.clone()),
+        )
+        .route(
+            "/contact/quarantine/{id}/release",
+            post(release_quarantined).with_state(message_service.clone()),
+        )
+        .route(
+            "/contact/messages/{id}/label",
+            put(label_message).with_state(message_service.clone()),
+        )
+        .route(
+            "/contact/disposable-domains",
+            get(get_disposable_domains).with_state(message_service.clone()),
+        )
+        .route(
+            "/contact/disposable-domains/reload",
+            post(reload_disposable_domains).with_state(message_service.clone()),
+        )
+        .route_layer(admin_auth);
+
+    // Image proxy, enabled when a cache directory is configured
+    let csp = if config.image_proxy_cache_dir.is_some() {
+        CONTENT_SECURITY_POLICY_IMAGE_PROXY
+    } else {
+        CONTENT_SECURITY_POLICY
+    };
+    let image_routes = match &config.image_proxy_cache_dir {
+        Some(cache_dir) => {
+            let proxy = ImageProxy::new(
+                db.clone(),
+                cache_dir,
+                config.image_proxy_cache_max_mb.saturating_mul(1024 * 1024),
+            )
+            .expect("Failed to open image proxy cache");
+            Router::new().route("/img/{hash}", get(get_image).with_state(Arc::new(proxy)))
+        }
+        None => Router::new(),
+    };
+
     let app = Router::new()
         .route("/health", get(check))
         .nest(
             "/api",
             Router::new()
-                .route("/rss", get(get_feeds).with_state(feed_service))
-                .route("/contact", post(handle_message).with_state(message_service)),
+                .route("/rss", get(get_feeds).with_state(feed_service.clone()))
+                .route(
+                    "/rss/featured",
+                    get(get_featured).with_state(feed_service.clone()),
+                )
+                .route(
+                    "/rss/items/{id}/related",
+                    get(get_related).with_state(feed_service),
+                )
+                .route("/placeholder", get(get_placeholder))
+                .route("/projects", get(get_projects).with_state(project_service))
+                .route(
+                    "/case-studies",
+                    get(list_case_studies).with_state(case_study_service.clone()),
+                )
+                .route(
+                    "/case-studies/{slug}",
+                    get(get_case_study).with_state(case_study_service),
+                )
+                .route("/posts", get(list_posts).with_state(post_service.clone()))
+                .route(
+                    "/posts/tags",
+                    get(list_post_tags).with_state(post_service.clone()),
+                )
+                .route("/posts/highlight.css", get(get_highlight_css))
+                .route("/posts/{slug}", get(get_post).with_state(post_service))
+                .route(
+                    "/i18n",
+                    get(get_negotiated_translations).with_state(i18n_service.clone()),
+                )
+                .route(
+                    "/i18n/{locale}",
+                    get(get_translations).with_state(i18n_service),
+                )
+                .route(
+                    "/analytics/event",
+                    post(record_event).with_state(analytics_service),
+                )
+                .route(
+                    "/contact/token",
+                    get(issue_contact_token).with_state(message_service.clone()),
+                )
+                .route(
+                    "/contact/challenge",
+                    get(issue_contact_challenge).with_state(message_service.clone()),
+                )
+                .route("/contact", post(handle_message).with_state(message_service))
+                .merge(image_routes)
+                .merge(admin_routes),
         )
         .with_state(state)
         .layer(cors)
         .layer(trace_layer)
         .layer(rate_limiter)
-        .layer(axum::middleware::map_response(add_security_headers))
+        .layer(axum::middleware::map_response_with_state(
+            csp,
+            add_security_headers,
+        ))
         .layer(MongoSanitizer);
 
     let addr = SocketAddr::from(([0, 0, 0, 0], config.port));
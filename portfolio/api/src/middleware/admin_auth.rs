@@ -0,0 +1,129 @@
+use axum::{
+    body::Body,
+    http::{header, Request, Response, StatusCode},
+};
+use futures_util::future::BoxFuture;
+use std::sync::Arc;
+use tower::Service;
+
+/// Bearer token authentication for administration routes
+///
+/// Requests must carry `Authorization: Bearer <ADMIN_API_TOKEN>`. When no token
+/// is configured every request is rejected, which disables the admin routes.
+#[derive(Clone)]
+pub struct AdminAuth {
+    token: Option<Arc<str>>,
+}
+
+impl AdminAuth {
+    #[must_use]
+    pub fn new(token: Option<String>) -> Self {
+        Self {
+            token: token.filter(|t| !t.is_empty()).map(Arc::from),
+        }
+    }
+
+    /// Checks the `Authorization` header against the configured token
+    #[must_use]
+    pub fn is_authorized(&self, authorization: Option<&str>) -> bool {
+        let (Some(expected), Some(provided)) = (
+            self.token.as_deref(),
+            authorization.and_then(|value| value.strip_prefix("Bearer ")),
+        ) else {
+            return false;
+        };
+
+        constant_time_eq(expected.as_bytes(), provided.trim().as_bytes())
+    }
+}
+
+/// Compares two byte strings without short-circuiting on the first difference
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+impl<S> tower::Layer<S> for AdminAuth {
+    type Service = AdminAuthMiddleware<S>;
+
+    fn layer(&self, service: S) -> Self::Service {
+        AdminAuthMiddleware {
+            inner: service,
+            auth: self.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct AdminAuthMiddleware<S> {
+    inner: S,
+    auth: AdminAuth,
+}
+
+impl<S> Service<Request<Body>> for AdminAuthMiddleware<S>
+where
+    S: Service<Request<Body>, Response = Response<Body>> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(
+        &mut self,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request<Body>) -> Self::Future {
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let auth = self.auth.clone();
+
+        Box::pin(async move {
+            let authorization = request
+                .headers()
+                .get(header::AUTHORIZATION)
+                .and_then(|value| value.to_str().ok());
+
+            if !auth.is_authorized(authorization) {
+                return Ok(Response::builder()
+                    .status(StatusCode::UNAUTHORIZED)
+                    .header(header::WWW_AUTHENTICATE, "Bearer")
+                    .body(Body::from("Authentification requise"))
+                    .unwrap());
+            }
+
+            inner.call(request).await
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_valid_token() {
+        let auth = AdminAuth::new(Some("secret".to_string()));
+        assert!(auth.is_authorized(Some("Bearer secret")));
+    }
+
+    #[test]
+    fn test_invalid_token() {
+        let auth = AdminAuth::new(Some("secret".to_string()));
+        assert!(!auth.is_authorized(Some("Bearer secreT")));
+        assert!(!auth.is_authorized(Some("secret")));
+        assert!(!auth.is_authorized(None));
+    }
+
+    #[test]
+    fn test_disabled_without_token() {
+        let auth = AdminAuth::new(Some(String::new()));
+        assert!(!auth.is_authorized(Some("Bearer ")));
+        assert!(!AdminAuth::new(None).is_authorized(Some("Bearer secret")));
+    }
+}
@@ -1,5 +1,7 @@
+pub mod admin_auth;
 pub mod mongo_sanitizer;
 pub mod rate_limit;
 
+pub use admin_auth::AdminAuth;
 pub use mongo_sanitizer::MongoSanitizer;
 pub use rate_limit::RateLimiter;
@@ -0,0 +1,75 @@
+use serde::{Deserialize, Serialize};
+
+/// Kind of tracked event
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventKind {
+    PageView,
+    /// Click on a link leaving the site, such as an aggregated RSS item
+    OutboundClick,
+}
+
+impl EventKind {
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::PageView => "page_view",
+            Self::OutboundClick => "outbound_click",
+        }
+    }
+}
+
+/// Event sent by the front-end
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnalyticsEvent {
+    #[serde(rename = "type")]
+    pub kind: EventKind,
+    /// Path of the page, query string and fragment are dropped
+    pub path: String,
+    /// `document.referrer`, only its host is kept
+    #[serde(default)]
+    pub referrer: Option<String>,
+    /// Destination of an outbound click
+    #[serde(default)]
+    pub target: Option<String>,
+}
+
+/// Totals of a day
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DayStats {
+    pub day: String,
+    pub page_views: u64,
+    pub visitors: u64,
+    pub outbound_clicks: u64,
+}
+
+/// Totals of a page over the period
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PageStats {
+    pub path: String,
+    pub page_views: u64,
+    /// Sum of the daily unique visitors
+    pub visitors: u64,
+}
+
+/// Count of a referrer host or outbound target
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CountStats {
+    pub name: String,
+    pub count: u64,
+}
+
+/// Traffic over a period of days, both included
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AnalyticsStats {
+    pub from: String,
+    pub to: String,
+    pub page_views: u64,
+    /// Sum of the daily unique visitors
+    pub visitors: u64,
+    pub outbound_clicks: u64,
+    pub days: Vec<DayStats>,
+    pub top_pages: Vec<PageStats>,
+    pub top_referrers: Vec<CountStats>,
+    pub top_outbound: Vec<CountStats>,
+}
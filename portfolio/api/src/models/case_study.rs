@@ -0,0 +1,191 @@
+use chrono::{DateTime, Utc};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+static SLUG_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"^[a-z0-9]+(?:-[a-z0-9]+)*$").unwrap());
+
+/// Publication state of a case study
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CaseStudyStatus {
+    /// Only visible to the administration
+    #[default]
+    Draft,
+    /// Listed by the public endpoints
+    Published,
+}
+
+impl CaseStudyStatus {
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Draft => "draft",
+            Self::Published => "published",
+        }
+    }
+}
+
+/// External link of a case study (demo, repository, article…)
+#[derive(Debug, Clone, Default, Serialize, Deserialize, Validate)]
+pub struct CaseStudyLink {
+    #[validate(length(
+        min = 1,
+        max = 50,
+        message = "Le libellé du lien doit faire entre 1 et 50 caractères"
+    ))]
+    pub label: String,
+    #[validate(url(message = "L'URL du lien n'est pas valide"))]
+    #[validate(custom(
+        function = "validate_http_url",
+        message = "L'URL du lien doit être en http(s)"
+    ))]
+    pub url: String,
+}
+
+/// Hand-curated case study shown in the portfolio
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CaseStudy {
+    pub slug: String,
+    pub title: String,
+    pub summary: String,
+    /// Sanitized HTML rendered from the Markdown body, only set on detail responses
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body_html: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub images: Vec<String>,
+    #[serde(default)]
+    pub links: Vec<CaseStudyLink>,
+    /// Display order, lower first
+    pub position: i32,
+    pub status: CaseStudyStatus,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub published_at: Option<DateTime<Utc>>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Case study as sent by the administration on create and update
+#[derive(Debug, Clone, Default, Serialize, Deserialize, Validate)]
+pub struct CaseStudyInput {
+    #[validate(length(
+        min = 2,
+        max = 150,
+        message = "Le titre doit faire entre 2 et 150 caractères"
+    ))]
+    pub title: String,
+
+    /// Derived from the title when missing
+    #[serde(default)]
+    #[validate(length(max = 100, message = "Le slug est trop long"))]
+    #[validate(custom(
+        function = "validate_slug",
+        message = "Le slug ne doit contenir que des minuscules, chiffres et tirets"
+    ))]
+    pub slug: Option<String>,
+
+    #[serde(default)]
+    #[validate(length(max = 500, message = "Le résumé est trop long"))]
+    pub summary: String,
+
+    /// Markdown source
+    #[serde(default)]
+    #[validate(length(max = 100_000, message = "Le contenu est trop long"))]
+    pub body: String,
+
+    #[serde(default)]
+    #[validate(length(max = 20, message = "Trop de tags"))]
+    #[validate(custom(function = "validate_tags", message = "Tag invalide"))]
+    pub tags: Vec<String>,
+
+    #[serde(default)]
+    #[validate(length(max = 20, message = "Trop d'images"))]
+    #[validate(custom(function = "validate_urls", message = "URL d'image invalide"))]
+    pub images: Vec<String>,
+
+    #[serde(default)]
+    #[validate(length(max = 10, message = "Trop de liens"))]
+    #[validate(nested)]
+    pub links: Vec<CaseStudyLink>,
+
+    #[serde(default)]
+    pub position: i32,
+
+    #[serde(default)]
+    pub status: CaseStudyStatus,
+}
+
+pub(crate) fn validate_slug(slug: &str) -> Result<(), validator::ValidationError> {
+    if SLUG_RE.is_match(slug) {
+        Ok(())
+    } else {
+        Err(validator::ValidationError::new("slug_invalide"))
+    }
+}
+
+fn validate_tags(tags: &[String]) -> Result<(), validator::ValidationError> {
+    if tags
+        .iter()
+        .all(|tag| !tag.trim().is_empty() && tag.chars().count() <= 30)
+    {
+        Ok(())
+    } else {
+        Err(validator::ValidationError::new("tag_invalide"))
+    }
+}
+
+pub(crate) fn validate_http_url(url: &str) -> Result<(), validator::ValidationError> {
+    if url.starts_with("https://") || url.starts_with("http://") {
+        Ok(())
+    } else {
+        Err(validator::ValidationError::new("url_invalide"))
+    }
+}
+
+fn validate_urls(urls: &[String]) -> Result<(), validator::ValidationError> {
+    if urls.iter().all(|url| validate_http_url(url).is_ok()) {
+        Ok(())
+    } else {
+        Err(validator::ValidationError::new("url_invalide"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input() -> CaseStudyInput {
+        CaseStudyInput {
+            title: "Refonte du portfolio".to_string(),
+            tags: vec!["rust".to_string()],
+            images: vec!["https://example.com/cover.png".to_string()],
+            links: vec![CaseStudyLink {
+                label: "Démo".to_string(),
+                url: "https://example.com".to_string(),
+            }],
+            ..CaseStudyInput::default()
+        }
+    }
+
+    #[test]
+    fn test_valid_input() {
+        assert!(input().validate().is_ok());
+    }
+
+    #[test]
+    fn test_invalid_slug_and_links() {
+        let mut invalid = input();
+        invalid.slug = Some("Not A Slug".to_string());
+        assert!(invalid.validate().is_err());
+
+        let mut invalid = input();
+        invalid.images = vec!["javascript:alert(1)".to_string()];
+        assert!(invalid.validate().is_err());
+
+        let mut invalid = input();
+        invalid.links[0].url = "javascript:alert(1)".to_string();
+        assert!(invalid.validate().is_err());
+    }
+}
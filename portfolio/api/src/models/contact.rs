@@ -1,3 +1,4 @@
+use chrono::{DateTime, Utc};
 use once_cell::sync::Lazy;
 use regex::Regex;
 use serde::{Deserialize, Serialize};
@@ -47,6 +48,53 @@ pub struct Request {
 
     #[serde(default)]
     pub test_name: Option<String>,
+
+    /// Language of the form (`fr`, `en-GB`…), used for the acknowledgment email
+    #[serde(default)]
+    #[validate(length(max = 35, message = "La langue n'est pas valide"))]
+    pub language: Option<String>,
+
+    /// Signed token of `GET /api/contact/token`
+    #[serde(default)]
+    pub token: Option<String>,
+
+    /// Honeypot, hidden from visitors: a filled value reveals a bot
+    #[serde(default)]
+    pub website: Option<String>,
+
+    /// Proof-of-work challenge of `GET /api/contact/challenge`
+    #[serde(default)]
+    pub challenge: Option<String>,
+
+    /// Solution found by the browser for `challenge`
+    #[serde(default)]
+    pub proof: Option<String>,
+}
+
+impl Request {
+    /// Returns true when the honeypot field was filled
+    #[must_use]
+    pub fn is_bot(&self) -> bool {
+        self.website
+            .as_deref()
+            .is_some_and(|website| !website.trim().is_empty())
+    }
+}
+
+/// Contact message held back by the spam filter, as listed to the administration
+#[derive(Debug, Clone, Serialize)]
+pub struct QuarantinedMessage {
+    pub id: String,
+    pub reference: String,
+    pub name: String,
+    pub email: String,
+    pub subject: String,
+    pub message: String,
+    pub language: Option<String>,
+    pub spam_score: f64,
+    /// Signals behind the score, such as `keyword:casino`
+    pub spam_reasons: Vec<String>,
+    pub created_at: DateTime<Utc>,
 }
 
 static NAME_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"^[\p{L}\s\-']+$").unwrap());
@@ -110,6 +158,11 @@ mod tests {
             message: "This is a valid message with good content.".to_string(),
             is_test: false,
             test_name: None,
+            language: None,
+            token: None,
+            website: None,
+            challenge: None,
+            proof: None,
         };
         assert!(request.validate().is_ok());
     }
@@ -123,6 +176,11 @@ mod tests {
             message: "Valid message".to_string(),
             is_test: false,
             test_name: None,
+            language: None,
+            token: None,
+            website: None,
+            challenge: None,
+            proof: None,
         };
         assert!(request.validate().is_err());
     }
@@ -136,6 +194,11 @@ mod tests {
             message: "Valid message".to_string(),
             is_test: false,
             test_name: None,
+            language: None,
+            token: None,
+            website: None,
+            challenge: None,
+            proof: None,
         };
         assert!(request.validate().is_err());
     }
@@ -149,6 +212,11 @@ mod tests {
             message: "Valid message".to_string(),
             is_test: false,
             test_name: None,
+            language: None,
+            token: None,
+            website: None,
+            challenge: None,
+            proof: None,
         };
         assert!(request.validate().is_err());
     }
@@ -162,6 +230,11 @@ mod tests {
             message: "Message with <script>alert('xss')</script>".to_string(),
             is_test: false,
             test_name: None,
+            language: None,
+            token: None,
+            website: None,
+            challenge: None,
+            proof: None,
         };
         assert!(request.validate().is_err());
     }
@@ -176,6 +249,11 @@ mod tests {
                 .to_string(),
             is_test: false,
             test_name: None,
+            language: None,
+            token: None,
+            website: None,
+            challenge: None,
+            proof: None,
         };
         assert!(request.validate().is_err());
     }
@@ -189,6 +267,11 @@ mod tests {
             message: "a".repeat(51),
             is_test: false,
             test_name: None,
+            language: None,
+            token: None,
+            website: None,
+            challenge: None,
+            proof: None,
         };
         assert!(request.validate().is_err());
     }
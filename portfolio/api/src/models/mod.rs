@@ -1,2 +1,6 @@
+pub mod analytics;
+pub mod case_study;
 pub mod contact;
+pub mod post;
+pub mod project;
 pub mod rss;
@@ -0,0 +1,94 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+/// Publication state of a blog post
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PostStatus {
+    /// `draft: true` in the front matter
+    Draft,
+    /// Publication date in the future
+    Scheduled,
+    Published,
+}
+
+/// Blog post rendered from its Markdown source
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Post {
+    pub slug: String,
+    pub title: String,
+    pub summary: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub image_url: Option<String>,
+    /// Sanitized and highlighted HTML, only set on detail responses
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_html: Option<String>,
+    pub reading_time_minutes: u32,
+    pub status: PostStatus,
+    /// Publication date, missing for drafts never given one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub published_at: Option<DateTime<Utc>>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Number of published posts of a tag
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TagCount {
+    pub tag: String,
+    pub count: u32,
+}
+
+/// YAML front matter opening a post source
+///
+/// ```markdown
+/// ---
+/// title: Hello
+/// date: 2024-05-01T08:00:00Z
+/// tags: [rust, axum]
+/// ---
+/// Body in **Markdown**
+/// ```
+#[derive(Debug, Clone, Default, Deserialize, Validate)]
+pub struct FrontMatter {
+    #[validate(length(
+        min = 2,
+        max = 150,
+        message = "Le titre doit faire entre 2 et 150 caractères"
+    ))]
+    pub title: String,
+
+    /// Derived from the title when missing
+    #[serde(default)]
+    #[validate(length(max = 100, message = "Le slug est trop long"))]
+    #[validate(custom(
+        function = "crate::models::case_study::validate_slug",
+        message = "Le slug ne doit contenir que des minuscules, chiffres et tirets"
+    ))]
+    pub slug: Option<String>,
+
+    /// Publication date, RFC 3339 or `YYYY-MM-DD`; the post is scheduled when in the future
+    #[serde(default)]
+    pub date: Option<String>,
+
+    #[serde(default)]
+    pub draft: bool,
+
+    #[serde(default)]
+    #[validate(length(max = 20, message = "Trop de tags"))]
+    pub tags: Vec<String>,
+
+    /// Derived from the first paragraph when missing
+    #[serde(default)]
+    #[validate(length(max = 500, message = "Le résumé est trop long"))]
+    pub summary: Option<String>,
+
+    #[serde(default)]
+    #[validate(custom(
+        function = "crate::models::case_study::validate_http_url",
+        message = "L'URL de l'image doit être en http(s)"
+    ))]
+    pub image: Option<String>,
+}
@@ -0,0 +1,33 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Public repository shown in the projects showcase
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Project {
+    /// Repository name, also used as identifier
+    pub name: String,
+    /// Custom blurb when set, GitHub description otherwise
+    pub description: String,
+    pub url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub homepage: Option<String>,
+    pub stars: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    #[serde(default)]
+    pub topics: Vec<String>,
+    pub pushed_at: DateTime<Utc>,
+    #[serde(default)]
+    pub pinned: bool,
+}
+
+/// Manual overrides of a project, kept across GitHub syncs
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProjectOverride {
+    /// Removes the project from the showcase
+    pub hidden: Option<bool>,
+    /// Sorts the project before the others
+    pub pinned: Option<bool>,
+    /// Replaces the GitHub description, an empty blurb restores it
+    pub blurb: Option<String>,
+}
@@ -39,11 +39,42 @@ pub struct ParsedItem {
     pub pub_date: Option<DateTime<Utc>>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct RssItem {
+    /// Stable identifier derived from the article URL
+    #[serde(default)]
+    pub id: String,
     pub title: String,
     pub url: String,
     pub pub_date: DateTime<Utc>,
     pub description: String,
     pub image_url: String,
+    /// Site icon read from the article page, when enrichment is enabled
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icon_url: Option<String>,
+    /// Image size and previews, when image metadata is enabled
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub image_width: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub image_height: Option<u32>,
+    /// Dominant color of the image, as `#rrggbb`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dominant_color: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub blurhash: Option<String>,
+    #[serde(default)]
+    pub pinned: bool,
+    #[serde(default)]
+    pub featured: bool,
+}
+
+/// Editorial flags applied to an aggregated item, kept across re-syncs
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ModerationUpdate {
+    /// Removes the item from every public listing
+    pub hidden: Option<bool>,
+    /// Sorts the item before the others in the main listing
+    pub pinned: Option<bool>,
+    /// Lists the item in `/api/rss/featured`
+    pub featured: Option<bool>,
 }
@@ -0,0 +1,100 @@
+use axum::{
+    extract::{ConnectInfo, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::{Days, NaiveDate, Utc};
+use serde::Deserialize;
+use serde_json::json;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use crate::models::analytics::AnalyticsEvent;
+use crate::services::analytics::{self, AnalyticsService};
+
+/// Days covered by the stats when no period is given
+const DEFAULT_PERIOD_DAYS: u64 = 30;
+/// Longest period of the stats
+const MAX_PERIOD_DAYS: i64 = 366;
+
+#[derive(Debug, Deserialize)]
+pub struct StatsQuery {
+    /// First day (`YYYY-MM-DD`), 30 days before `to` by default
+    pub from: Option<NaiveDate>,
+    /// Last day (`YYYY-MM-DD`), today by default
+    pub to: Option<NaiveDate>,
+}
+
+fn header_str<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers.get(name).and_then(|v| v.to_str().ok())
+}
+
+/// Counts a page view or an outbound click
+///
+/// Always answers `204 No Content` for valid events, including the ones that
+/// are ignored because of `DNT`/`Sec-GPC` or a crawler user agent.
+pub async fn record_event(
+    State(service): State<Arc<AnalyticsService>>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    Json(event): Json<AnalyticsEvent>,
+) -> Response {
+    let event = match analytics::normalize(&event, service.site_host()) {
+        Ok(event) => event,
+        Err(message) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(json!({
+                    "status": "error",
+                    "message": message
+                })),
+            )
+                .into_response()
+        }
+    };
+
+    let user_agent = header_str(&headers, header::USER_AGENT.as_str()).unwrap_or_default();
+    if analytics::opted_out(header_str(&headers, "dnt"), header_str(&headers, "sec-gpc"))
+        || analytics::is_bot(user_agent)
+    {
+        return StatusCode::NO_CONTENT.into_response();
+    }
+
+    if let Err(e) = service.record(&event, addr.ip(), user_agent).await {
+        tracing::error!("Error recording analytics event: {}", e);
+        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    }
+    StatusCode::NO_CONTENT.into_response()
+}
+
+/// Traffic of a period, for the administration
+pub async fn get_stats(
+    State(service): State<Arc<AnalyticsService>>,
+    Query(query): Query<StatsQuery>,
+) -> Response {
+    let to = query.to.unwrap_or_else(|| Utc::now().date_naive());
+    let from = query.from.unwrap_or_else(|| {
+        to.checked_sub_days(Days::new(DEFAULT_PERIOD_DAYS - 1))
+            .unwrap_or(to)
+    });
+    let length = (to - from).num_days();
+    if !(0..MAX_PERIOD_DAYS).contains(&length) {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(json!({
+                "status": "error",
+                "message": "Période invalide"
+            })),
+        )
+            .into_response();
+    }
+
+    match service.stats(from, to).await {
+        Ok(stats) => Json(stats).into_response(),
+        Err(e) => {
+            tracing::error!("Error computing analytics stats: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
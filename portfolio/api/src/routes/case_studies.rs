@@ -0,0 +1,123 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Deserialize;
+use serde_json::json;
+use std::sync::Arc;
+
+use crate::models::case_study::CaseStudyInput;
+use crate::services::case_studies::CaseStudyService;
+use crate::services::db::is_duplicate_key;
+
+#[derive(Debug, Deserialize)]
+pub struct CaseStudyQuery {
+    pub tag: Option<String>,
+}
+
+fn not_found() -> Response {
+    (
+        StatusCode::NOT_FOUND,
+        Json(json!({
+            "status": "error",
+            "message": "Projet introuvable"
+        })),
+    )
+        .into_response()
+}
+
+/// Maps a service error: 400 for invalid input, 409 for a used slug, 500 otherwise
+fn error_response(slug: &str, e: &anyhow::Error) -> Response {
+    let (status, message) = if e.downcast_ref::<validator::ValidationErrors>().is_some() {
+        (StatusCode::BAD_REQUEST, e.to_string())
+    } else if is_duplicate_key(e) {
+        (StatusCode::CONFLICT, "Ce slug est déjà utilisé".to_string())
+    } else {
+        tracing::error!("Error saving case study {}: {}", slug, e);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Erreur lors de l'enregistrement du projet".to_string(),
+        )
+    };
+
+    (
+        status,
+        Json(json!({
+            "status": "error",
+            "message": message
+        })),
+    )
+        .into_response()
+}
+
+pub async fn list_case_studies(
+    State(service): State<Arc<CaseStudyService>>,
+    Query(query): Query<CaseStudyQuery>,
+) -> Response {
+    match service.list(query.tag.as_deref()).await {
+        Ok(case_studies) => Json(case_studies).into_response(),
+        Err(e) => {
+            tracing::error!("Error listing case studies: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+pub async fn get_case_study(
+    State(service): State<Arc<CaseStudyService>>,
+    Path(slug): Path<String>,
+) -> Response {
+    match service.get(&slug).await {
+        Ok(Some(case_study)) => Json(case_study).into_response(),
+        Ok(None) => not_found(),
+        Err(e) => {
+            tracing::error!("Error fetching case study {}: {}", slug, e);
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+pub async fn create_case_study(
+    State(service): State<Arc<CaseStudyService>>,
+    Json(input): Json<CaseStudyInput>,
+) -> Response {
+    match service.create(&input).await {
+        Ok(case_study) => (StatusCode::CREATED, Json(case_study)).into_response(),
+        Err(e) => error_response(input.slug.as_deref().unwrap_or(&input.title), &e),
+    }
+}
+
+pub async fn update_case_study(
+    State(service): State<Arc<CaseStudyService>>,
+    Path(slug): Path<String>,
+    Json(input): Json<CaseStudyInput>,
+) -> Response {
+    match service.update(&slug, &input).await {
+        Ok(Some(case_study)) => Json(case_study).into_response(),
+        Ok(None) => not_found(),
+        Err(e) => error_response(&slug, &e),
+    }
+}
+
+pub async fn delete_case_study(
+    State(service): State<Arc<CaseStudyService>>,
+    Path(slug): Path<String>,
+) -> Response {
+    match service.delete(&slug).await {
+        Ok(true) => (
+            StatusCode::OK,
+            Json(json!({
+                "status": "success",
+                "message": "Projet supprimé"
+            })),
+        )
+            .into_response(),
+        Ok(false) => not_found(),
+        Err(e) => {
+            tracing::error!("Error deleting case study {}: {}", slug, e);
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
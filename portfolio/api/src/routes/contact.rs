@@ -1,23 +1,184 @@
-use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
+use axum::{
+    extract::{ConnectInfo, Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use mongodb::bson::oid::ObjectId;
+use serde::Deserialize;
 use serde_json::json;
+use std::net::SocketAddr;
 use std::sync::Arc;
 
 use crate::models::contact::Request;
 use crate::services::contact::MessageService;
+use crate::services::contact_throttle::{throttled_message, Throttled};
+use crate::services::form_token::TokenError;
+use crate::services::proof_of_work::ChallengeError;
+
+#[derive(Debug, Deserialize)]
+pub struct QuarantineQuery {
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LabelRequest {
+    /// True for spam, false for a legitimate message
+    pub spam: bool,
+}
+
+fn error(status: StatusCode, message: &str) -> Response {
+    (
+        status,
+        Json(json!({
+            "status": "error",
+            "message": message
+        })),
+    )
+        .into_response()
+}
+
+fn success() -> axum::response::Response {
+    (
+        StatusCode::OK,
+        Json(json!({
+            "status": "success",
+            "message": "Message envoyé avec succès"
+        })),
+    )
+        .into_response()
+}
+
+/// Issues the signed token the contact form must send back
+pub async fn issue_contact_token(State(state): State<Arc<MessageService>>) -> impl IntoResponse {
+    let tokens = state.tokens();
+    (
+        [(header::CACHE_CONTROL, "no-store")],
+        Json(json!({
+            "token": tokens.issue(),
+            "min_fill_secs": tokens.min_fill().as_secs(),
+            "expires_in": tokens.max_age().as_secs(),
+        })),
+    )
+}
+
+/// Issues a proof-of-work challenge, harder while contact volume spikes
+pub async fn issue_contact_challenge(State(state): State<Arc<MessageService>>) -> Response {
+    let challenges = state.challenges();
+    match challenges.issue().await {
+        Ok((challenge, difficulty)) => (
+            [(header::CACHE_CONTROL, "no-store")],
+            Json(json!({
+                "challenge": challenge,
+                "algorithm": "sha256",
+                "difficulty": difficulty,
+                "expires_in": challenges.ttl().as_secs(),
+            })),
+        )
+            .into_response(),
+        Err(e) => {
+            tracing::error!("Error issuing contact challenge: {:#}", e);
+            error(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Erreur lors de la génération du défi anti-spam",
+            )
+        }
+    }
+}
+
+/// 429 response of a throttled submission, localized for the visitor
+fn too_many_messages(throttled: Throttled, locale: &str) -> Response {
+    // Rounded up, so that a retry at the given time is accepted
+    let retry_after =
+        throttled.retry_after.as_secs() + u64::from(throttled.retry_after.subsec_nanos() > 0);
+    (
+        StatusCode::TOO_MANY_REQUESTS,
+        [(header::RETRY_AFTER, retry_after.to_string())],
+        Json(json!({
+            "status": "error",
+            "message": throttled_message(locale),
+            "retry_after": retry_after
+        })),
+    )
+        .into_response()
+}
 
 pub async fn handle_message(
     State(state): State<Arc<MessageService>>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
     Json(form): Json<Request>,
 ) -> impl IntoResponse {
-    match state.submit_contact(form).await {
-        Ok(()) => (
-            StatusCode::OK,
+    // Bots filling the honeypot are told the message was sent
+    if form.is_bot() {
+        tracing::info!("Contact message dropped: honeypot filled");
+        return success();
+    }
+
+    // Checked before the token, the challenge and the limits are spent
+    if let Err(e) = state.check_contact(&form).await {
+        tracing::info!("Contact message refused: {}", e);
+        return error(StatusCode::BAD_REQUEST, &e.to_string());
+    }
+
+    if let Err(e) = state.tokens().redeem(form.token.as_deref()).await {
+        let status = if matches!(e, TokenError::Storage(_)) {
+            tracing::error!("Error redeeming contact token: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        } else {
+            tracing::info!("Contact message refused: {}", e);
+            StatusCode::BAD_REQUEST
+        };
+        return (
+            status,
             Json(json!({
-                "status": "success",
-                "message": "Message envoyé avec succès"
+                "status": "error",
+                "message": e.message()
             })),
         )
-            .into_response(),
+            .into_response();
+    }
+
+    if let Err(e) = state
+        .challenges()
+        .redeem(form.challenge.as_deref(), form.proof.as_deref())
+        .await
+    {
+        let status = if matches!(e, ChallengeError::Storage(_)) {
+            tracing::error!("Error redeeming contact challenge: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        } else {
+            tracing::info!("Contact message refused: {}", e);
+            StatusCode::BAD_REQUEST
+        };
+        return error(status, e.message());
+    }
+
+    let throttle = state.throttle();
+    match throttle.check(addr.ip(), &form.email).await {
+        Ok(None) => {}
+        Ok(Some(throttled)) => {
+            tracing::info!(
+                "Contact message throttled: {} limit reached",
+                throttled.scope.as_str()
+            );
+            let accept_language = headers
+                .get(header::ACCEPT_LANGUAGE)
+                .and_then(|v| v.to_str().ok());
+            let locale = throttle.locale(form.language.as_deref(), accept_language);
+            return too_many_messages(throttled, locale);
+        }
+        Err(e) => {
+            tracing::error!("Error checking contact limits: {:#}", e);
+            return error(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Erreur lors de la vérification des limites d'envoi",
+            );
+        }
+    }
+
+    match state.submit_contact(form).await {
+        Ok(()) => success(),
         Err(e) => {
             let error_msg = e.to_string();
             let status = if error_msg.contains("Validation error") {
@@ -37,3 +198,95 @@ pub async fn handle_message(
         }
     }
 }
+
+/// Outcome of a review action on a message
+fn reviewed(result: anyhow::Result<bool>) -> Response {
+    match result {
+        Ok(true) => Json(json!({ "status": "success" })).into_response(),
+        Ok(false) => error(StatusCode::NOT_FOUND, "Message introuvable"),
+        Err(e) => {
+            tracing::error!("Error reviewing contact message: {:#}", e);
+            error(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Erreur lors de la revue du message",
+            )
+        }
+    }
+}
+
+fn invalid_id() -> Response {
+    error(StatusCode::BAD_REQUEST, "Identifiant de message invalide")
+}
+
+/// Messages held back by the spam filter, most recent first
+pub async fn list_quarantine(
+    State(state): State<Arc<MessageService>>,
+    Query(query): Query<QuarantineQuery>,
+) -> Response {
+    match state
+        .list_quarantine(query.limit.unwrap_or(50).clamp(1, 200))
+        .await
+    {
+        Ok(messages) => Json(json!({ "messages": messages })).into_response(),
+        Err(e) => {
+            tracing::error!("Error listing quarantined messages: {:#}", e);
+            error(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Erreur lors de la récupération des messages en quarantaine",
+            )
+        }
+    }
+}
+
+/// Delivers a quarantined message and learns it as legitimate
+pub async fn release_quarantined(
+    State(state): State<Arc<MessageService>>,
+    Path(id): Path<String>,
+) -> Response {
+    let Ok(id) = ObjectId::parse_str(&id) else {
+        return invalid_id();
+    };
+    reviewed(state.release_quarantined(id).await)
+}
+
+/// Deletes a quarantined message and learns it as spam
+pub async fn reject_quarantined(
+    State(state): State<Arc<MessageService>>,
+    Path(id): Path<String>,
+) -> Response {
+    let Ok(id) = ObjectId::parse_str(&id) else {
+        return invalid_id();
+    };
+    reviewed(state.reject_quarantined(id).await)
+}
+
+/// Labels a delivered message as spam or ham for the classifier
+pub async fn label_message(
+    State(state): State<Arc<MessageService>>,
+    Path(id): Path<String>,
+    Json(label): Json<LabelRequest>,
+) -> Response {
+    let Ok(id) = ObjectId::parse_str(&id) else {
+        return invalid_id();
+    };
+    reviewed(state.label_message(id, label.spam).await)
+}
+
+/// Number of disposable email domains refused by the contact form
+pub async fn get_disposable_domains(State(state): State<Arc<MessageService>>) -> Response {
+    Json(json!({ "count": state.email_domains().disposable().count() })).into_response()
+}
+
+/// Reloads the file of disposable email domains
+pub async fn reload_disposable_domains(State(state): State<Arc<MessageService>>) -> Response {
+    match state.email_domains().disposable().reload() {
+        Ok(count) => Json(json!({ "status": "success", "count": count })).into_response(),
+        Err(e) => {
+            tracing::error!("Error reloading disposable domains: {:#}", e);
+            error(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Erreur lors du rechargement des domaines jetables",
+            )
+        }
+    }
+}
@@ -0,0 +1,84 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Deserialize;
+use serde_json::json;
+use std::sync::Arc;
+
+use crate::services::email_templates::EmailTemplates;
+
+#[derive(Debug, Deserialize)]
+pub struct PreviewQuery {
+    /// Requested locale, the fallback locale when unset
+    pub locale: Option<String>,
+}
+
+fn error(status: StatusCode, message: &str) -> Response {
+    (
+        status,
+        Json(json!({
+            "status": "error",
+            "message": message
+        })),
+    )
+        .into_response()
+}
+
+/// Names of the email templates and their locales
+pub async fn list_email_templates(State(templates): State<Arc<EmailTemplates>>) -> Response {
+    match templates.list() {
+        Ok(list) => Json(json!({ "templates": list })).into_response(),
+        Err(e) => {
+            tracing::error!("Error listing email templates: {}", e);
+            error(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Erreur lors de la lecture des templates",
+            )
+        }
+    }
+}
+
+/// Renders an email template with its sample data
+pub async fn preview_email_template(
+    State(templates): State<Arc<EmailTemplates>>,
+    Path(name): Path<String>,
+    Query(query): Query<PreviewQuery>,
+) -> Response {
+    let sample = match templates.sample(&name) {
+        Ok(Some(sample)) => sample,
+        Ok(None) => {
+            return error(
+                StatusCode::NOT_FOUND,
+                "Données d'exemple du template introuvables",
+            )
+        }
+        Err(e) => {
+            tracing::error!(
+                "Error reading sample data of email template {}: {}",
+                name,
+                e
+            );
+            return error(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Erreur lors de la lecture des données d'exemple du template",
+            );
+        }
+    };
+
+    match templates.render(&name, query.locale.as_deref().unwrap_or_default(), &sample) {
+        Ok(email) => Json(json!({
+            "locale": email.locale,
+            "subject": email.subject,
+            "html": email.html,
+            "text": email.text,
+        }))
+        .into_response(),
+        Err(e) => {
+            tracing::warn!("Error previewing email template {}: {:#}", name, e);
+            error(StatusCode::UNPROCESSABLE_ENTITY, &format!("{e:#}"))
+        }
+    }
+}
@@ -0,0 +1,184 @@
+use axum::{
+    extract::{Path, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Deserialize;
+use serde_json::json;
+use std::sync::Arc;
+
+use crate::services::i18n::{self, I18nService};
+
+#[derive(Debug, Deserialize)]
+pub struct TranslationUpdate {
+    pub value: String,
+}
+
+fn error(status: StatusCode, message: &str) -> Response {
+    (
+        status,
+        Json(json!({
+            "status": "error",
+            "message": message
+        })),
+    )
+        .into_response()
+}
+
+/// Returns true when `If-None-Match` lists the `ETag` (weak comparison)
+fn matches_etag(headers: &HeaderMap, etag: &str) -> bool {
+    headers
+        .get(header::IF_NONE_MATCH)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| {
+            v.split(',')
+                .map(|tag| tag.trim().trim_start_matches("W/"))
+                .any(|tag| tag == etag || tag == "*")
+        })
+}
+
+/// Serves the bundle of a locale, revalidated with its `ETag`
+async fn serve_bundle(service: &I18nService, locale: &str, headers: &HeaderMap) -> Response {
+    match service.bundle(locale).await {
+        Ok(bundle) => {
+            let cache_headers = [
+                (header::CACHE_CONTROL, "no-cache".to_string()),
+                (header::ETAG, bundle.etag.clone()),
+                (header::CONTENT_LANGUAGE, bundle.locale.clone()),
+                (header::VARY, "Accept-Language".to_string()),
+            ];
+            if matches_etag(headers, &bundle.etag) {
+                return (StatusCode::NOT_MODIFIED, cache_headers).into_response();
+            }
+
+            (
+                StatusCode::OK,
+                cache_headers,
+                [(header::CONTENT_TYPE, "application/json")],
+                bundle.body.clone(),
+            )
+                .into_response()
+        }
+        Err(e) => {
+            tracing::error!("Error loading translations for {}: {}", locale, e);
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+fn accept_language(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(header::ACCEPT_LANGUAGE)
+        .and_then(|v| v.to_str().ok())
+}
+
+/// Bundle of the locale negotiated from `Accept-Language`
+pub async fn get_negotiated_translations(
+    State(service): State<Arc<I18nService>>,
+    headers: HeaderMap,
+) -> Response {
+    let locale = service.negotiate(accept_language(&headers)).to_string();
+    serve_bundle(&service, &locale, &headers).await
+}
+
+/// Bundle of a locale
+///
+/// An unsupported locale falls back to its primary language, then to the
+/// `Accept-Language` header, then to the default locale.
+pub async fn get_translations(
+    State(service): State<Arc<I18nService>>,
+    Path(locale): Path<String>,
+    headers: HeaderMap,
+) -> Response {
+    let locale = service
+        .matching(&locale)
+        .unwrap_or_else(|| service.negotiate(accept_language(&headers)))
+        .to_string();
+    serve_bundle(&service, &locale, &headers).await
+}
+
+/// Validates the path of an edited key
+fn check_key(service: &I18nService, locale: &str, namespace: &str, key: &str) -> Option<Response> {
+    if !service.is_supported(locale) {
+        return Some(error(StatusCode::BAD_REQUEST, "Langue non prise en charge"));
+    }
+    if !i18n::is_valid_name(namespace) || !i18n::is_valid_name(key) {
+        return Some(error(
+            StatusCode::BAD_REQUEST,
+            "Espace de noms ou clé invalide",
+        ));
+    }
+    None
+}
+
+pub async fn update_translation(
+    State(service): State<Arc<I18nService>>,
+    Path((locale, namespace, key)): Path<(String, String, String)>,
+    Json(update): Json<TranslationUpdate>,
+) -> Response {
+    if let Some(response) = check_key(&service, &locale, &namespace, &key) {
+        return response;
+    }
+    if update.value.chars().count() > i18n::MAX_VALUE_CHARS {
+        return error(StatusCode::BAD_REQUEST, "La traduction est trop longue");
+    }
+
+    match service.set(&locale, &namespace, &key, &update.value).await {
+        Ok(()) => (
+            StatusCode::OK,
+            Json(json!({
+                "status": "success",
+                "message": "Traduction mise à jour"
+            })),
+        )
+            .into_response(),
+        Err(e) => {
+            tracing::error!(
+                "Error updating translation {}/{}/{}: {}",
+                locale,
+                namespace,
+                key,
+                e
+            );
+            error(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Erreur lors de la mise à jour de la traduction",
+            )
+        }
+    }
+}
+
+pub async fn delete_translation(
+    State(service): State<Arc<I18nService>>,
+    Path((locale, namespace, key)): Path<(String, String, String)>,
+) -> Response {
+    if let Some(response) = check_key(&service, &locale, &namespace, &key) {
+        return response;
+    }
+
+    match service.delete(&locale, &namespace, &key).await {
+        Ok(true) => (
+            StatusCode::OK,
+            Json(json!({
+                "status": "success",
+                "message": "Traduction supprimée"
+            })),
+        )
+            .into_response(),
+        Ok(false) => error(StatusCode::NOT_FOUND, "Traduction introuvable"),
+        Err(e) => {
+            tracing::error!(
+                "Error deleting translation {}/{}/{}: {}",
+                locale,
+                namespace,
+                key,
+                e
+            );
+            error(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Erreur lors de la suppression de la traduction",
+            )
+        }
+    }
+}
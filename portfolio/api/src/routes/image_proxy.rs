@@ -0,0 +1,67 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::IntoResponse,
+};
+use serde::Deserialize;
+use std::sync::Arc;
+
+use crate::services::image_proxy::{self, ImageProxy};
+
+#[derive(Debug, Deserialize)]
+pub struct ImageParams {
+    /// Requested width, rounded up to one of the served widths
+    w: Option<u32>,
+}
+
+/// Serves a resized copy of an article image
+///
+/// The content of a given hash and width never changes, so responses are cached
+/// for a year. The encoding depends on the `Accept` header.
+pub async fn get_image(
+    State(proxy): State<Arc<ImageProxy>>,
+    Path(hash): Path<String>,
+    headers: HeaderMap,
+    Query(params): Query<ImageParams>,
+) -> impl IntoResponse {
+    let width = image_proxy::snap_width(params.w);
+    let accepts_webp = headers
+        .get(header::ACCEPT)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.contains("image/webp"));
+
+    match proxy.get(&hash, width, accepts_webp).await {
+        Ok(Some(image)) => {
+            let etag = format!("\"{hash}-{width}-{}\"", image.format.content_type());
+            let cache_headers = [
+                (
+                    header::CACHE_CONTROL,
+                    "public, max-age=31536000, immutable".to_string(),
+                ),
+                (header::ETAG, etag.clone()),
+                (header::VARY, "Accept".to_string()),
+            ];
+
+            if headers
+                .get(header::IF_NONE_MATCH)
+                .and_then(|v| v.to_str().ok())
+                .is_some_and(|v| v == etag)
+            {
+                return (StatusCode::NOT_MODIFIED, cache_headers).into_response();
+            }
+
+            (
+                StatusCode::OK,
+                cache_headers,
+                [(header::CONTENT_TYPE, image.format.content_type())],
+                image.bytes,
+            )
+                .into_response()
+        }
+        Ok(None) => StatusCode::NOT_FOUND.into_response(),
+        Err(e) => {
+            tracing::error!("Error proxying image {}: {}", hash, e);
+            StatusCode::BAD_GATEWAY.into_response()
+        }
+    }
+}
@@ -1,7 +1,32 @@
+pub mod analytics;
+pub mod case_studies;
 pub mod contact;
+pub mod email_templates;
 pub mod health;
+pub mod i18n;
+pub mod image_proxy;
+pub mod placeholder;
+pub mod posts;
+pub mod projects;
 pub mod rss;
 
-pub use contact::handle_message;
+pub use analytics::{get_stats, record_event};
+pub use case_studies::{
+    create_case_study, delete_case_study, get_case_study, list_case_studies, update_case_study,
+};
+pub use contact::{
+    get_disposable_domains, handle_message, issue_contact_challenge, issue_contact_token,
+    label_message, list_quarantine, reject_quarantined, release_quarantined,
+};
+pub use email_templates::{list_email_templates, preview_email_template};
 pub use health::check;
-pub use rss::get_feeds;
+pub use i18n::{
+    delete_translation, get_negotiated_translations, get_translations, update_translation,
+};
+pub use image_proxy::get_image;
+pub use placeholder::get_placeholder;
+pub use posts::{
+    create_post, delete_post, get_highlight_css, get_post, list_post_tags, list_posts, update_post,
+};
+pub use projects::{get_projects, update_project};
+pub use rss::{get_featured, get_feeds, get_related, moderate_item};
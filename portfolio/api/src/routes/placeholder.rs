@@ -0,0 +1,105 @@
+use axum::{
+    extract::Query,
+    http::{header, HeaderMap, StatusCode},
+    response::IntoResponse,
+};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+
+use crate::services::placeholder::{self, DEFAULT_HEIGHT, DEFAULT_WIDTH};
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PlaceholderFormat {
+    Svg,
+    Png,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PlaceholderParams {
+    #[serde(default)]
+    title: String,
+    #[serde(default)]
+    source: String,
+    #[serde(default = "default_format")]
+    format: PlaceholderFormat,
+    #[serde(default = "default_width")]
+    w: u32,
+    #[serde(default = "default_height")]
+    h: u32,
+}
+
+fn default_format() -> PlaceholderFormat {
+    PlaceholderFormat::Svg
+}
+
+fn default_width() -> u32 {
+    DEFAULT_WIDTH
+}
+
+fn default_height() -> u32 {
+    DEFAULT_HEIGHT
+}
+
+/// Serves a deterministic placeholder image for articles without one
+///
+/// The output only depends on the query, so it is cached for a year.
+pub async fn get_placeholder(
+    headers: HeaderMap,
+    Query(params): Query<PlaceholderParams>,
+) -> impl IntoResponse {
+    let width = params.w.clamp(16, 1600);
+    let height = params.h.clamp(16, 1600);
+
+    let etag = format!(
+        "\"{}\"",
+        hex::encode(
+            &Sha256::new()
+                .chain_update(format!("{:?}|{width}|{height}|", params.format))
+                .chain_update(params.title.as_bytes())
+                .chain_update([0])
+                .chain_update(params.source.as_bytes())
+                .finalize()[..16]
+        )
+    );
+    let cache_headers = [
+        (
+            header::CACHE_CONTROL,
+            "public, max-age=31536000, immutable".to_string(),
+        ),
+        (header::ETAG, etag.clone()),
+    ];
+
+    if headers
+        .get(header::IF_NONE_MATCH)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v == etag)
+    {
+        return (StatusCode::NOT_MODIFIED, cache_headers).into_response();
+    }
+
+    match params.format {
+        PlaceholderFormat::Svg => (
+            StatusCode::OK,
+            cache_headers,
+            [(header::CONTENT_TYPE, "image/svg+xml")],
+            placeholder::render_svg(&params.title, &params.source, width, height),
+        )
+            .into_response(),
+        PlaceholderFormat::Png => {
+            match placeholder::render_png(&params.title, &params.source, width, height) {
+                Ok(png) => (
+                    StatusCode::OK,
+                    cache_headers,
+                    [(header::CONTENT_TYPE, "image/png")],
+                    png,
+                )
+                    .into_response(),
+                Err(e) => {
+                    tracing::error!("Error rendering PNG placeholder: {}", e);
+                    StatusCode::INTERNAL_SERVER_ERROR.into_response()
+                }
+            }
+        }
+    }
+}
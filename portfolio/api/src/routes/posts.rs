@@ -0,0 +1,158 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Deserialize;
+use serde_json::json;
+use std::sync::Arc;
+
+use crate::services::db::is_duplicate_key;
+use crate::services::markdown;
+use crate::services::posts::PostService;
+
+/// Largest page of the post listing
+const MAX_LIMIT: u32 = 50;
+
+#[derive(Debug, Deserialize)]
+pub struct PostQuery {
+    pub tag: Option<String>,
+    #[serde(default = "default_page")]
+    pub page: u32,
+    #[serde(default = "default_limit")]
+    pub limit: u32,
+}
+
+fn default_page() -> u32 {
+    1
+}
+
+fn default_limit() -> u32 {
+    10
+}
+
+fn not_found() -> Response {
+    (
+        StatusCode::NOT_FOUND,
+        Json(json!({
+            "status": "error",
+            "message": "Article introuvable"
+        })),
+    )
+        .into_response()
+}
+
+/// Maps a service error: 400 for an invalid source, 409 for a used slug, 500 otherwise
+fn error_response(slug: &str, e: &anyhow::Error) -> Response {
+    let (status, message) = if e.downcast_ref::<validator::ValidationErrors>().is_some() {
+        (StatusCode::BAD_REQUEST, e.to_string())
+    } else if is_duplicate_key(e) {
+        (StatusCode::CONFLICT, "Ce slug est déjà utilisé".to_string())
+    } else {
+        tracing::error!("Error saving post {}: {}", slug, e);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Erreur lors de l'enregistrement de l'article".to_string(),
+        )
+    };
+
+    (
+        status,
+        Json(json!({
+            "status": "error",
+            "message": message
+        })),
+    )
+        .into_response()
+}
+
+pub async fn list_posts(
+    State(service): State<Arc<PostService>>,
+    Query(query): Query<PostQuery>,
+) -> Response {
+    let limit = query.limit.clamp(1, MAX_LIMIT);
+    match service.list(query.tag.as_deref(), query.page, limit).await {
+        Ok(posts) => Json(posts).into_response(),
+        Err(e) => {
+            tracing::error!("Error listing posts: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+pub async fn list_post_tags(State(service): State<Arc<PostService>>) -> Response {
+    match service.tags().await {
+        Ok(tags) => Json(tags).into_response(),
+        Err(e) => {
+            tracing::error!("Error counting post tags: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+pub async fn get_post(
+    State(service): State<Arc<PostService>>,
+    Path(slug): Path<String>,
+) -> Response {
+    match service.get(&slug).await {
+        Ok(Some(post)) => Json(post).into_response(),
+        Ok(None) => not_found(),
+        Err(e) => {
+            tracing::error!("Error fetching post {}: {}", slug, e);
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+/// Stylesheet of the highlighted code blocks
+pub async fn get_highlight_css() -> impl IntoResponse {
+    (
+        [
+            (header::CONTENT_TYPE, "text/css; charset=utf-8"),
+            (header::CACHE_CONTROL, "public, max-age=86400"),
+        ],
+        markdown::highlight_css(),
+    )
+}
+
+/// Creates a post from its Markdown source, sent as the raw request body
+pub async fn create_post(State(service): State<Arc<PostService>>, source: String) -> Response {
+    match service.create(&source).await {
+        Ok(post) => (StatusCode::CREATED, Json(post)).into_response(),
+        Err(e) => error_response("(new)", &e),
+    }
+}
+
+pub async fn update_post(
+    State(service): State<Arc<PostService>>,
+    Path(slug): Path<String>,
+    source: String,
+) -> Response {
+    match service.update(&slug, &source).await {
+        Ok(Some(post)) => Json(post).into_response(),
+        Ok(None) => not_found(),
+        Err(e) => error_response(&slug, &e),
+    }
+}
+
+pub async fn delete_post(
+    State(service): State<Arc<PostService>>,
+    Path(slug): Path<String>,
+) -> Response {
+    match service.delete(&slug).await {
+        Ok(true) => (
+            StatusCode::OK,
+            Json(json!({
+                "status": "success",
+                "message": "Article supprimé"
+            })),
+        )
+            .into_response(),
+        Ok(false) => not_found(),
+        Err(e) => {
+            tracing::error!("Error deleting post {}: {}", slug, e);
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
@@ -0,0 +1,53 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use serde_json::json;
+use std::sync::Arc;
+
+use crate::models::project::{Project, ProjectOverride};
+use crate::services::projects::ProjectService;
+
+pub async fn get_projects(
+    State(project_service): State<Arc<ProjectService>>,
+) -> Json<Vec<Project>> {
+    Json(project_service.get_projects().await)
+}
+
+pub async fn update_project(
+    State(project_service): State<Arc<ProjectService>>,
+    Path(name): Path<String>,
+    Json(update): Json<ProjectOverride>,
+) -> impl IntoResponse {
+    match project_service.update_override(&name, &update).await {
+        Ok(true) => (
+            StatusCode::OK,
+            Json(json!({
+                "status": "success",
+                "message": "Projet mis à jour"
+            })),
+        )
+            .into_response(),
+        Ok(false) => (
+            StatusCode::NOT_FOUND,
+            Json(json!({
+                "status": "error",
+                "message": "Projet introuvable"
+            })),
+        )
+            .into_response(),
+        Err(e) => {
+            tracing::error!("Error updating project {}: {}", name, e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({
+                    "status": "error",
+                    "message": "Erreur lors de la mise à jour du projet"
+                })),
+            )
+                .into_response()
+        }
+    }
+}
@@ -1,10 +1,16 @@
 use axum::{
-    extract::{Query, State},
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
     Json,
 };
 use serde::Deserialize;
+use serde_json::json;
 use std::sync::Arc;
 
+use crate::models::rss::ModerationUpdate;
+use crate::services::ranking::RankingMode;
+use crate::services::related::MAX_RELATED;
 use crate::services::rss::FeedService;
 
 #[derive(Debug, Deserialize)]
@@ -13,6 +19,25 @@ pub struct PaginationParams {
     page: u32,
     #[serde(default = "default_limit")]
     limit: u32,
+    /// `recent` or `ranked`, defaults to the deployment setting
+    #[serde(default)]
+    sort: Option<RankingMode>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LimitParams {
+    #[serde(default = "default_limit")]
+    limit: u32,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RelatedParams {
+    #[serde(default = "default_related_limit")]
+    limit: u32,
+}
+
+fn default_related_limit() -> u32 {
+    4
 }
 
 fn default_page() -> u32 {
@@ -27,6 +52,86 @@ pub async fn get_feeds(
     State(feed_service): State<Arc<FeedService>>,
     Query(params): Query<PaginationParams>,
 ) -> Json<Vec<crate::models::rss::RssItem>> {
-    let feeds = feed_service.get_feeds(params.page, params.limit).await;
+    let feeds = match params.sort.unwrap_or(feed_service.default_ranking_mode()) {
+        RankingMode::Recent => feed_service.get_feeds(params.page, params.limit).await,
+        RankingMode::Ranked => {
+            feed_service
+                .get_ranked_feeds(params.page, params.limit)
+                .await
+        }
+    };
     Json(feeds)
 }
+
+pub async fn get_featured(
+    State(feed_service): State<Arc<FeedService>>,
+    Query(params): Query<LimitParams>,
+) -> Json<Vec<crate::models::rss::RssItem>> {
+    Json(feed_service.get_featured(params.limit).await)
+}
+
+pub async fn get_related(
+    State(feed_service): State<Arc<FeedService>>,
+    Path(id): Path<String>,
+    Query(params): Query<RelatedParams>,
+) -> impl IntoResponse {
+    let limit = params.limit.min(MAX_RELATED as u32);
+    match feed_service.get_related(&id, limit).await {
+        Ok(Some(items)) => (StatusCode::OK, Json(json!(items))).into_response(),
+        Ok(None) => (
+            StatusCode::NOT_FOUND,
+            Json(json!({
+                "status": "error",
+                "message": "Article introuvable"
+            })),
+        )
+            .into_response(),
+        Err(e) => {
+            tracing::error!("Error fetching items related to {}: {}", id, e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({
+                    "status": "error",
+                    "message": "Erreur lors de la recherche des articles similaires"
+                })),
+            )
+                .into_response()
+        }
+    }
+}
+
+pub async fn moderate_item(
+    State(feed_service): State<Arc<FeedService>>,
+    Path(id): Path<String>,
+    Json(update): Json<ModerationUpdate>,
+) -> impl IntoResponse {
+    match feed_service.moderate_item(&id, &update).await {
+        Ok(true) => (
+            StatusCode::OK,
+            Json(json!({
+                "status": "success",
+                "message": "Article mis à jour"
+            })),
+        )
+            .into_response(),
+        Ok(false) => (
+            StatusCode::NOT_FOUND,
+            Json(json!({
+                "status": "error",
+                "message": "Article introuvable"
+            })),
+        )
+            .into_response(),
+        Err(e) => {
+            tracing::error!("Error moderating RSS item {}: {}", id, e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({
+                    "status": "error",
+                    "message": "Erreur lors de la mise à jour de l'article"
+                })),
+            )
+                .into_response()
+        }
+    }
+}
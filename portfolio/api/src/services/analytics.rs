@@ -0,0 +1,481 @@
+use anyhow::Result;
+use chrono::{NaiveDate, Utc};
+use futures_util::TryStreamExt;
+use mongodb::bson::{doc, Document};
+use mongodb::options::ReturnDocument;
+use mongodb::Database;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+use std::net::IpAddr;
+use std::sync::Mutex;
+use url::Url;
+
+use crate::models::analytics::{
+    AnalyticsEvent, AnalyticsStats, CountStats, DayStats, EventKind, PageStats,
+};
+use crate::services::db::is_duplicate_key;
+
+/// Collection of the counters per day, kind, path, referrer and target
+const DAILY_COLLECTION: &str = "analytics_daily";
+/// Collection of the visitor hashes of the last days, used to count uniques
+const VISITORS_COLLECTION: &str = "analytics_visitors";
+/// Collection of the daily salts
+const SALTS_COLLECTION: &str = "analytics_salts";
+/// Visitor scope counting the unique visitors of the whole site
+const SITE_SCOPE: &str = "*";
+/// Maximum length of a tracked path
+const MAX_PATH_CHARS: usize = 300;
+/// Maximum length of an outbound target
+const MAX_TARGET_CHARS: usize = 2000;
+/// Entries of the top pages, referrers and outbound targets
+const TOP_ENTRIES: usize = 20;
+/// User agents of crawlers, whose events are ignored
+const BOT_MARKERS: [&str; 5] = ["bot", "crawler", "spider", "headless", "preview"];
+
+/// Event once validated and stripped of personal data
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NormalizedEvent {
+    pub kind: EventKind,
+    pub path: String,
+    /// Host of an external referrer, empty for direct and internal visits
+    pub referrer: String,
+    /// Outbound URL without fragment, empty for page views
+    pub target: String,
+}
+
+/// Returns true when the request asked not to be tracked (`DNT: 1` or `Sec-GPC: 1`)
+#[must_use]
+pub fn opted_out(dnt: Option<&str>, gpc: Option<&str>) -> bool {
+    [dnt, gpc]
+        .iter()
+        .any(|value| value.is_some_and(|value| value.trim() == "1"))
+}
+
+/// Returns true for the user agents of crawlers and link previews
+#[must_use]
+pub fn is_bot(user_agent: &str) -> bool {
+    let user_agent = user_agent.to_lowercase();
+    user_agent.is_empty() || BOT_MARKERS.iter().any(|marker| user_agent.contains(marker))
+}
+
+/// Lowercased host without `www.`
+fn host_of(url: &Url) -> Option<String> {
+    url.host_str()
+        .map(|host| host.trim_start_matches("www.").to_lowercase())
+}
+
+/// Validates an event and keeps only what is aggregated
+///
+/// # Errors
+///
+/// Returns the reason for an invalid path or outbound target.
+pub fn normalize(
+    event: &AnalyticsEvent,
+    site_host: Option<&str>,
+) -> Result<NormalizedEvent, &'static str> {
+    let path = event
+        .path
+        .split(['?', '#'])
+        .next()
+        .unwrap_or_default()
+        .trim();
+    if !path.starts_with('/') || path.starts_with("//") || path.chars().count() > MAX_PATH_CHARS {
+        return Err("Chemin invalide");
+    }
+
+    let referrer = event
+        .referrer
+        .as_deref()
+        .and_then(|referrer| Url::parse(referrer).ok())
+        .and_then(|url| host_of(&url))
+        .filter(|host| Some(host.as_str()) != site_host)
+        .unwrap_or_default();
+
+    let target = match event.kind {
+        EventKind::PageView => String::new(),
+        EventKind::OutboundClick => {
+            let mut url = event
+                .target
+                .as_deref()
+                .and_then(|target| Url::parse(target).ok())
+                .filter(|url| matches!(url.scheme(), "http" | "https"))
+                .ok_or("Lien sortant invalide")?;
+            url.set_fragment(None);
+            let target = url.to_string();
+            if target.chars().count() > MAX_TARGET_CHARS {
+                return Err("Lien sortant invalide");
+            }
+            target
+        }
+    };
+
+    Ok(NormalizedEvent {
+        kind: event.kind,
+        path: path.to_string(),
+        referrer,
+        target,
+    })
+}
+
+/// Hashes a visitor with the salt of the day; the inputs are never stored
+#[must_use]
+pub fn visitor_hash(salt: &str, ip: IpAddr, user_agent: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(salt.as_bytes());
+    hasher.update(ip.to_string().as_bytes());
+    hasher.update([0]);
+    hasher.update(user_agent.as_bytes());
+    hex::encode(&hasher.finalize()[..16])
+}
+
+fn get_u64(doc: &Document, key: &str) -> u64 {
+    doc.get_i64(key)
+        .ok()
+        .or_else(|| doc.get_i32(key).ok().map(i64::from))
+        .and_then(|value| u64::try_from(value).ok())
+        .unwrap_or(0)
+}
+
+fn top(counts: HashMap<String, u64>) -> Vec<CountStats> {
+    let mut counts: Vec<CountStats> = counts
+        .into_iter()
+        .map(|(name, count)| CountStats { name, count })
+        .collect();
+    counts.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.name.cmp(&b.name)));
+    counts.truncate(TOP_ENTRIES);
+    counts
+}
+
+/// Summarizes the `analytics_daily` counters of a period
+#[must_use]
+pub fn summarize(from: NaiveDate, to: NaiveDate, counters: &[Document]) -> AnalyticsStats {
+    let mut days: BTreeMap<String, DayStats> = BTreeMap::new();
+    let mut pages: HashMap<String, PageStats> = HashMap::new();
+    let mut referrers: HashMap<String, u64> = HashMap::new();
+    let mut outbound: HashMap<String, u64> = HashMap::new();
+
+    for counter in counters {
+        let day_name = counter.get_str("day").unwrap_or_default().to_string();
+        let path = counter.get_str("path").unwrap_or_default();
+        let count = get_u64(counter, "count");
+        let visitors = get_u64(counter, "visitors");
+        let day = days.entry(day_name.clone()).or_insert_with(|| DayStats {
+            day: day_name,
+            ..DayStats::default()
+        });
+
+        match counter.get_str("kind").unwrap_or_default() {
+            "site" => day.visitors += visitors,
+            "page_view" => {
+                day.page_views += count;
+                let page = pages.entry(path.to_string()).or_insert_with(|| PageStats {
+                    path: path.to_string(),
+                    ..PageStats::default()
+                });
+                page.page_views += count;
+                page.visitors += visitors;
+                if let Ok(referrer) = counter.get_str("referrer") {
+                    if !referrer.is_empty() {
+                        *referrers.entry(referrer.to_string()).or_default() += count;
+                    }
+                }
+            }
+            "outbound_click" => {
+                day.outbound_clicks += count;
+                if let Ok(target) = counter.get_str("target") {
+                    *outbound.entry(target.to_string()).or_default() += count;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let days: Vec<DayStats> = days.into_values().collect();
+    let mut top_pages: Vec<PageStats> = pages.into_values().collect();
+    top_pages.sort_by(|a, b| {
+        b.page_views
+            .cmp(&a.page_views)
+            .then_with(|| a.path.cmp(&b.path))
+    });
+    top_pages.truncate(TOP_ENTRIES);
+
+    AnalyticsStats {
+        from: from.to_string(),
+        to: to.to_string(),
+        page_views: days.iter().map(|day| day.page_views).sum(),
+        visitors: days.iter().map(|day| day.visitors).sum(),
+        outbound_clicks: days.iter().map(|day| day.outbound_clicks).sum(),
+        days,
+        top_pages,
+        top_referrers: top(referrers),
+        top_outbound: top(outbound),
+    }
+}
+
+/// Cookie-less page view and outbound click counters
+///
+/// Visitors are identified by a hash of their IP and user agent salted with a
+/// random value that changes every day. Salts and hashes expire after two
+/// days, so visitors cannot be followed from one day to the next; only the
+/// aggregated counters are kept.
+pub struct AnalyticsService {
+    db: Database,
+    site_host: Option<String>,
+    /// Salt of the current day, shared through `analytics_salts` by all instances
+    salt: Mutex<Option<(String, String)>>,
+}
+
+impl AnalyticsService {
+    #[must_use]
+    pub fn new(db: Database, frontend_url: &str) -> Self {
+        Self {
+            db,
+            site_host: Url::parse(frontend_url).ok().and_then(|url| host_of(&url)),
+            salt: Mutex::new(None),
+        }
+    }
+
+    /// Host of the site, whose referrers are internal navigation
+    #[must_use]
+    pub fn site_host(&self) -> Option<&str> {
+        self.site_host.as_deref()
+    }
+
+    /// Returns the salt of a day, created by the first instance needing it
+    async fn salt(&self, day: &str) -> Result<String> {
+        if let Ok(cached) = self.salt.lock() {
+            if let Some((cached_day, salt)) = cached.as_ref() {
+                if cached_day == day {
+                    return Ok(salt.clone());
+                }
+            }
+        }
+
+        let salt = self
+            .db
+            .collection::<Document>(SALTS_COLLECTION)
+            .find_one_and_update(
+                doc! { "day": day },
+                doc! { "$setOnInsert": {
+                    "salt": hex::encode(rand::random::<[u8; 32]>()),
+                    "created_at": mongodb::bson::DateTime::now(),
+                }},
+            )
+            .upsert(true)
+            .return_document(ReturnDocument::After)
+            .await?
+            .and_then(|doc| doc.get_str("salt").ok().map(str::to_string))
+            .ok_or_else(|| anyhow::anyhow!("Missing analytics salt for {day}"))?;
+
+        if let Ok(mut cached) = self.salt.lock() {
+            *cached = Some((day.to_string(), salt.clone()));
+        }
+        Ok(salt)
+    }
+
+    /// Records a visitor hash, returns true the first time it is seen in the scope
+    async fn first_visit(&self, day: &str, scope: &str, visitor: &str) -> Result<bool> {
+        let result = self
+            .db
+            .collection::<Document>(VISITORS_COLLECTION)
+            .insert_one(doc! {
+                "day": day,
+                "scope": scope,
+                "visitor": visitor,
+                "created_at": mongodb::bson::DateTime::now(),
+            })
+            .await;
+        match result {
+            Ok(_) => Ok(true),
+            Err(e) => {
+                let error = anyhow::Error::from(e);
+                if is_duplicate_key(&error) {
+                    Ok(false)
+                } else {
+                    Err(error)
+                }
+            }
+        }
+    }
+
+    async fn increment(&self, key: Document, count: i64, visitors: i64) -> Result<()> {
+        self.db
+            .collection::<Document>(DAILY_COLLECTION)
+            .update_one(
+                key,
+                doc! { "$inc": { "count": count, "visitors": visitors } },
+            )
+            .upsert(true)
+            .await?;
+        Ok(())
+    }
+
+    /// Counts an event
+    ///
+    /// # Errors
+    ///
+    /// Returns error if a database operation fails.
+    pub async fn record(
+        &self,
+        event: &NormalizedEvent,
+        ip: IpAddr,
+        user_agent: &str,
+    ) -> Result<()> {
+        let day = Utc::now().date_naive().to_string();
+        let key = doc! {
+            "day": &day,
+            "kind": event.kind.as_str(),
+            "path": &event.path,
+            "referrer": &event.referrer,
+            "target": &event.target,
+        };
+        if event.kind == EventKind::OutboundClick {
+            return self.increment(key, 1, 0).await;
+        }
+
+        let visitor = visitor_hash(&self.salt(&day).await?, ip, user_agent);
+        let new_on_page = self.first_visit(&day, &event.path, &visitor).await?;
+        self.increment(key, 1, i64::from(new_on_page)).await?;
+        if self.first_visit(&day, SITE_SCOPE, &visitor).await? {
+            self.increment(
+                doc! { "day": &day, "kind": "site", "path": SITE_SCOPE, "referrer": "", "target": "" },
+                0,
+                1,
+            )
+            .await?;
+        }
+        Ok(())
+    }
+
+    /// Traffic between two days, both included
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the database query fails.
+    pub async fn stats(&self, from: NaiveDate, to: NaiveDate) -> Result<AnalyticsStats> {
+        let counters: Vec<Document> = self
+            .db
+            .collection::<Document>(DAILY_COLLECTION)
+            .find(doc! { "day": { "$gte": from.to_string(), "$lte": to.to_string() } })
+            .projection(doc! { "_id": 0 })
+            .await?
+            .try_collect()
+            .await?;
+        Ok(summarize(from, to, &counters))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(
+        kind: EventKind,
+        path: &str,
+        referrer: Option<&str>,
+        target: Option<&str>,
+    ) -> AnalyticsEvent {
+        AnalyticsEvent {
+            kind,
+            path: path.to_string(),
+            referrer: referrer.map(str::to_string),
+            target: target.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_normalize_keeps_no_personal_data() {
+        let normalized = normalize(
+            &event(
+                EventKind::PageView,
+                "/blog/post?utm_source=x#top",
+                Some("https://www.Example.com/search?q=secret"),
+                Some("https://ignored.example"),
+            ),
+            Some("portfolio.dev"),
+        )
+        .unwrap();
+
+        assert_eq!(normalized.path, "/blog/post");
+        assert_eq!(normalized.referrer, "example.com");
+        assert!(normalized.target.is_empty());
+    }
+
+    #[test]
+    fn test_normalize_internal_referrer_and_clicks() {
+        let normalized = normalize(
+            &event(
+                EventKind::OutboundClick,
+                "/",
+                Some("https://portfolio.dev/about"),
+                Some("https://blog.example.com/post#comments"),
+            ),
+            Some("portfolio.dev"),
+        )
+        .unwrap();
+
+        assert!(normalized.referrer.is_empty());
+        assert_eq!(normalized.target, "https://blog.example.com/post");
+
+        assert!(normalize(
+            &event(EventKind::OutboundClick, "/", None, Some("javascript:x")),
+            None
+        )
+        .is_err());
+        assert!(normalize(
+            &event(EventKind::PageView, "https://evil.example", None, None),
+            None
+        )
+        .is_err());
+        assert!(normalize(
+            &event(EventKind::PageView, "//evil.example", None, None),
+            None
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_opt_out_and_bots() {
+        assert!(opted_out(Some("1"), None));
+        assert!(opted_out(None, Some("1")));
+        assert!(!opted_out(Some("0"), None));
+        assert!(is_bot("Mozilla/5.0 (compatible; Googlebot/2.1)"));
+        assert!(is_bot(""));
+        assert!(!is_bot("Mozilla/5.0 (X11; Linux x86_64) Firefox/128.0"));
+    }
+
+    #[test]
+    fn test_visitor_hash_depends_on_salt() {
+        let ip: IpAddr = "203.0.113.7".parse().unwrap();
+
+        let hash = visitor_hash("salt-1", ip, "Firefox");
+        assert_eq!(hash, visitor_hash("salt-1", ip, "Firefox"));
+        assert_ne!(hash, visitor_hash("salt-2", ip, "Firefox"));
+        assert!(!hash.contains("203.0.113.7"));
+    }
+
+    #[test]
+    fn test_summarize() {
+        let counters = vec![
+            doc! { "day": "2024-05-01", "kind": "page_view", "path": "/", "referrer": "", "target": "", "count": 5_i64, "visitors": 3_i64 },
+            doc! { "day": "2024-05-01", "kind": "page_view", "path": "/", "referrer": "example.com", "target": "", "count": 2_i64, "visitors": 1_i64 },
+            doc! { "day": "2024-05-02", "kind": "page_view", "path": "/blog", "referrer": "", "target": "", "count": 1_i32, "visitors": 1_i32 },
+            doc! { "day": "2024-05-01", "kind": "site", "path": "*", "referrer": "", "target": "", "count": 0_i64, "visitors": 4_i64 },
+            doc! { "day": "2024-05-02", "kind": "outbound_click", "path": "/", "referrer": "", "target": "https://a.example/", "count": 2_i64, "visitors": 0_i64 },
+        ];
+        let from = NaiveDate::from_ymd_opt(2024, 5, 1).unwrap();
+        let to = NaiveDate::from_ymd_opt(2024, 5, 2).unwrap();
+
+        let stats = summarize(from, to, &counters);
+
+        assert_eq!(stats.page_views, 8);
+        assert_eq!(stats.visitors, 4);
+        assert_eq!(stats.outbound_clicks, 2);
+        assert_eq!(stats.days.len(), 2);
+        assert_eq!(stats.days[0].page_views, 7);
+        assert_eq!(stats.top_pages[0].path, "/");
+        assert_eq!(stats.top_pages[0].visitors, 4);
+        assert_eq!(stats.top_referrers[0].name, "example.com");
+        assert_eq!(stats.top_outbound[0].count, 2);
+    }
+}
@@ -0,0 +1,113 @@
+use std::collections::{HashMap, HashSet};
+
+/// Messages of each class needed before the classifier is trusted
+pub const MIN_TRAINING: u64 = 5;
+
+/// Splits a text into lowercase word tokens
+#[must_use]
+pub fn tokenize(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric() && c != '\'')
+        .map(|word| word.trim_matches('\'').to_lowercase())
+        .filter(|word| (2..=30).contains(&word.chars().count()))
+        .collect()
+}
+
+/// Per-class counts of the classifier
+#[derive(Debug, Clone, Default)]
+struct ClassCounts {
+    documents: u64,
+    /// Token → number of documents of the class containing it
+    tokens: HashMap<String, u64>,
+}
+
+/// Naive Bayes spam classifier over the distinct tokens of a message
+///
+/// Trained on messages an administrator labelled as spam or ham, with
+/// Laplace smoothing. Each token is counted once per message.
+#[derive(Debug, Clone, Default)]
+pub struct NaiveBayes {
+    spam: ClassCounts,
+    ham: ClassCounts,
+}
+
+impl NaiveBayes {
+    /// Adds a labelled message
+    pub fn train(&mut self, tokens: &[String], spam: bool) {
+        let class = if spam { &mut self.spam } else { &mut self.ham };
+        class.documents += 1;
+        for token in tokens.iter().collect::<HashSet<_>>() {
+            *class.tokens.entry(token.clone()).or_default() += 1;
+        }
+    }
+
+    /// Returns true once both classes have enough messages
+    #[must_use]
+    pub const fn is_trained(&self) -> bool {
+        self.spam.documents >= MIN_TRAINING && self.ham.documents >= MIN_TRAINING
+    }
+
+    /// Probability that a message is spam, `None` until the classifier is trained
+    #[must_use]
+    pub fn spam_probability(&self, tokens: &[String]) -> Option<f64> {
+        if !self.is_trained() {
+            return None;
+        }
+
+        let spam_docs = self.spam.documents as f64;
+        let ham_docs = self.ham.documents as f64;
+        let mut log_spam = (spam_docs / (spam_docs + ham_docs)).ln();
+        let mut log_ham = (ham_docs / (spam_docs + ham_docs)).ln();
+
+        for token in tokens.iter().collect::<HashSet<_>>() {
+            let in_spam = self.spam.tokens.get(token).copied().unwrap_or(0) as f64;
+            let in_ham = self.ham.tokens.get(token).copied().unwrap_or(0) as f64;
+            // Tokens never seen in training carry no information
+            if in_spam + in_ham == 0.0 {
+                continue;
+            }
+            log_spam += ((in_spam + 1.0) / (spam_docs + 2.0)).ln();
+            log_ham += ((in_ham + 1.0) / (ham_docs + 2.0)).ln();
+        }
+
+        Some(1.0 / (1.0 + (log_ham - log_spam).exp()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tokenize() {
+        assert_eq!(
+            tokenize("Hello, WORLD! l'été a x"),
+            vec!["hello", "world", "l'été"]
+        );
+    }
+
+    #[test]
+    fn test_spam_probability() {
+        let mut model = NaiveBayes::default();
+        assert_eq!(model.spam_probability(&tokenize("casino")), None);
+
+        for _ in 0..MIN_TRAINING {
+            model.train(&tokenize("cheap casino bonus win money now"), true);
+            model.train(
+                &tokenize("hello I would like to discuss a rust project"),
+                false,
+            );
+        }
+        assert!(model.is_trained());
+
+        let spam = model
+            .spam_probability(&tokenize("win a casino bonus"))
+            .unwrap();
+        let ham = model
+            .spam_probability(&tokenize("a rust project to discuss"))
+            .unwrap();
+        let unknown = model.spam_probability(&tokenize("zebra")).unwrap();
+        assert!(spam > 0.99, "{spam}");
+        assert!(ham < 0.01, "{ham}");
+        assert!((unknown - 0.5).abs() < 1e-9);
+    }
+}
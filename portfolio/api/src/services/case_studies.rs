@@ -0,0 +1,301 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use futures_util::TryStreamExt;
+use mongodb::bson::{doc, Bson, Document};
+use mongodb::Database;
+use validator::{Validate, ValidationError, ValidationErrors};
+
+use crate::models::case_study::{CaseStudy, CaseStudyInput, CaseStudyLink, CaseStudyStatus};
+use crate::services::markdown;
+
+/// Collection storing the case studies
+const COLLECTION: &str = "projects";
+/// Maximum length of a slug derived from a title
+const MAX_SLUG_CHARS: usize = 100;
+
+/// Lowercases, trims and deduplicates tags, keeping their order
+pub(crate) fn normalize_tags(tags: &[String]) -> Vec<String> {
+    let mut normalized: Vec<String> = Vec::with_capacity(tags.len());
+    for tag in tags {
+        let tag = tag.trim().to_lowercase();
+        if !tag.is_empty() && !normalized.contains(&tag) {
+            normalized.push(tag);
+        }
+    }
+    normalized
+}
+
+/// Returns the requested slug, or one derived from the title
+///
+/// # Errors
+///
+/// Returns a validation error if the title yields an empty slug.
+fn slug_for(input: &CaseStudyInput) -> Result<String> {
+    let slug = match input.slug.as_deref().filter(|slug| !slug.is_empty()) {
+        Some(slug) => slug.to_string(),
+        None => {
+            let slug = slug::slugify(&input.title);
+            slug.chars()
+                .take(MAX_SLUG_CHARS)
+                .collect::<String>()
+                .trim_end_matches('-')
+                .to_string()
+        }
+    };
+
+    if slug.is_empty() {
+        let mut errors = ValidationErrors::new();
+        errors.add(
+            "slug",
+            ValidationError::new("slug_invalide")
+                .with_message("Impossible de générer un slug à partir du titre".into()),
+        );
+        return Err(errors.into());
+    }
+    Ok(slug)
+}
+
+fn to_chrono(date: mongodb::bson::DateTime) -> Option<DateTime<Utc>> {
+    DateTime::from_timestamp_millis(date.timestamp_millis())
+}
+
+fn strings(doc: &Document, key: &str) -> Vec<String> {
+    doc.get_array(key)
+        .map(|values| {
+            values
+                .iter()
+                .filter_map(|value| value.as_str().map(str::to_string))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Converts a stored `projects` document, with the rendered body on demand
+fn to_case_study(doc: &Document, with_body: bool) -> CaseStudy {
+    CaseStudy {
+        slug: doc.get_str("slug").unwrap_or_default().to_string(),
+        title: doc.get_str("title").unwrap_or_default().to_string(),
+        summary: doc.get_str("summary").unwrap_or_default().to_string(),
+        body_html: with_body.then(|| doc.get_str("body_html").unwrap_or_default().to_string()),
+        tags: strings(doc, "tags"),
+        images: strings(doc, "images"),
+        links: doc
+            .get_array("links")
+            .map(|links| {
+                links
+                    .iter()
+                    .filter_map(Bson::as_document)
+                    .map(|link| CaseStudyLink {
+                        label: link.get_str("label").unwrap_or_default().to_string(),
+                        url: link.get_str("url").unwrap_or_default().to_string(),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default(),
+        position: doc.get_i32("position").unwrap_or(0),
+        status: if doc.get_str("status") == Ok("published") {
+            CaseStudyStatus::Published
+        } else {
+            CaseStudyStatus::Draft
+        },
+        published_at: doc
+            .get_datetime("published_at")
+            .ok()
+            .copied()
+            .and_then(to_chrono),
+        updated_at: doc
+            .get_datetime("updated_at")
+            .ok()
+            .copied()
+            .and_then(to_chrono)
+            .unwrap_or_default(),
+    }
+}
+
+/// Hand-curated case studies, stored in the `projects` collection
+///
+/// Bodies are written in Markdown and rendered to sanitized HTML when saved,
+/// so reads never run the renderer. Drafts are only returned to the
+/// administration, in the responses of create and update.
+pub struct CaseStudyService {
+    db: Database,
+}
+
+impl CaseStudyService {
+    #[must_use]
+    pub const fn new(db: Database) -> Self {
+        Self { db }
+    }
+
+    fn collection(&self) -> mongodb::Collection<Document> {
+        self.db.collection::<Document>(COLLECTION)
+    }
+
+    /// Lists the published case studies, optionally restricted to a tag
+    ///
+    /// Sorted by position, then most recently published first. Bodies are
+    /// left out of the listing.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the database query fails.
+    pub async fn list(&self, tag: Option<&str>) -> Result<Vec<CaseStudy>> {
+        let mut filter = doc! { "status": CaseStudyStatus::Published.as_str() };
+        if let Some(tag) = tag.map(|tag| tag.trim().to_lowercase()) {
+            filter.insert("tags", tag);
+        }
+        let options = mongodb::options::FindOptions::builder()
+            .sort(doc! { "position": 1, "published_at": -1 })
+            .projection(doc! { "body": 0, "body_html": 0 })
+            .build();
+
+        let docs: Vec<Document> = self
+            .collection()
+            .find(filter)
+            .with_options(options)
+            .await?
+            .try_collect()
+            .await?;
+        Ok(docs.iter().map(|doc| to_case_study(doc, false)).collect())
+    }
+
+    /// Retrieves a published case study with its rendered body
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the database query fails.
+    pub async fn get(&self, slug: &str) -> Result<Option<CaseStudy>> {
+        let doc = self
+            .collection()
+            .find_one(doc! { "slug": slug, "status": CaseStudyStatus::Published.as_str() })
+            .await?;
+        Ok(doc.map(|doc| to_case_study(&doc, true)))
+    }
+
+    /// Builds the stored fields of a case study from the administration input
+    fn fields(input: &CaseStudyInput, slug: &str) -> Document {
+        let links = input
+            .links
+            .iter()
+            .map(|link| doc! { "label": link.label.trim(), "url": &link.url })
+            .collect::<Vec<_>>();
+
+        doc! {
+            "slug": slug,
+            "title": input.title.trim(),
+            "summary": input.summary.trim(),
+            "body": &input.body,
+            "body_html": markdown::render(&input.body),
+            "tags": normalize_tags(&input.tags),
+            "images": &input.images,
+            "links": links,
+            "position": input.position,
+            "status": input.status.as_str(),
+            "updated_at": mongodb::bson::DateTime::now(),
+        }
+    }
+
+    /// Creates a case study
+    ///
+    /// # Errors
+    ///
+    /// Returns a `ValidationErrors` error for invalid input and a duplicate
+    /// key error (see [`crate::services::db::is_duplicate_key`]) if the slug is already used.
+    pub async fn create(&self, input: &CaseStudyInput) -> Result<CaseStudy> {
+        input.validate()?;
+        let slug = slug_for(input)?;
+
+        let now = mongodb::bson::DateTime::now();
+        let mut fields = Self::fields(input, &slug);
+        fields.insert("created_at", now);
+        if input.status == CaseStudyStatus::Published {
+            fields.insert("published_at", now);
+        }
+        self.collection().insert_one(&fields).await?;
+
+        Ok(to_case_study(&fields, true))
+    }
+
+    /// Replaces a case study, which may be renamed through its slug
+    ///
+    /// The creation date is kept, as well as the first publication date.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`Self::create`]. Returns `Ok(None)` when no case study has
+    /// this slug.
+    pub async fn update(&self, slug: &str, input: &CaseStudyInput) -> Result<Option<CaseStudy>> {
+        input.validate()?;
+        let new_slug = match input.slug {
+            Some(_) => slug_for(input)?,
+            None => slug.to_string(),
+        };
+
+        let Some(existing) = self.collection().find_one(doc! { "slug": slug }).await? else {
+            return Ok(None);
+        };
+
+        let mut fields = Self::fields(input, &new_slug);
+        if let Ok(created_at) = existing.get_datetime("created_at") {
+            fields.insert("created_at", *created_at);
+        }
+        match existing.get_datetime("published_at") {
+            Ok(published_at) => {
+                fields.insert("published_at", *published_at);
+            }
+            Err(_) if input.status == CaseStudyStatus::Published => {
+                fields.insert("published_at", mongodb::bson::DateTime::now());
+            }
+            Err(_) => {}
+        }
+
+        let result = self
+            .collection()
+            .replace_one(doc! { "slug": slug }, &fields)
+            .await?;
+        Ok((result.matched_count > 0).then(|| to_case_study(&fields, true)))
+    }
+
+    /// Deletes a case study
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the database operation fails. Returns `Ok(false)`
+    /// when no case study has this slug.
+    pub async fn delete(&self, slug: &str) -> Result<bool> {
+        let result = self.collection().delete_one(doc! { "slug": slug }).await?;
+        Ok(result.deleted_count > 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_slug_derived_from_title() {
+        let input = CaseStudyInput {
+            title: "Refonte du Portfolio : Rust & Next.js".to_string(),
+            ..CaseStudyInput::default()
+        };
+        assert_eq!(
+            slug_for(&input).unwrap(),
+            "refonte-du-portfolio-rust-next-js"
+        );
+
+        let input = CaseStudyInput {
+            title: "!!".to_string(),
+            ..CaseStudyInput::default()
+        };
+        assert!(slug_for(&input)
+            .unwrap_err()
+            .downcast_ref::<ValidationErrors>()
+            .is_some());
+    }
+
+    #[test]
+    fn test_normalize_tags() {
+        let tags = vec![" Rust ".to_string(), "rust".to_string(), "Axum".to_string()];
+        assert_eq!(normalize_tags(&tags), vec!["rust", "axum"]);
+    }
+}
@@ -1,42 +1,148 @@
 use crate::config::Config;
-use crate::models::contact::Request;
+use crate::models::contact::{QuarantinedMessage, Request};
+use crate::services::contact_throttle::ContactThrottle;
+use crate::services::db::is_duplicate_key;
+use crate::services::email_domains::EmailDomains;
+use crate::services::email_queue::{EmailMessage, EmailOutbox, RetryPolicy};
+use crate::services::email_templates::EmailTemplates;
+use crate::services::form_token::{contact_secret, FormTokens};
+use crate::services::proof_of_work::ProofOfWork;
+use crate::services::spam::{SpamFilter, SpamInput};
 use anyhow::Result;
+use chrono::Utc;
 use futures_util::TryStreamExt;
-use mongodb::bson::{doc, Document};
+use mongodb::bson::{doc, oid::ObjectId, Document};
 use mongodb::Database;
+use serde_json::json;
+use std::sync::Arc;
 use validator::Validate;
 
+/// Collection recording the acknowledgments sent, one per address and window
+const AUTO_REPLIES_COLLECTION: &str = "contact_auto_replies";
+/// Collection of the messages held back by the spam filter
+const QUARANTINE_COLLECTION: &str = "contact_quarantine";
+
 pub struct MessageService {
     db: Database,
     config: Config,
-    #[cfg(test)]
-    brevo_url: String,
+    outbox: EmailOutbox,
+    templates: Arc<EmailTemplates>,
+    tokens: FormTokens,
+    challenges: ProofOfWork,
+    spam: SpamFilter,
+    domains: EmailDomains,
+    throttle: ContactThrottle,
 }
 
 impl MessageService {
+    /// Crée le service avec les templates d'email configurés.
+    ///
+    /// # Panics
+    ///
+    /// Panique si les templates de `EMAIL_TEMPLATES_DIR`, les règles de
+    /// `CONTACT_SPAM_RULES_PATH`, les domaines de `CONTACT_DISPOSABLE_DOMAINS_PATH`
+    /// ou le résolveur DNS ne peuvent pas être chargés.
     #[must_use]
-    pub const fn new(db: Database, config: Config) -> Self {
+    pub fn new(db: Database, config: Config) -> Self {
+        let outbox = EmailOutbox::new(db.clone(), RetryPolicy::from_config(&config));
+        let templates = Arc::new(
+            EmailTemplates::from_config(&config).expect("Failed to load the email templates"),
+        );
+        let secret = contact_secret(&config);
+        let tokens = FormTokens::from_config(db.clone(), &secret, &config);
+        let challenges = ProofOfWork::from_config(db.clone(), &secret, &config);
+        let spam =
+            SpamFilter::from_config(db.clone(), &config).expect("Failed to load the spam rules");
+        let domains =
+            EmailDomains::from_config(&config).expect("Failed to load the email domain checks");
+        let throttle = ContactThrottle::from_config(db.clone(), &config);
         Self {
             db,
             config,
-            #[cfg(test)]
-            brevo_url: String::new(),
+            outbox,
+            templates,
+            tokens,
+            challenges,
+            spam,
+            domains,
+            throttle,
         }
     }
 
-    /// Soumet un nouveau formulaire de contact.
+    /// Templates d'email utilisés par le service
+    #[must_use]
+    pub fn templates(&self) -> Arc<EmailTemplates> {
+        self.templates.clone()
+    }
+
+    /// Jetons signés du formulaire de contact
+    #[must_use]
+    pub const fn tokens(&self) -> &FormTokens {
+        &self.tokens
+    }
+
+    /// Limites d'envoi du formulaire de contact
+    #[must_use]
+    pub const fn throttle(&self) -> &ContactThrottle {
+        &self.throttle
+    }
+
+    /// Vérifications du domaine des adresses de contact
+    #[must_use]
+    pub const fn email_domains(&self) -> &EmailDomains {
+        &self.domains
+    }
+
+    /// Défis de preuve de travail du formulaire de contact
+    #[must_use]
+    pub const fn challenges(&self) -> &ProofOfWork {
+        &self.challenges
+    }
+
+    /// Vérifie un formulaire de contact avant son envoi.
+    ///
+    /// La route l'appelle avant de consommer le jeton et le défi et de compter
+    /// l'envoi, pour qu'un formulaire invalide ne les gaspille pas. La réponse
+    /// DNS du domaine est en cache, la vérification refaite par
+    /// [`MessageService::submit_contact`] ne coûte donc pas de requête.
     ///
     /// # Errors
     ///
-    /// Cette fonction retourne une erreur si :
+    /// Cette fonction retourne une erreur `Validation error` si :
     /// - La validation du formulaire échoue
-    /// - L'insertion dans la base de données échoue
-    /// - L'envoi de l'email échoue
-    pub async fn submit_contact(&self, form: Request) -> Result<()> {
+    /// - Le domaine de l'adresse est jetable ou n'a pas de serveur de mail
+    pub async fn check_contact(&self, form: &Request) -> Result<()> {
         // Valider le formulaire
         form.validate()
             .map_err(|e| anyhow::anyhow!("Validation error: {}", e))?;
 
+        // Refuser les adresses jetables ou sans serveur de mail
+        self.domains
+            .check(&form.email)
+            .await
+            .map_err(|e| anyhow::anyhow!("Validation error: {}", e))?;
+
+        Ok(())
+    }
+
+    /// Soumet un nouveau formulaire de contact.
+    ///
+    /// # Errors
+    ///
+    /// Cette fonction retourne une erreur si :
+    /// - La validation du formulaire échoue (voir [`MessageService::check_contact`])
+    /// - L'insertion dans la base de données échoue
+    /// - La mise en file d'attente de l'email échoue
+    ///
+    /// L'email est envoyé en arrière-plan par [`crate::services::email_queue::EmailWorker`].
+    /// Avec `CONTACT_AUTO_REPLY`, un accusé de réception est aussi envoyé au visiteur
+    /// (voir [`MessageService::send_auto_reply`]) ; son échec n'annule pas l'envoi.
+    ///
+    /// Un message dont le score de spam atteint `CONTACT_SPAM_THRESHOLD` est mis
+    /// en quarantaine dans `contact_quarantine`, sans email, jusqu'à sa revue.
+    pub async fn submit_contact(&self, form: Request) -> Result<()> {
+        self.check_contact(&form).await?;
+
         // Stocker dans la base de données
         // Use is_test field to determine collection name (more reliable than cfg(test))
         let collection_name: String = if form.is_test {
@@ -49,37 +155,273 @@ impl MessageService {
             "contacts".to_string()
         };
 
-        let collection = self.db.collection::<Document>(&collection_name);
-        println!("Insertion du document dans la collection {collection_name}...");
-
-        let doc = doc! {
+        let id = ObjectId::new();
+        let reference = contact_reference(&id);
+        let mut doc = doc! {
+            "_id": id,
+            "reference": reference.clone(),
             "name": form.name.clone(),
             "email": form.email.clone(),
             "subject": form.subject.clone(),
             "message": form.message.clone(),
+            "language": form.language.clone(),
             "created_at": mongodb::bson::DateTime::now()
         };
+
+        // Mettre en quarantaine les messages indésirables
+        if !form.is_test {
+            let report = self.spam.score(&spam_input(&form)).await?;
+            if report.score >= self.spam.threshold() {
+                tracing::info!(
+                    "Message {reference} mis en quarantaine (score {:.1} : {})",
+                    report.score,
+                    report.reasons.join(", ")
+                );
+                doc.insert("spam_score", report.score);
+                doc.insert("spam_reasons", report.reasons);
+                self.db
+                    .collection::<Document>(QUARANTINE_COLLECTION)
+                    .insert_one(doc)
+                    .await?;
+                return Ok(());
+            }
+        }
+
+        let collection = self.db.collection::<Document>(&collection_name);
+        println!("Insertion du document dans la collection {collection_name}...");
         println!("Document à insérer : {doc:?}");
 
         let result = collection.insert_one(doc).await?;
         println!("Document inséré avec l'ID : {:?}", result.inserted_id);
 
         // Ne pas envoyer d'email si c'est un test
-        #[cfg(not(test))]
-        if !form.is_test && self.config.brevo_api_key != "test_key" {
-            // Envoyer l'email
-            self.send_email(&form).await?;
-        }
-
-        #[cfg(test)]
         if !form.is_test {
-            // Envoyer l'email
-            self.send_email(&form).await?;
+            self.deliver(&form, &reference).await?;
         }
 
         Ok(())
     }
 
+    /// Met en file d'attente la notification d'un message et son accusé de réception
+    async fn deliver(&self, form: &Request, reference: &str) -> Result<()> {
+        let job_id = self
+            .outbox
+            .enqueue(&contact_email(&self.config, &self.templates, form)?)
+            .await?;
+        tracing::debug!("Email mis en file d'attente : {job_id}");
+
+        if self.config.contact_auto_reply {
+            if let Err(e) = self.send_auto_reply(form, reference).await {
+                tracing::warn!("Accusé de réception non envoyé pour {reference} : {e:#}");
+            }
+        }
+        Ok(())
+    }
+
+    /// Liste les messages en quarantaine, du plus récent au plus ancien.
+    ///
+    /// # Errors
+    ///
+    /// Cette fonction retourne une erreur si la lecture des messages échoue.
+    pub async fn list_quarantine(&self, limit: i64) -> Result<Vec<QuarantinedMessage>> {
+        let docs: Vec<Document> = self
+            .db
+            .collection::<Document>(QUARANTINE_COLLECTION)
+            .find(doc! {})
+            .sort(doc! { "created_at": -1 })
+            .limit(limit)
+            .await?
+            .try_collect()
+            .await?;
+
+        docs.iter()
+            .map(|doc| {
+                let form = request_from_doc(doc)?;
+                Ok(QuarantinedMessage {
+                    id: doc.get_object_id("_id")?.to_hex(),
+                    reference: doc.get_str("reference").unwrap_or_default().to_string(),
+                    name: form.name,
+                    email: form.email,
+                    subject: form.subject,
+                    message: form.message,
+                    language: form.language,
+                    spam_score: doc.get_f64("spam_score").unwrap_or_default(),
+                    spam_reasons: doc
+                        .get_array("spam_reasons")
+                        .map(|reasons| {
+                            reasons
+                                .iter()
+                                .filter_map(|reason| reason.as_str().map(str::to_string))
+                                .collect()
+                        })
+                        .unwrap_or_default(),
+                    created_at: chrono::DateTime::from_timestamp_millis(
+                        doc.get_datetime("created_at")?.timestamp_millis(),
+                    )
+                    .unwrap_or_default(),
+                })
+            })
+            .collect()
+    }
+
+    /// Libère un message de la quarantaine : il est enregistré, notifié et
+    /// appris comme légitime. Retourne `false` si le message est introuvable.
+    ///
+    /// La copie en quarantaine n'est supprimée qu'une fois ces étapes réussies,
+    /// pour qu'une libération en échec puisse être relancée. L'enregistrement
+    /// remplace le message de même `_id`, une relance ne le duplique donc pas.
+    ///
+    /// # Errors
+    ///
+    /// Cette fonction retourne une erreur si :
+    /// - L'accès à la base de données échoue
+    /// - La mise en file d'attente de l'email échoue
+    pub async fn release_quarantined(&self, id: ObjectId) -> Result<bool> {
+        let quarantine = self.db.collection::<Document>(QUARANTINE_COLLECTION);
+        let Some(mut doc) = quarantine.find_one(doc! { "_id": id }).await? else {
+            return Ok(false);
+        };
+        doc.remove("spam_score");
+        doc.remove("spam_reasons");
+        let form = request_from_doc(&doc)?;
+        let reference = doc.get_str("reference").unwrap_or_default().to_string();
+
+        self.db
+            .collection::<Document>("contacts")
+            .replace_one(doc! { "_id": id }, doc)
+            .upsert(true)
+            .await?;
+        self.spam.train(id, &spam_input(&form), false).await?;
+        self.deliver(&form, &reference).await?;
+        quarantine.delete_one(doc! { "_id": id }).await?;
+        Ok(true)
+    }
+
+    /// Supprime un message de la quarantaine et l'apprend comme spam.
+    /// Retourne `false` si le message est introuvable.
+    ///
+    /// # Errors
+    ///
+    /// Cette fonction retourne une erreur si l'accès à la base de données échoue.
+    pub async fn reject_quarantined(&self, id: ObjectId) -> Result<bool> {
+        let Some(doc) = self
+            .db
+            .collection::<Document>(QUARANTINE_COLLECTION)
+            .find_one_and_delete(doc! { "_id": id })
+            .await?
+        else {
+            return Ok(false);
+        };
+        self.spam
+            .train(id, &spam_input(&request_from_doc(&doc)?), true)
+            .await?;
+        Ok(true)
+    }
+
+    /// Apprend un message reçu comme spam ou comme légitime.
+    /// Retourne `false` si le message est introuvable.
+    ///
+    /// # Errors
+    ///
+    /// Cette fonction retourne une erreur si l'accès à la base de données échoue.
+    pub async fn label_message(&self, id: ObjectId, spam: bool) -> Result<bool> {
+        let Some(doc) = self
+            .db
+            .collection::<Document>("contacts")
+            .find_one(doc! { "_id": id })
+            .await?
+        else {
+            return Ok(false);
+        };
+        self.spam
+            .train(id, &spam_input(&request_from_doc(&doc)?), spam)
+            .await?;
+        Ok(true)
+    }
+
+    /// Envoie l'accusé de réception d'un message au visiteur.
+    ///
+    /// Il est rédigé dans la langue du formulaire et contient une copie du
+    /// message et sa référence. Il n'est envoyé qu'aux messages ayant passé
+    /// les contrôles anti-spam, jamais à une adresse jetable, et au plus une
+    /// fois par adresse et par fenêtre de `CONTACT_AUTO_REPLY_WINDOW_HOURS`.
+    /// Retourne `false` lorsqu'il n'est pas envoyé.
+    ///
+    /// # Errors
+    ///
+    /// Cette fonction retourne une erreur si :
+    /// - L'enregistrement de l'envoi échoue
+    /// - Le rendu du template échoue
+    /// - La mise en file d'attente de l'email échoue
+    pub async fn send_auto_reply(&self, form: &Request, reference: &str) -> Result<bool> {
+        if self.domains.disposable().is_disposable(&form.email) {
+            return Ok(false);
+        }
+        if !self.claim_auto_reply(&form.email).await? {
+            return Ok(false);
+        }
+
+        let locale = form
+            .language
+            .as_deref()
+            .or_else(|| self.config.i18n_locales.first().map(String::as_str))
+            .unwrap_or_default();
+        let email = self.templates.render(
+            "auto_reply",
+            locale,
+            &json!({
+                "name": form.name,
+                "subject": form.subject,
+                "message": form.message,
+                "reference": reference,
+            }),
+        )?;
+        self.outbox
+            .enqueue(&EmailMessage {
+                to: form.email.clone(),
+                subject: email.subject,
+                html: email.html,
+                text: email.text,
+                reply_to: Some(self.config.recipient_email.clone()),
+            })
+            .await?;
+        Ok(true)
+    }
+
+    /// Réserve l'accusé de réception d'une adresse, `false` si un accusé a déjà été envoyé dans la fenêtre
+    async fn claim_auto_reply(&self, email: &str) -> Result<bool> {
+        let now = Utc::now();
+        let window = chrono::Duration::hours(
+            i64::try_from(self.config.contact_auto_reply_window_hours).unwrap_or(i64::MAX / 3600),
+        );
+        let expires_at = mongodb::bson::DateTime::from_millis((now + window).timestamp_millis());
+
+        // Un document encore valide fait échouer l'upsert sur l'index unique
+        let result = self
+            .db
+            .collection::<Document>(AUTO_REPLIES_COLLECTION)
+            .update_one(
+                doc! {
+                    "email": email.trim().to_lowercase(),
+                    "expires_at": { "$lte": mongodb::bson::DateTime::from_millis(now.timestamp_millis()) },
+                },
+                doc! { "$set": { "sent_at": mongodb::bson::DateTime::now(), "expires_at": expires_at } },
+            )
+            .upsert(true)
+            .await;
+        match result {
+            Ok(_) => Ok(true),
+            Err(e) => {
+                let error = anyhow::Error::from(e);
+                if is_duplicate_key(&error) {
+                    Ok(false)
+                } else {
+                    Err(error)
+                }
+            }
+        }
+    }
+
     #[cfg(test)]
     pub fn with_test_collections(mut self, _test_name: &str) -> Self {
         self.db = self.db.clone();
@@ -102,30 +444,12 @@ impl MessageService {
             .limit(limit)
             .await?;
 
-        let mut contacts = Vec::new();
-        for doc in cursor.try_collect::<Vec<Document>>().await? {
-            contacts.push(Request {
-                name: doc
-                    .get_str("name")
-                    .map_err(|e| anyhow::anyhow!(e))?
-                    .to_string(),
-                email: doc
-                    .get_str("email")
-                    .map_err(|e| anyhow::anyhow!(e))?
-                    .to_string(),
-                subject: doc
-                    .get_str("subject")
-                    .map_err(|e| anyhow::anyhow!(e))?
-                    .to_string(),
-                message: doc
-                    .get_str("message")
-                    .map_err(|e| anyhow::anyhow!(e))?
-                    .to_string(),
-                is_test: doc.get_bool("is_test").unwrap_or(false),
-                test_name: None,
-            });
-        }
-        Ok(contacts)
+        cursor
+            .try_collect::<Vec<Document>>()
+            .await?
+            .iter()
+            .map(request_from_doc)
+            .collect()
     }
 
     /// Récupère les statistiques des contacts.
@@ -164,50 +488,72 @@ impl MessageService {
             "daily_stats": stats
         }))
     }
+}
 
-    /// Envoie un email via le service Brevo.
-    ///
-    /// # Errors
-    ///
-    /// Cette fonction retourne une erreur si :
-    /// - La requête HTTP échoue
-    /// - Le service Brevo retourne une erreur
-    async fn send_email(&self, form: &Request) -> Result<()> {
-        let client = reqwest::Client::new();
-
-        let email_data = serde_json::json!({
-            "sender": {
-                "name": self.config.sender_name,
-                "email": self.config.sender_email
-            },
-            "to": [{
-                "email": self.config.recipient_email,
-                "name": self.config.recipient_email
-            }],
-            "subject": "Nouveau message de contact",
-            "htmlContent": format!(
-                "<p><strong>Nom:</strong> {}</p><p><strong>Email:</strong> {}</p><p><strong>Message:</strong></p><p>{}</p>",
-                form.name, form.email, form.message
-            )
-        });
-
-        #[cfg(test)]
-        let brevo_url = format!("{}/v3/smtp/email", self.brevo_url);
-        #[cfg(not(test))]
-        let brevo_url = "https://api.brevo.com/v3/smtp/email";
-
-        let response = client
-            .post(brevo_url)
-            .header("api-key", &self.config.brevo_api_key)
-            .header("Content-Type", "application/json")
-            .json(&email_data)
-            .send()
-            .await?;
-
-        if !response.status().is_success() {
-            anyhow::bail!("Failed to send email: {}", response.status());
-        }
+/// Formulaire d'un message enregistré
+fn request_from_doc(doc: &Document) -> Result<Request> {
+    let field = |key: &str| {
+        doc.get_str(key)
+            .map(str::to_string)
+            .map_err(|e| anyhow::anyhow!(e))
+    };
+    Ok(Request {
+        name: field("name")?,
+        email: field("email")?,
+        subject: field("subject")?,
+        message: field("message")?,
+        is_test: doc.get_bool("is_test").unwrap_or(false),
+        test_name: None,
+        language: doc.get_str("language").ok().map(str::to_string),
+        token: None,
+        website: None,
+        challenge: None,
+        proof: None,
+    })
+}
 
-        Ok(())
+/// Message of a form, as scored by the spam filter
+fn spam_input(form: &Request) -> SpamInput<'_> {
+    SpamInput {
+        subject: &form.subject,
+        message: &form.message,
+        language: form.language.as_deref(),
     }
 }
+
+/// Reference of a contact message given to the visitor, such as `C-20261018-0A1B2C`
+///
+/// The last bytes of the `ObjectId` are its counter, so references do not repeat in practice.
+fn contact_reference(id: &ObjectId) -> String {
+    let bytes = id.bytes();
+    format!(
+        "C-{}-{}",
+        Utc::now().format("%Y%m%d"),
+        hex::encode_upper(&bytes[9..])
+    )
+}
+
+/// Email notifying the recipient of a new contact message, answered to the visitor
+///
+/// It is written in the default locale of the site, the first of `I18N_LOCALES`.
+fn contact_email(
+    config: &Config,
+    templates: &EmailTemplates,
+    form: &Request,
+) -> Result<EmailMessage> {
+    let locale = config.i18n_locales.first().map_or("fr", String::as_str);
+    let email = templates.render_contact(
+        locale,
+        &form.name,
+        &form.email,
+        &form.subject,
+        &form.message,
+    )?;
+    Ok(EmailMessage {
+        to: config.recipient_email.clone(),
+        subject: email.subject,
+        html: email.html,
+        text: email.text,
+        reply_to: Some(form.email.clone()),
+    })
+}
@@ -0,0 +1,258 @@
+use anyhow::Result;
+use chrono::Utc;
+use mongodb::bson::{doc, Document};
+use mongodb::options::ReturnDocument;
+use mongodb::Database;
+use sha2::{Digest, Sha256};
+use std::net::IpAddr;
+use std::time::Duration;
+
+use crate::config::Config;
+use crate::services::email_domains;
+use crate::services::i18n::{match_locale, negotiate};
+
+/// Collection of the submission counters, one per key and window
+const COLLECTION: &str = "contact_throttles";
+
+/// Message shown to a throttled visitor, by language
+const MESSAGES: [(&str, &str); 2] = [
+    (
+        "fr",
+        "Trop de messages envoyés, veuillez réessayer plus tard",
+    ),
+    ("en", "Too many messages sent, please try again later"),
+];
+
+/// What a limit counts the submissions of
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ThrottleScope {
+    Ip,
+    Email,
+    /// All the submissions
+    Global,
+}
+
+impl ThrottleScope {
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Ip => "ip",
+            Self::Email => "email",
+            Self::Global => "global",
+        }
+    }
+}
+
+/// At most `max` submissions per `window`, disabled when `max` is 0
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ThrottleLimit {
+    pub max: u64,
+    pub window: Duration,
+}
+
+/// A submission over one of the limits
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Throttled {
+    pub scope: ThrottleScope,
+    /// Time until the window of the limit ends
+    pub retry_after: Duration,
+}
+
+/// Start and end of the fixed window containing `now` (milliseconds since the epoch)
+fn window_bounds(now: i64, window: Duration) -> (i64, i64) {
+    let length = i64::try_from(window.as_millis()).unwrap_or(i64::MAX).max(1);
+    let start = now - now.rem_euclid(length);
+    (start, start.saturating_add(length))
+}
+
+/// Key of a counter, hashed so that addresses are not stored in clear
+fn counter_id(scope: ThrottleScope, key: &str, window_start: i64) -> String {
+    let hash = Sha256::digest(key.as_bytes());
+    format!(
+        "{}:{}:{window_start}",
+        scope.as_str(),
+        hex::encode(&hash[..16])
+    )
+}
+
+/// Contact submission limits per IP, per address and overall
+///
+/// Submissions are counted in fixed windows stored in `contact_throttles`,
+/// one document per key and window removed by a TTL index once the window
+/// ends, so the counts survive restarts and are shared between instances.
+pub struct ContactThrottle {
+    db: Database,
+    per_ip: ThrottleLimit,
+    per_email: ThrottleLimit,
+    global: ThrottleLimit,
+    locales: Vec<String>,
+}
+
+impl ContactThrottle {
+    #[must_use]
+    pub const fn new(
+        db: Database,
+        per_ip: ThrottleLimit,
+        per_email: ThrottleLimit,
+        global: ThrottleLimit,
+        locales: Vec<String>,
+    ) -> Self {
+        Self {
+            db,
+            per_ip,
+            per_email,
+            global,
+            locales,
+        }
+    }
+
+    /// Limits of the `CONTACT_LIMIT_*` settings
+    #[must_use]
+    pub fn from_config(db: Database, config: &Config) -> Self {
+        Self::new(
+            db,
+            ThrottleLimit {
+                max: config.contact_limit_per_ip,
+                window: Duration::from_secs(config.contact_limit_ip_window_secs),
+            },
+            ThrottleLimit {
+                max: config.contact_limit_per_email,
+                window: Duration::from_secs(config.contact_limit_email_window_secs),
+            },
+            ThrottleLimit {
+                max: config.contact_limit_global,
+                window: Duration::from_secs(config.contact_limit_global_window_secs),
+            },
+            config.i18n_locales.clone(),
+        )
+    }
+
+    /// Counts a submission against a limit, returning the throttle when it is over.
+    async fn hit(
+        &self,
+        scope: ThrottleScope,
+        limit: ThrottleLimit,
+        key: &str,
+        now: i64,
+    ) -> Result<Option<Throttled>> {
+        if limit.max == 0 {
+            return Ok(None);
+        }
+
+        let (start, end) = window_bounds(now, limit.window);
+        let count = self
+            .db
+            .collection::<Document>(COLLECTION)
+            .find_one_and_update(
+                doc! { "_id": counter_id(scope, key, start) },
+                doc! {
+                    "$inc": { "count": 1_i64 },
+                    "$setOnInsert": {
+                        "scope": scope.as_str(),
+                        "expires_at": mongodb::bson::DateTime::from_millis(end),
+                    },
+                },
+            )
+            .upsert(true)
+            .return_document(ReturnDocument::After)
+            .await?
+            .and_then(|doc| doc.get_i64("count").ok())
+            .unwrap_or_default();
+
+        Ok(
+            (u64::try_from(count).unwrap_or_default() > limit.max).then(|| Throttled {
+                scope,
+                retry_after: Duration::from_millis(u64::try_from(end - now).unwrap_or_default()),
+            }),
+        )
+    }
+
+    /// Counts a submission from `ip` for `email`, checking the IP limit, then
+    /// the address limit, then the global limit.
+    ///
+    /// A submission refused by one limit is not counted by the next ones.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the counters cannot be updated.
+    pub async fn check(&self, ip: IpAddr, email: &str) -> Result<Option<Throttled>> {
+        let now = Utc::now().timestamp_millis();
+        let email = match email.trim().rsplit_once('@') {
+            Some((local, _)) => format!(
+                "{}@{}",
+                local.to_lowercase(),
+                email_domains::domain(email).unwrap_or_default()
+            ),
+            None => email.trim().to_lowercase(),
+        };
+
+        for (scope, limit, key) in [
+            (ThrottleScope::Ip, self.per_ip, ip.to_string()),
+            (ThrottleScope::Email, self.per_email, email),
+            (ThrottleScope::Global, self.global, String::new()),
+        ] {
+            if let Some(throttled) = self.hit(scope, limit, &key, now).await? {
+                return Ok(Some(throttled));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Locale of the form language, else of `Accept-Language`, else the default one
+    #[must_use]
+    pub fn locale<'a>(&'a self, language: Option<&str>, accept_language: Option<&str>) -> &'a str {
+        language
+            .and_then(|language| match_locale(language, &self.locales))
+            .or_else(|| accept_language.and_then(|header| negotiate(header, &self.locales)))
+            .or_else(|| self.locales.first().map(String::as_str))
+            .unwrap_or("fr")
+    }
+}
+
+/// Message shown to a throttled visitor in `locale`, French when it has none
+#[must_use]
+pub fn throttled_message(locale: &str) -> &'static str {
+    let language = locale.split('-').next().unwrap_or_default();
+    MESSAGES
+        .iter()
+        .find(|(code, _)| code.eq_ignore_ascii_case(language))
+        .unwrap_or(&MESSAGES[0])
+        .1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_window_bounds() {
+        let hour = Duration::from_secs(3600);
+        assert_eq!(window_bounds(7_200_000, hour), (7_200_000, 10_800_000));
+        assert_eq!(window_bounds(7_250_000, hour), (7_200_000, 10_800_000));
+        assert_eq!(window_bounds(10_799_999, hour), (7_200_000, 10_800_000));
+    }
+
+    #[test]
+    fn test_counter_id() {
+        let id = counter_id(ThrottleScope::Email, "john@example.com", 7_200_000);
+        assert!(id.starts_with("email:"));
+        assert!(id.ends_with(":7200000"));
+        assert!(!id.contains("john"));
+        assert_ne!(
+            id,
+            counter_id(ThrottleScope::Ip, "john@example.com", 7_200_000)
+        );
+    }
+
+    #[test]
+    fn test_throttled_message() {
+        assert_eq!(
+            throttled_message("en-GB"),
+            "Too many messages sent, please try again later"
+        );
+        assert_eq!(
+            throttled_message("de"),
+            "Trop de messages envoyés, veuillez réessayer plus tard"
+        );
+    }
+}
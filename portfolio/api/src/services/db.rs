@@ -1,4 +1,5 @@
 use anyhow::Result;
+use mongodb::error::{ErrorKind, WriteFailure};
 use mongodb::IndexModel;
 use mongodb::{
     bson::{doc, Document},
@@ -30,6 +31,19 @@ pub async fn initialize() -> Result<()> {
     Ok(())
 }
 
+/// Returns true when the error comes from a unique index violation
+#[must_use]
+pub fn is_duplicate_key(error: &anyhow::Error) -> bool {
+    error
+        .downcast_ref::<mongodb::error::Error>()
+        .is_some_and(|error| {
+            matches!(
+                error.kind.as_ref(),
+                ErrorKind::Write(WriteFailure::WriteError(write_error)) if write_error.code == 11000
+            )
+        })
+}
+
 /// Initializes the database collections with their indexes.
 ///
 /// # Errors
@@ -39,7 +53,28 @@ pub async fn initialize() -> Result<()> {
 /// - Index creation fails
 /// - MongoDB operation fails
 async fn init_collections(db: &Database) -> Result<()> {
-    let collections = ["portfolio", "contacts"];
+    let collections = [
+        "portfolio",
+        "contacts",
+        "rss_moderation",
+        "og_cache",
+        "image_meta",
+        "link_status",
+        "github_repos",
+        "projects",
+        "posts",
+        "translations",
+        "analytics_daily",
+        "analytics_visitors",
+        "analytics_salts",
+        "email_outbox",
+        "contact_auto_replies",
+        "contact_tokens",
+        "contact_challenges",
+        "contact_throttles",
+        "contact_quarantine",
+        "spam_training",
+    ];
     println!("Starting collection initialization");
 
     // First step: create collections
@@ -78,6 +113,18 @@ async fn init_collections(db: &Database) -> Result<()> {
                 collection.create_index(index).await?;
                 println!("url/pub_date index created successfully");
 
+                // Index for editorial moderation lookups
+                println!("Creating item_id index for portfolio");
+                let index = IndexModel::builder().keys(doc! { "item_id": 1 }).build();
+                collection.create_index(index).await?;
+                println!("item_id index created successfully");
+
+                // Index for image proxy lookups
+                println!("Creating image_hash index for portfolio");
+                let index = IndexModel::builder().keys(doc! { "image_hash": 1 }).build();
+                collection.create_index(index).await?;
+                println!("image_hash index created successfully");
+
                 // TTL index to clean up old articles (90 days)
                 println!("Creating TTL index on pub_date for portfolio");
                 let ttl_index = IndexModel::builder()
@@ -118,6 +165,462 @@ async fn init_collections(db: &Database) -> Result<()> {
                 collection.create_index(ttl_index).await?;
                 println!("TTL index created successfully for contacts");
             }
+            "rss_moderation" => {
+                println!("Configuring indexes for rss_moderation");
+
+                // One set of editorial flags per item
+                println!("Creating unique item_id index for rss_moderation");
+                let index = IndexModel::builder()
+                    .keys(doc! { "item_id": 1 })
+                    .options(
+                        mongodb::options::IndexOptions::builder()
+                            .unique(true)
+                            .build(),
+                    )
+                    .build();
+                collection.create_index(index).await?;
+                println!("item_id index created successfully for rss_moderation");
+            }
+            "og_cache" => {
+                println!("Configuring indexes for og_cache");
+
+                // Each page is scraped at most once
+                println!("Creating unique url index for og_cache");
+                let index = IndexModel::builder()
+                    .keys(doc! { "url": 1 })
+                    .options(
+                        mongodb::options::IndexOptions::builder()
+                            .unique(true)
+                            .build(),
+                    )
+                    .build();
+                collection.create_index(index).await?;
+                println!("url index created successfully for og_cache");
+
+                // TTL index aligned with the lifetime of articles (90 days)
+                println!("Creating TTL index on fetched_at for og_cache");
+                let ttl_index = IndexModel::builder()
+                    .keys(doc! { "fetched_at": 1 })
+                    .options(
+                        mongodb::options::IndexOptions::builder()
+                            .expire_after(Duration::from_secs(90 * 24 * 60 * 60))
+                            .build(),
+                    )
+                    .build();
+                collection.create_index(ttl_index).await?;
+                println!("TTL index created successfully for og_cache");
+            }
+            "image_meta" => {
+                println!("Configuring indexes for image_meta");
+
+                // Each image is downloaded at most once
+                println!("Creating unique url index for image_meta");
+                let index = IndexModel::builder()
+                    .keys(doc! { "url": 1 })
+                    .options(
+                        mongodb::options::IndexOptions::builder()
+                            .unique(true)
+                            .build(),
+                    )
+                    .build();
+                collection.create_index(index).await?;
+                println!("url index created successfully for image_meta");
+
+                // TTL index aligned with the lifetime of articles (90 days)
+                println!("Creating TTL index on fetched_at for image_meta");
+                let ttl_index = IndexModel::builder()
+                    .keys(doc! { "fetched_at": 1 })
+                    .options(
+                        mongodb::options::IndexOptions::builder()
+                            .expire_after(Duration::from_secs(90 * 24 * 60 * 60))
+                            .build(),
+                    )
+                    .build();
+                collection.create_index(ttl_index).await?;
+                println!("TTL index created successfully for image_meta");
+            }
+            "link_status" => {
+                println!("Configuring indexes for link_status");
+
+                // One status per checked URL
+                println!("Creating unique url index for link_status");
+                let index = IndexModel::builder()
+                    .keys(doc! { "url": 1 })
+                    .options(
+                        mongodb::options::IndexOptions::builder()
+                            .unique(true)
+                            .build(),
+                    )
+                    .build();
+                collection.create_index(index).await?;
+                println!("url index created successfully for link_status");
+
+                // Index for dead URL lookups during sync
+                println!("Creating dead index for link_status");
+                let index = IndexModel::builder().keys(doc! { "dead": 1 }).build();
+                collection.create_index(index).await?;
+                println!("dead index created successfully for link_status");
+
+                // Statuses of URLs that are no longer checked expire with the articles (90 days)
+                println!("Creating TTL index on checked_at for link_status");
+                let ttl_index = IndexModel::builder()
+                    .keys(doc! { "checked_at": 1 })
+                    .options(
+                        mongodb::options::IndexOptions::builder()
+                            .expire_after(Duration::from_secs(90 * 24 * 60 * 60))
+                            .build(),
+                    )
+                    .build();
+                collection.create_index(ttl_index).await?;
+                println!("TTL index created successfully for link_status");
+            }
+            "github_repos" => {
+                println!("Configuring indexes for github_repos");
+
+                // Repositories are upserted by name
+                println!("Creating unique name index for github_repos");
+                let index = IndexModel::builder()
+                    .keys(doc! { "name": 1 })
+                    .options(
+                        mongodb::options::IndexOptions::builder()
+                            .unique(true)
+                            .build(),
+                    )
+                    .build();
+                collection.create_index(index).await?;
+                println!("name index created successfully for github_repos");
+
+                // Index matching the showcase order
+                println!("Creating pinned/stars/pushed_at index for github_repos");
+                let index = IndexModel::builder()
+                    .keys(doc! { "pinned": -1, "stars": -1, "pushed_at": -1 })
+                    .build();
+                collection.create_index(index).await?;
+                println!("pinned/stars/pushed_at index created successfully for github_repos");
+            }
+            "projects" => {
+                println!("Configuring indexes for projects");
+
+                // Case studies are addressed by slug
+                println!("Creating unique slug index for projects");
+                let index = IndexModel::builder()
+                    .keys(doc! { "slug": 1 })
+                    .options(
+                        mongodb::options::IndexOptions::builder()
+                            .unique(true)
+                            .build(),
+                    )
+                    .build();
+                collection.create_index(index).await?;
+                println!("slug index created successfully for projects");
+
+                // Index matching the public listing order
+                println!("Creating status/position/published_at index for projects");
+                let index = IndexModel::builder()
+                    .keys(doc! { "status": 1, "position": 1, "published_at": -1 })
+                    .build();
+                collection.create_index(index).await?;
+                println!("status/position/published_at index created successfully for projects");
+
+                // Index for tag filtering
+                println!("Creating tags index for projects");
+                let index = IndexModel::builder().keys(doc! { "tags": 1 }).build();
+                collection.create_index(index).await?;
+                println!("tags index created successfully for projects");
+            }
+            "posts" => {
+                println!("Configuring indexes for posts");
+
+                // Posts are addressed by slug
+                println!("Creating unique slug index for posts");
+                let index = IndexModel::builder()
+                    .keys(doc! { "slug": 1 })
+                    .options(
+                        mongodb::options::IndexOptions::builder()
+                            .unique(true)
+                            .build(),
+                    )
+                    .build();
+                collection.create_index(index).await?;
+                println!("slug index created successfully for posts");
+
+                // Index for the published listing, newest first
+                println!("Creating draft/publish_at index for posts");
+                let index = IndexModel::builder()
+                    .keys(doc! { "draft": 1, "publish_at": -1 })
+                    .build();
+                collection.create_index(index).await?;
+                println!("draft/publish_at index created successfully for posts");
+
+                // Index for the tag pages
+                println!("Creating tags index for posts");
+                let index = IndexModel::builder().keys(doc! { "tags": 1 }).build();
+                collection.create_index(index).await?;
+                println!("tags index created successfully for posts");
+            }
+            "translations" => {
+                println!("Configuring indexes for translations");
+
+                // One value per locale, namespace and key; also serves the bundle queries
+                println!("Creating unique locale/namespace/key index for translations");
+                let index = IndexModel::builder()
+                    .keys(doc! { "locale": 1, "namespace": 1, "key": 1 })
+                    .options(
+                        mongodb::options::IndexOptions::builder()
+                            .unique(true)
+                            .build(),
+                    )
+                    .build();
+                collection.create_index(index).await?;
+                println!("locale/namespace/key index created successfully for translations");
+            }
+            "analytics_daily" => {
+                println!("Configuring indexes for analytics_daily");
+
+                // One counter per day, kind, page, referrer and outbound target
+                println!("Creating unique counter index for analytics_daily");
+                let index = IndexModel::builder()
+                    .keys(doc! { "day": 1, "kind": 1, "path": 1, "referrer": 1, "target": 1 })
+                    .options(
+                        mongodb::options::IndexOptions::builder()
+                            .unique(true)
+                            .build(),
+                    )
+                    .build();
+                collection.create_index(index).await?;
+                println!("counter index created successfully for analytics_daily");
+            }
+            "analytics_visitors" => {
+                println!("Configuring indexes for analytics_visitors");
+
+                // A visitor is counted once per day and scope (page or whole site)
+                println!("Creating unique day/scope/visitor index for analytics_visitors");
+                let index = IndexModel::builder()
+                    .keys(doc! { "day": 1, "scope": 1, "visitor": 1 })
+                    .options(
+                        mongodb::options::IndexOptions::builder()
+                            .unique(true)
+                            .build(),
+                    )
+                    .build();
+                collection.create_index(index).await?;
+                println!("day/scope/visitor index created successfully for analytics_visitors");
+
+                // Visitor hashes are only needed for the current day (2 days)
+                println!("Creating TTL index on created_at for analytics_visitors");
+                let ttl_index = IndexModel::builder()
+                    .keys(doc! { "created_at": 1 })
+                    .options(
+                        mongodb::options::IndexOptions::builder()
+                            .expire_after(Duration::from_secs(2 * 24 * 60 * 60))
+                            .build(),
+                    )
+                    .build();
+                collection.create_index(ttl_index).await?;
+                println!("TTL index created successfully for analytics_visitors");
+            }
+            "analytics_salts" => {
+                println!("Configuring indexes for analytics_salts");
+
+                // One salt per day, shared by all instances
+                println!("Creating unique day index for analytics_salts");
+                let index = IndexModel::builder()
+                    .keys(doc! { "day": 1 })
+                    .options(
+                        mongodb::options::IndexOptions::builder()
+                            .unique(true)
+                            .build(),
+                    )
+                    .build();
+                collection.create_index(index).await?;
+                println!("day index created successfully for analytics_salts");
+
+                // Expired salts make the old visitor hashes impossible to recompute (2 days)
+                println!("Creating TTL index on created_at for analytics_salts");
+                let ttl_index = IndexModel::builder()
+                    .keys(doc! { "created_at": 1 })
+                    .options(
+                        mongodb::options::IndexOptions::builder()
+                            .expire_after(Duration::from_secs(2 * 24 * 60 * 60))
+                            .build(),
+                    )
+                    .build();
+                collection.create_index(ttl_index).await?;
+                println!("TTL index created successfully for analytics_salts");
+            }
+            "email_outbox" => {
+                println!("Configuring indexes for email_outbox");
+
+                // Due jobs, leased by the email worker
+                println!("Creating status/next_attempt_at index for email_outbox");
+                let due_index = IndexModel::builder()
+                    .keys(doc! { "status": 1, "next_attempt_at": 1 })
+                    .build();
+                collection.create_index(due_index).await?;
+                println!("status/next_attempt_at index created successfully for email_outbox");
+
+                // Jobs whose lease expired
+                println!("Creating status/lease_until index for email_outbox");
+                let lease_index = IndexModel::builder()
+                    .keys(doc! { "status": 1, "lease_until": 1 })
+                    .build();
+                collection.create_index(lease_index).await?;
+                println!("status/lease_until index created successfully for email_outbox");
+
+                // Sent emails are kept 30 days, dead-lettered ones until handled
+                println!("Creating TTL index on sent_at for email_outbox");
+                let ttl_index = IndexModel::builder()
+                    .keys(doc! { "sent_at": 1 })
+                    .options(
+                        mongodb::options::IndexOptions::builder()
+                            .expire_after(Duration::from_secs(30 * 24 * 60 * 60))
+                            .build(),
+                    )
+                    .build();
+                collection.create_index(ttl_index).await?;
+                println!("TTL index created successfully for email_outbox");
+            }
+            "contact_auto_replies" => {
+                println!("Configuring indexes for contact_auto_replies");
+
+                // One acknowledgment per address and window
+                println!("Creating unique email index for contact_auto_replies");
+                let index = IndexModel::builder()
+                    .keys(doc! { "email": 1 })
+                    .options(
+                        mongodb::options::IndexOptions::builder()
+                            .unique(true)
+                            .build(),
+                    )
+                    .build();
+                collection.create_index(index).await?;
+                println!("email index created successfully for contact_auto_replies");
+
+                // Documents expire at the end of their window
+                println!("Creating TTL index on expires_at for contact_auto_replies");
+                let ttl_index = IndexModel::builder()
+                    .keys(doc! { "expires_at": 1 })
+                    .options(
+                        mongodb::options::IndexOptions::builder()
+                            .expire_after(Duration::from_secs(0))
+                            .build(),
+                    )
+                    .build();
+                collection.create_index(ttl_index).await?;
+                println!("TTL index created successfully for contact_auto_replies");
+            }
+            "contact_tokens" => {
+                println!("Configuring indexes for contact_tokens");
+
+                // A form token is redeemed once
+                println!("Creating unique nonce index for contact_tokens");
+                let index = IndexModel::builder()
+                    .keys(doc! { "nonce": 1 })
+                    .options(
+                        mongodb::options::IndexOptions::builder()
+                            .unique(true)
+                            .build(),
+                    )
+                    .build();
+                collection.create_index(index).await?;
+                println!("nonce index created successfully for contact_tokens");
+
+                // Redeemed tokens are kept until they expire
+                println!("Creating TTL index on expires_at for contact_tokens");
+                let ttl_index = IndexModel::builder()
+                    .keys(doc! { "expires_at": 1 })
+                    .options(
+                        mongodb::options::IndexOptions::builder()
+                            .expire_after(Duration::from_secs(0))
+                            .build(),
+                    )
+                    .build();
+                collection.create_index(ttl_index).await?;
+                println!("TTL index created successfully for contact_tokens");
+            }
+            "contact_challenges" => {
+                println!("Configuring indexes for contact_challenges");
+
+                // A challenge is solved once
+                println!("Creating unique nonce index for contact_challenges");
+                let index = IndexModel::builder()
+                    .keys(doc! { "nonce": 1 })
+                    .options(
+                        mongodb::options::IndexOptions::builder()
+                            .unique(true)
+                            .build(),
+                    )
+                    .build();
+                collection.create_index(index).await?;
+                println!("nonce index created successfully for contact_challenges");
+
+                // Recent solutions set the difficulty of the next challenges
+                println!("Creating solved_at index for contact_challenges");
+                let index = IndexModel::builder().keys(doc! { "solved_at": -1 }).build();
+                collection.create_index(index).await?;
+                println!("solved_at index created successfully for contact_challenges");
+
+                // Solved challenges are kept until they expire
+                println!("Creating TTL index on expires_at for contact_challenges");
+                let ttl_index = IndexModel::builder()
+                    .keys(doc! { "expires_at": 1 })
+                    .options(
+                        mongodb::options::IndexOptions::builder()
+                            .expire_after(Duration::from_secs(0))
+                            .build(),
+                    )
+                    .build();
+                collection.create_index(ttl_index).await?;
+                println!("TTL index created successfully for contact_challenges");
+            }
+            "contact_throttles" => {
+                println!("Configuring indexes for contact_throttles");
+
+                // Counters are dropped once their window ends
+                println!("Creating TTL index on expires_at for contact_throttles");
+                let ttl_index = IndexModel::builder()
+                    .keys(doc! { "expires_at": 1 })
+                    .options(
+                        mongodb::options::IndexOptions::builder()
+                            .expire_after(Duration::from_secs(0))
+                            .build(),
+                    )
+                    .build();
+                collection.create_index(ttl_index).await?;
+                println!("TTL index created successfully for contact_throttles");
+            }
+            "contact_quarantine" => {
+                println!("Configuring indexes for contact_quarantine");
+
+                // Unreviewed messages are dropped like old contacts (30 days)
+                println!("Creating TTL index on created_at for contact_quarantine");
+                let ttl_index = IndexModel::builder()
+                    .keys(doc! { "created_at": -1 })
+                    .options(
+                        mongodb::options::IndexOptions::builder()
+                            .expire_after(Duration::from_secs(30 * 24 * 60 * 60))
+                            .build(),
+                    )
+                    .build();
+                collection.create_index(ttl_index).await?;
+                println!("TTL index created successfully for contact_quarantine");
+            }
+            "spam_training" => {
+                println!("Configuring indexes for spam_training");
+
+                // One label per contact message
+                println!("Creating unique contact_id index for spam_training");
+                let index = IndexModel::builder()
+                    .keys(doc! { "contact_id": 1 })
+                    .options(
+                        mongodb::options::IndexOptions::builder()
+                            .unique(true)
+                            .build(),
+                    )
+                    .build();
+                collection.create_index(index).await?;
+                println!("contact_id index created successfully for spam_training");
+            }
             _ => {}
         }
     }
@@ -0,0 +1,511 @@
+//! Checks on the domain of email addresses
+
+use anyhow::{Context, Result};
+use hickory_resolver::config::{ConnectionConfig, NameServerConfig, ResolverConfig};
+use hickory_resolver::net::runtime::TokioRuntimeProvider;
+use hickory_resolver::proto::rr::RData;
+use hickory_resolver::TokioResolver;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::{Mutex, RwLock};
+use std::time::{Duration, Instant};
+
+use crate::config::Config;
+
+/// Disposable email providers, matched with their subdomains
+const DISPOSABLE_DOMAINS: [&str; 20] = [
+    "10minutemail.com",
+    "dispostable.com",
+    "emailondeck.com",
+    "fakeinbox.com",
+    "getnada.com",
+    "guerrillamail.com",
+    "guerrillamail.info",
+    "jetable.org",
+    "maildrop.cc",
+    "mailinator.com",
+    "mintemail.com",
+    "mohmal.com",
+    "sharklasers.com",
+    "spamgourmet.com",
+    "temp-mail.org",
+    "tempmail.dev",
+    "throwawaymail.com",
+    "trashmail.com",
+    "yopmail.com",
+    "yopmail.fr",
+];
+
+/// Entries kept in the DNS cache before expired ones are purged
+const MAX_CACHED_DOMAINS: usize = 10_000;
+
+/// ASCII form of a domain, lowercased, internationalized labels in punycode
+#[must_use]
+pub fn normalize_domain(domain: &str) -> Option<String> {
+    let domain = idna::domain_to_ascii(domain.trim().trim_end_matches('.')).ok()?;
+    (!domain.is_empty()).then_some(domain)
+}
+
+/// Domain of an email address, normalized with [`normalize_domain`]
+#[must_use]
+pub fn domain(email: &str) -> Option<String> {
+    let (_, domain) = email.trim().rsplit_once('@')?;
+    normalize_domain(domain)
+}
+
+/// Reason the domain of an email address is refused
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DomainError {
+    Invalid,
+    Disposable,
+    /// Neither MX nor A/AAAA records
+    NoMailServer,
+}
+
+impl fmt::Display for DomainError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Invalid => "Le domaine de l'email n'est pas valide",
+            Self::Disposable => "Les adresses email jetables ne sont pas acceptées",
+            Self::NoMailServer => "Le domaine de l'email ne peut pas recevoir d'emails",
+        })
+    }
+}
+
+impl std::error::Error for DomainError {}
+
+/// Disposable email providers, matched with their subdomains
+///
+/// The built-in providers are extended by an optional file with one domain
+/// per line, `#` starting a comment. The file can be reloaded while the
+/// server runs to update the list.
+pub struct DisposableDomains {
+    path: Option<PathBuf>,
+    domains: RwLock<HashSet<String>>,
+}
+
+impl DisposableDomains {
+    /// Built-in providers and those of `path`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read.
+    pub fn new(path: Option<PathBuf>) -> Result<Self> {
+        let list = Self {
+            path,
+            domains: RwLock::new(HashSet::new()),
+        };
+        list.reload()?;
+        Ok(list)
+    }
+
+    /// Built-in providers only
+    #[must_use]
+    pub fn builtin() -> Self {
+        Self {
+            path: None,
+            domains: RwLock::new(Self::builtin_domains()),
+        }
+    }
+
+    fn builtin_domains() -> HashSet<String> {
+        DISPOSABLE_DOMAINS.iter().map(ToString::to_string).collect()
+    }
+
+    /// Reloads the file, returning the number of domains now listed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read, the list is then kept.
+    pub fn reload(&self) -> Result<usize> {
+        let mut domains = Self::builtin_domains();
+        if let Some(path) = &self.path {
+            let text = std::fs::read_to_string(path)
+                .with_context(|| format!("Cannot read {}", path.display()))?;
+            domains.extend(
+                text.lines()
+                    .map(|line| line.split('#').next().unwrap_or_default())
+                    .filter_map(normalize_domain),
+            );
+        }
+
+        let count = domains.len();
+        *self.domains.write().unwrap_or_else(|e| e.into_inner()) = domains;
+        Ok(count)
+    }
+
+    /// Number of listed domains
+    #[must_use]
+    pub fn count(&self) -> usize {
+        self.domains.read().unwrap_or_else(|e| e.into_inner()).len()
+    }
+
+    /// Returns true when the normalized `domain` or one of its parents is listed
+    #[must_use]
+    pub fn contains(&self, domain: &str) -> bool {
+        let domains = self.domains.read().unwrap_or_else(|e| e.into_inner());
+        let mut candidate = domain;
+        loop {
+            if domains.contains(candidate) {
+                return true;
+            }
+            match candidate.split_once('.') {
+                Some((_, parent)) => candidate = parent,
+                None => return false,
+            }
+        }
+    }
+
+    /// Returns true when the address belongs to a disposable email provider
+    #[must_use]
+    pub fn is_disposable(&self, email: &str) -> bool {
+        domain(email).is_some_and(|domain| self.contains(&domain))
+    }
+}
+
+/// Checks that domains can receive email, caching the answers
+///
+/// A domain accepts mail when it has MX records, other than the null MX of
+/// RFC 7505, or failing that A/AAAA records.
+pub struct MailServers {
+    resolver: TokioResolver,
+    timeout: Duration,
+    cache_ttl: Duration,
+    cache: Mutex<HashMap<String, (Instant, bool)>>,
+}
+
+impl MailServers {
+    #[must_use]
+    pub fn new(resolver: TokioResolver, timeout: Duration, cache_ttl: Duration) -> Self {
+        Self {
+            resolver,
+            timeout,
+            cache_ttl,
+            cache: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Resolver of the `servers`, or of the system configuration when empty
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the system configuration cannot be read.
+    pub fn resolver(servers: &[SocketAddr], timeout: Duration) -> Result<TokioResolver> {
+        let mut builder = if servers.is_empty() {
+            TokioResolver::builder_tokio().context("Cannot read the system DNS configuration")?
+        } else {
+            let name_servers = servers
+                .iter()
+                .map(|server| {
+                    let mut udp = ConnectionConfig::udp();
+                    udp.port = server.port();
+                    let mut tcp = ConnectionConfig::tcp();
+                    tcp.port = server.port();
+                    NameServerConfig::new(server.ip(), true, vec![udp, tcp])
+                })
+                .collect();
+            TokioResolver::builder_with_config(
+                ResolverConfig::from_name_servers(name_servers),
+                TokioRuntimeProvider::default(),
+            )
+        };
+        builder.options_mut().timeout = timeout;
+        builder.options_mut().attempts = 1;
+        builder.build().context("Cannot build the DNS resolver")
+    }
+
+    /// Resolver of `CONTACT_DNS_SERVERS` with the configured timeout and cache
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a server address is invalid or the resolver
+    /// cannot be built.
+    pub fn from_config(config: &Config) -> Result<Self> {
+        let servers = config
+            .contact_dns_servers
+            .as_deref()
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|server| !server.is_empty())
+            .map(|server| {
+                server
+                    .parse::<SocketAddr>()
+                    .or_else(|_| format!("{server}:53").parse())
+                    .with_context(|| format!("Invalid DNS server {server}"))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let timeout = Duration::from_millis(config.contact_dns_timeout_ms);
+        Ok(Self::new(
+            Self::resolver(&servers, timeout)?,
+            timeout,
+            Duration::from_secs(config.contact_dns_cache_secs),
+        ))
+    }
+
+    /// Returns true when the normalized `domain` can receive email.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the DNS lookup fails or times out; failures are
+    /// not cached.
+    pub async fn accepts_mail(&self, domain: &str) -> Result<bool> {
+        if let Some(&(checked_at, accepts)) = self
+            .cache
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(domain)
+        {
+            if checked_at.elapsed() < self.cache_ttl {
+                return Ok(accepts);
+            }
+        }
+
+        // MX then A/AAAA lookups, each bounded by the resolver timeout
+        let accepts = tokio::time::timeout(self.timeout * 2, self.lookup(domain))
+            .await
+            .with_context(|| format!("DNS lookup of {domain} timed out"))??;
+
+        let mut cache = self.cache.lock().unwrap_or_else(|e| e.into_inner());
+        if cache.len() >= MAX_CACHED_DOMAINS {
+            let ttl = self.cache_ttl;
+            cache.retain(|_, (checked_at, _)| checked_at.elapsed() < ttl);
+        }
+        cache.insert(domain.to_string(), (Instant::now(), accepts));
+        Ok(accepts)
+    }
+
+    async fn lookup(&self, domain: &str) -> Result<bool> {
+        // Fully qualified, so that the search domains are not tried
+        let name = format!("{domain}.");
+        match self.resolver.mx_lookup(name.as_str()).await {
+            Ok(lookup) => {
+                return Ok(lookup
+                    .answers()
+                    .iter()
+                    .any(|record| matches!(&record.data, RData::MX(mx) if !mx.exchange.is_root())))
+            }
+            Err(e) if e.is_nx_domain() => return Ok(false),
+            Err(e) if e.is_no_records_found() => {}
+            Err(e) => return Err(e).with_context(|| format!("MX lookup of {domain} failed")),
+        }
+
+        match self.resolver.lookup_ip(name.as_str()).await {
+            Ok(lookup) => Ok(lookup.iter().next().is_some()),
+            Err(e) if e.is_no_records_found() => Ok(false),
+            Err(e) => Err(e).with_context(|| format!("A/AAAA lookup of {domain} failed")),
+        }
+    }
+}
+
+/// Checks on the domain of the contact addresses
+pub struct EmailDomains {
+    disposable: DisposableDomains,
+    mail_servers: Option<MailServers>,
+}
+
+impl EmailDomains {
+    #[must_use]
+    pub const fn new(disposable: DisposableDomains, mail_servers: Option<MailServers>) -> Self {
+        Self {
+            disposable,
+            mail_servers,
+        }
+    }
+
+    /// Disposable domains of `CONTACT_DISPOSABLE_DOMAINS_PATH`, DNS checks
+    /// with `CONTACT_MX_CHECK`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the domain list or the resolver cannot be loaded.
+    pub fn from_config(config: &Config) -> Result<Self> {
+        let disposable = DisposableDomains::new(
+            config
+                .contact_disposable_domains_path
+                .as_ref()
+                .map(PathBuf::from),
+        )?;
+        let mail_servers = if config.contact_mx_check {
+            Some(MailServers::from_config(config)?)
+        } else {
+            None
+        };
+        Ok(Self::new(disposable, mail_servers))
+    }
+
+    #[must_use]
+    pub const fn disposable(&self) -> &DisposableDomains {
+        &self.disposable
+    }
+
+    /// Checks that an address is neither disposable nor without mail server.
+    ///
+    /// A failing DNS lookup lets the address through, so that an outage of
+    /// the resolver does not block the form.
+    ///
+    /// # Errors
+    ///
+    /// Returns the reason the domain is refused.
+    pub async fn check(&self, email: &str) -> Result<(), DomainError> {
+        let domain = domain(email).ok_or(DomainError::Invalid)?;
+        if self.disposable.contains(&domain) {
+            return Err(DomainError::Disposable);
+        }
+
+        if let Some(mail_servers) = &self.mail_servers {
+            match mail_servers.accepts_mail(&domain).await {
+                Ok(true) => {}
+                Ok(false) => return Err(DomainError::NoMailServer),
+                Err(e) => tracing::warn!("Email domain not checked: {:#}", e),
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hickory_resolver::proto::op::{Message, ResponseCode};
+    use hickory_resolver::proto::rr::rdata::{A, MX};
+    use hickory_resolver::proto::rr::{Name, Record, RecordType};
+    use std::io::Write;
+    use tokio::net::UdpSocket;
+
+    /// Local DNS server answering for `mail.test` (MX), `web.test` (A only)
+    /// and `nullmx.test` (null MX), every other name being unknown
+    async fn dns_stand_in() -> SocketAddr {
+        let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let addr = socket.local_addr().unwrap();
+        tokio::spawn(async move {
+            let mut buf = [0u8; 512];
+            loop {
+                let Ok((len, peer)) = socket.recv_from(&mut buf).await else {
+                    return;
+                };
+                let Ok(query) = Message::from_vec(&buf[..len]) else {
+                    continue;
+                };
+                let mut response = Message::response(query.metadata.id, query.metadata.op_code);
+                response.metadata.authoritative = true;
+                response.metadata.recursion_desired = query.metadata.recursion_desired;
+                response.metadata.recursion_available = true;
+                for question in &query.queries {
+                    response.add_query(question.clone());
+                    let name = question.name().clone();
+                    let rdata = match (name.to_ascii().as_str(), question.query_type()) {
+                        ("mail.test.", RecordType::MX) => Some(RData::MX(MX::new(
+                            10,
+                            Name::from_ascii("mx.mail.test.").unwrap(),
+                        ))),
+                        ("nullmx.test.", RecordType::MX) => {
+                            Some(RData::MX(MX::new(0, Name::root())))
+                        }
+                        ("web.test.", RecordType::A) => Some(RData::A(A::new(192, 0, 2, 1))),
+                        ("mail.test." | "web.test." | "nullmx.test.", _) => None,
+                        _ => {
+                            response.metadata.response_code = ResponseCode::NXDomain;
+                            None
+                        }
+                    };
+                    if let Some(rdata) = rdata {
+                        response.add_answer(Record::from_rdata(name, 300, rdata));
+                    }
+                }
+                let _ = socket.send_to(&response.to_vec().unwrap(), peer).await;
+            }
+        });
+        addr
+    }
+
+    async fn mail_servers() -> MailServers {
+        let timeout = Duration::from_millis(500);
+        MailServers::new(
+            MailServers::resolver(&[dns_stand_in().await], timeout).unwrap(),
+            timeout,
+            Duration::from_secs(60),
+        )
+    }
+
+    #[test]
+    fn test_domain() {
+        assert_eq!(domain("John@Example.COM."), Some("example.com".to_string()));
+        assert_eq!(
+            domain("anna@Bücher.de"),
+            Some("xn--bcher-kva.de".to_string())
+        );
+        assert_eq!(domain("not-an-email"), None);
+        assert_eq!(domain("john@"), None);
+    }
+
+    #[test]
+    fn test_is_disposable() {
+        let disposable = DisposableDomains::builtin();
+        assert!(disposable.is_disposable("bot@mailinator.com"));
+        assert!(disposable.is_disposable("bot@eu.YOPMAIL.com"));
+        assert!(!disposable.is_disposable("john@example.com"));
+        assert!(!disposable.is_disposable("john@notyopmail.com"));
+    }
+
+    #[test]
+    fn test_reload_disposable_domains() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        writeln!(file, "# Providers\nthrowaway.example\nposte-jetée.fr # IDN").unwrap();
+        let disposable = DisposableDomains::new(Some(file.path().to_path_buf())).unwrap();
+
+        assert_eq!(disposable.count(), DISPOSABLE_DOMAINS.len() + 2);
+        assert!(disposable.is_disposable("bot@throwaway.example"));
+        assert!(disposable.is_disposable("bot@xn--poste-jete-j7a.fr"));
+        assert!(disposable.is_disposable("bot@Poste-Jetée.fr"));
+        assert!(disposable.is_disposable("bot@mailinator.com"));
+
+        writeln!(file, "later.example").unwrap();
+        assert!(!disposable.is_disposable("bot@later.example"));
+        assert_eq!(disposable.reload().unwrap(), DISPOSABLE_DOMAINS.len() + 3);
+        assert!(disposable.is_disposable("bot@later.example"));
+    }
+
+    #[tokio::test]
+    async fn test_accepts_mail() {
+        let mail_servers = mail_servers().await;
+        assert!(mail_servers.accepts_mail("mail.test").await.unwrap());
+        assert!(mail_servers.accepts_mail("web.test").await.unwrap());
+        assert!(!mail_servers.accepts_mail("nullmx.test").await.unwrap());
+        assert!(!mail_servers.accepts_mail("unknown.test").await.unwrap());
+        assert!(mail_servers.cache.lock().unwrap().contains_key("mail.test"));
+    }
+
+    #[tokio::test]
+    async fn test_dns_timeout_lets_address_through() {
+        // Nothing answers on this socket
+        let silent = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+        let timeout = Duration::from_millis(200);
+        let mail_servers = MailServers::new(
+            MailServers::resolver(&[silent.local_addr().unwrap()], timeout).unwrap(),
+            timeout,
+            Duration::from_secs(60),
+        );
+        assert!(mail_servers.accepts_mail("mail.test").await.is_err());
+
+        let domains = EmailDomains::new(DisposableDomains::builtin(), Some(mail_servers));
+        assert_eq!(domains.check("john@mail.test").await, Ok(()));
+        assert_eq!(
+            domains.check("bot@mailinator.com").await,
+            Err(DomainError::Disposable)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_check() {
+        let domains = EmailDomains::new(DisposableDomains::builtin(), Some(mail_servers().await));
+        assert_eq!(domains.check("john@mail.test").await, Ok(()));
+        assert_eq!(
+            domains.check("john@unknown.test").await,
+            Err(DomainError::NoMailServer)
+        );
+        assert_eq!(domains.check("john@").await, Err(DomainError::Invalid));
+    }
+}
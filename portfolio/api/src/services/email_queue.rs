@@ -1,122 +1,445 @@
 use anyhow::Result;
-use once_cell::sync::OnceCell;
+use chrono::Utc;
+use mongodb::bson::{doc, oid::ObjectId, Bson, Document};
+use mongodb::options::ReturnDocument;
+use mongodb::Database;
+use serde::{Deserialize, Serialize};
 use std::sync::Arc;
-use tokio::sync::{
-    mpsc::{self, Receiver, Sender},
-    Mutex,
-};
+use std::time::Duration;
 
-static EMAIL_QUEUE: OnceCell<Arc<EmailQueue>> = OnceCell::new();
+use crate::config::Config;
+use crate::services::email_transports::EmailTransport;
 
-/// Thread-safe email queue for asynchronous message processing
-///
-/// # Features
-/// - Buffered channel with 100 message capacity
-/// - Background processor thread
-/// - Test utilities for isolated testing
-///
-///   Email message structure for queue items
-#[derive(Debug, Clone)]
+/// Collection holding the outgoing emails
+const COLLECTION: &str = "email_outbox";
+/// Longest delay between two attempts
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(6 * 60 * 60);
+/// Time a worker owns a job; past it, the job is handed to another worker
+const LEASE_DURATION: Duration = Duration::from_secs(5 * 60);
+/// Delay between two polls of an empty outbox
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+/// Maximum length of the stored delivery error
+const MAX_ERROR_CHARS: usize = 500;
+
+/// Email waiting in the outbox
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct EmailMessage {
     /// Recipient email address
     pub to: String,
     /// Email subject line
     pub subject: String,
     /// HTML email body content
-    pub body: String,
+    #[serde(alias = "body")]
+    pub html: String,
+    /// Plain-text alternative of the HTML body
+    #[serde(default)]
+    pub text: String,
+    /// Address answers go to, such as the visitor of the contact form
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reply_to: Option<String>,
 }
 
-struct EmailQueue {
-    sender: Sender<EmailMessage>,
-    receiver: Mutex<Option<Receiver<EmailMessage>>>,
+/// State of an outbox job
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JobStatus {
+    /// Waiting for its next attempt
+    Pending,
+    /// Leased by a worker
+    Sending,
+    Sent,
+    /// Given up after too many attempts
+    Dead,
 }
 
-/// Initializes email queue
-///
-/// Thread-safe - can only be called once
-/// Subsequent calls have no effect.
-pub fn init_queue() {
-    EMAIL_QUEUE.get_or_init(|| {
-        let (sender, receiver) = mpsc::channel(100);
-        Arc::new(EmailQueue {
-            sender,
-            receiver: Mutex::new(Some(receiver)),
-        })
-    });
+impl JobStatus {
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Sending => "sending",
+            Self::Sent => "sent",
+            Self::Dead => "dead",
+        }
+    }
 }
 
-/// Enqueues email for background processing
-///
-/// # Errors
-/// Returns error if:
-/// - Queue not initialized
-/// - Channel is closed
-pub async fn enqueue_email(message: EmailMessage) -> Result<()> {
-    let queue = EMAIL_QUEUE
-        .get()
-        .ok_or_else(|| anyhow::anyhow!("Queue not initialized"))?;
-    queue.sender.send(message).await?;
-    Ok(())
+/// Number of attempts and exponential backoff between them
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    pub max_attempts: u32,
+    /// Delay before the second attempt, doubled after each failure
+    pub base_delay: Duration,
+    pub max_delay: Duration,
 }
 
-/// Starts background email processing task
-///
-/// # Behavior
-/// - Claims exclusive access to queue receiver
-/// - Processes messages until channel closes
-/// - Runs indefinitely in tokio task
+impl RetryPolicy {
+    #[must_use]
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            max_attempts: config.email_max_attempts.max(1),
+            base_delay: Duration::from_secs(config.email_retry_base_secs),
+            max_delay: MAX_RETRY_DELAY,
+        }
+    }
+
+    /// Delay before the attempt following the failed attempt number `attempt` (from 1)
+    #[must_use]
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let factor = 1u32 << attempt.saturating_sub(1).min(20);
+        self.base_delay.saturating_mul(factor).min(self.max_delay)
+    }
+
+    /// Status of a job after its attempt number `attempt` failed
+    #[must_use]
+    pub const fn status_after_failure(&self, attempt: u32) -> JobStatus {
+        if attempt >= self.max_attempts {
+            JobStatus::Dead
+        } else {
+            JobStatus::Pending
+        }
+    }
+}
+
+/// Job leased by a worker
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EmailJob {
+    pub id: ObjectId,
+    pub message: EmailMessage,
+    /// Attempt number, the current one included
+    pub attempts: u32,
+}
+
+fn to_job(doc: &Document) -> Option<EmailJob> {
+    let message = doc.get_document("message").ok()?;
+    Some(EmailJob {
+        id: doc.get_object_id("_id").ok()?,
+        message: mongodb::bson::from_document(message.clone()).ok()?,
+        attempts: doc
+            .get_i32("attempts")
+            .ok()
+            .and_then(|attempts| u32::try_from(attempts).ok())
+            .unwrap_or(0),
+    })
+}
+
+fn bson_after(delay: Duration) -> mongodb::bson::DateTime {
+    let delay = chrono::Duration::from_std(delay).unwrap_or_else(|_| chrono::Duration::zero());
+    mongodb::bson::DateTime::from_millis((Utc::now() + delay).timestamp_millis())
+}
+
+/// Durable outbox of the outgoing emails
 ///
-/// # Safety
-/// Should only be called once during application startup
-pub async fn start_email_processor() {
-    let Some(queue) = EMAIL_QUEUE.get() else {
-        return;
-    };
-
-    if let Some(mut receiver) = queue.receiver.lock().await.take() {
-        while let Some(message) = receiver.recv().await {
-            process_email(&message);
+/// Each document of `email_outbox` holds the `message`, its `status`
+/// (`pending`, `sending`, `sent` or `dead`), the number of `attempts` and
+/// the `next_attempt_at` date. Workers lease due jobs for a limited time, so
+/// that a job left by a crashed worker is picked up again once its lease
+/// expires. Delivery is at least once.
+pub struct EmailOutbox {
+    db: Database,
+    policy: RetryPolicy,
+}
+
+impl EmailOutbox {
+    #[must_use]
+    pub const fn new(db: Database, policy: RetryPolicy) -> Self {
+        Self { db, policy }
+    }
+
+    fn collection(&self) -> mongodb::Collection<Document> {
+        self.db.collection::<Document>(COLLECTION)
+    }
+
+    #[must_use]
+    pub const fn policy(&self) -> &RetryPolicy {
+        &self.policy
+    }
+
+    /// Stores an email, to be sent as soon as possible
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the insertion fails.
+    pub async fn enqueue(&self, message: &EmailMessage) -> Result<ObjectId> {
+        let now = mongodb::bson::DateTime::now();
+        let result = self
+            .collection()
+            .insert_one(doc! {
+                "message": mongodb::bson::to_document(message)?,
+                "status": JobStatus::Pending.as_str(),
+                "attempts": 0,
+                "next_attempt_at": now,
+                "created_at": now,
+                "updated_at": now,
+            })
+            .await?;
+        result
+            .inserted_id
+            .as_object_id()
+            .ok_or_else(|| anyhow::anyhow!("Invalid outbox job id"))
+    }
+
+    /// Leases the oldest due job, or a job whose lease expired
+    ///
+    /// A job whose document cannot be read is dead-lettered instead of being
+    /// leased again forever, and the next due job is leased.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the database update fails.
+    pub async fn lease(&self, worker: &str) -> Result<Option<EmailJob>> {
+        loop {
+            let Some(doc) = self.lease_document(worker).await? else {
+                return Ok(None);
+            };
+            if let Some(job) = to_job(&doc) {
+                return Ok(Some(job));
+            }
+
+            let id = doc.get("_id").cloned().unwrap_or(Bson::Null);
+            tracing::error!("Email {} dead-lettered: malformed job document", id);
+            self.collection()
+                .update_one(
+                    doc! { "_id": id, "lease_owner": worker },
+                    doc! {
+                        "$set": {
+                            "status": JobStatus::Dead.as_str(),
+                            "last_error": "Malformed job document",
+                            "updated_at": mongodb::bson::DateTime::now(),
+                        },
+                        "$unset": { "lease_owner": "", "lease_until": "" },
+                    },
+                )
+                .await?;
+        }
+    }
+
+    /// Marks the oldest due job as leased by the worker and returns its document
+    async fn lease_document(&self, worker: &str) -> Result<Option<Document>> {
+        let now = mongodb::bson::DateTime::now();
+        Ok(self
+            .collection()
+            .find_one_and_update(
+                doc! { "$or": [
+                    { "status": JobStatus::Pending.as_str(), "next_attempt_at": { "$lte": now } },
+                    { "status": JobStatus::Sending.as_str(), "lease_until": { "$lte": now } },
+                ]},
+                doc! {
+                    "$set": {
+                        "status": JobStatus::Sending.as_str(),
+                        "lease_owner": worker,
+                        "lease_until": bson_after(LEASE_DURATION),
+                        "updated_at": now,
+                    },
+                    "$inc": { "attempts": 1 },
+                },
+            )
+            .sort(doc! { "next_attempt_at": 1 })
+            .return_document(ReturnDocument::After)
+            .await?)
+    }
+
+    /// Updates a job still leased by the worker, returns false when the lease was lost
+    async fn settle(&self, job: &EmailJob, worker: &str, update: Document) -> Result<bool> {
+        let result = self
+            .collection()
+            .update_one(
+                doc! {
+                    "_id": job.id,
+                    "status": JobStatus::Sending.as_str(),
+                    "lease_owner": worker,
+                },
+                update,
+            )
+            .await?;
+        Ok(result.matched_count > 0)
+    }
+
+    /// Marks a leased job as sent
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the database update fails. Returns `Ok(false)` when
+    /// the lease expired and another worker took the job.
+    pub async fn complete(&self, job: &EmailJob, worker: &str) -> Result<bool> {
+        let now = mongodb::bson::DateTime::now();
+        self.settle(
+            job,
+            worker,
+            doc! {
+                "$set": { "status": JobStatus::Sent.as_str(), "sent_at": now, "updated_at": now },
+                "$unset": { "lease_owner": "", "lease_until": "", "last_error": "" },
+            },
+        )
+        .await
+    }
+
+    /// Records a failed attempt: the job is retried later, or dead-lettered
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the database update fails.
+    pub async fn fail(&self, job: &EmailJob, worker: &str, error: &str) -> Result<JobStatus> {
+        let status = self.policy.status_after_failure(job.attempts);
+        let error: String = error.chars().take(MAX_ERROR_CHARS).collect();
+        let mut set = doc! {
+            "status": status.as_str(),
+            "last_error": error,
+            "updated_at": mongodb::bson::DateTime::now(),
+        };
+        if status == JobStatus::Pending {
+            set.insert(
+                "next_attempt_at",
+                bson_after(self.policy.delay(job.attempts)),
+            );
         }
+        self.settle(
+            job,
+            worker,
+            doc! { "$set": set, "$unset": { "lease_owner": "", "lease_until": "" } },
+        )
+        .await?;
+        Ok(status)
     }
 }
 
-/// Processes email from queue
-fn process_email(message: &EmailMessage) {
-    println!(
-        "Email sent to {} with subject: {}",
-        message.to, message.subject
-    );
+/// Background worker delivering the outbox
+pub struct EmailWorker {
+    outbox: Arc<EmailOutbox>,
+    transport: Arc<dyn EmailTransport>,
+    /// Identifies the leases of this worker
+    id: String,
+}
+
+impl EmailWorker {
+    #[must_use]
+    pub fn new(outbox: Arc<EmailOutbox>, transport: Arc<dyn EmailTransport>) -> Self {
+        Self {
+            outbox,
+            transport,
+            id: ObjectId::new().to_hex(),
+        }
+    }
+
+    async fn process(&self, job: &EmailJob) -> Result<()> {
+        // A job whose worker crashed during its last attempt
+        if job.attempts > self.outbox.policy().max_attempts {
+            self.outbox
+                .fail(job, &self.id, "Lease expired on the last attempt")
+                .await?;
+            tracing::error!("Email {} dead-lettered after lease expiry", job.id);
+            return Ok(());
+        }
+
+        match self.transport.send(&job.message).await {
+            Ok(()) => {
+                if !self.outbox.complete(job, &self.id).await? {
+                    tracing::warn!("Email {} sent after its lease expired", job.id);
+                }
+            }
+            Err(e) => match self.outbox.fail(job, &self.id, &e.to_string()).await? {
+                JobStatus::Dead => tracing::error!(
+                    "Email {} dead-lettered after {} attempt(s): {}",
+                    job.id,
+                    job.attempts,
+                    e
+                ),
+                _ => tracing::warn!(
+                    "Email {} attempt {} failed with {}, retrying later: {}",
+                    job.id,
+                    job.attempts,
+                    self.transport.name(),
+                    e
+                ),
+            },
+        }
+        Ok(())
+    }
+
+    /// Delivers the due jobs until the process stops
+    pub async fn run(self) {
+        loop {
+            match self.outbox.lease(&self.id).await {
+                Ok(Some(job)) => {
+                    if let Err(e) = self.process(&job).await {
+                        tracing::error!("Error settling email {}: {}", job.id, e);
+                    }
+                }
+                Ok(None) => tokio::time::sleep(POLL_INTERVAL).await,
+                Err(e) => {
+                    tracing::error!("Error leasing email: {}", e);
+                    tokio::time::sleep(POLL_INTERVAL).await;
+                }
+            }
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    #[tokio::test]
-    async fn test_email_queue() {
-        // Initialize queue
-        init_queue();
+    fn policy() -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: 4,
+            base_delay: Duration::from_secs(30),
+            max_delay: Duration::from_secs(100),
+        }
+    }
 
-        // Create test message
-        let message = EmailMessage {
+    fn message() -> EmailMessage {
+        EmailMessage {
             to: "test@example.com".to_string(),
             subject: "Test Subject".to_string(),
-            body: "<p>Test content</p>".to_string(),
+            html: "<p>Test content</p>".to_string(),
+            text: "Test content".to_string(),
+            reply_to: Some("visitor@example.com".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_retry_delay_doubles_up_to_max() {
+        let policy = policy();
+
+        assert_eq!(policy.delay(1), Duration::from_secs(30));
+        assert_eq!(policy.delay(2), Duration::from_secs(60));
+        assert_eq!(policy.delay(3), Duration::from_secs(100));
+        assert_eq!(policy.delay(u32::MAX), Duration::from_secs(100));
+    }
+
+    #[test]
+    fn test_dead_letter_after_max_attempts() {
+        let policy = policy();
+
+        assert_eq!(policy.status_after_failure(1), JobStatus::Pending);
+        assert_eq!(policy.status_after_failure(3), JobStatus::Pending);
+        assert_eq!(policy.status_after_failure(4), JobStatus::Dead);
+    }
+
+    #[test]
+    fn test_job_from_document() {
+        let id = ObjectId::new();
+        let doc = doc! {
+            "_id": id,
+            "message": mongodb::bson::to_document(&message()).unwrap(),
+            "status": "sending",
+            "attempts": 2,
         };
 
-        // Send message
-        let result = enqueue_email(message).await;
-        assert!(result.is_ok());
-
-        // Retrieve message directly from queue
-        if let Some(queue) = EMAIL_QUEUE.get() {
-            if let Some(mut receiver) = queue.receiver.lock().await.take() {
-                if let Some(received_message) = receiver.recv().await {
-                    process_email(&received_message);
-                    assert_eq!(received_message.to, "test@example.com");
-                    assert_eq!(received_message.subject, "Test Subject");
-                }
-            }
-        }
+        let job = to_job(&doc).unwrap();
+        assert_eq!(job.id, id);
+        assert_eq!(job.message, message());
+        assert_eq!(job.attempts, 2);
+        assert!(to_job(&doc! { "_id": id }).is_none());
+
+        // Jobs queued before the plain-text body
+        let legacy = doc! {
+            "_id": id,
+            "message": { "to": "test@example.com", "subject": "Test Subject", "body": "<p>Hi</p>" },
+            "attempts": 1,
+        };
+        let job = to_job(&legacy).unwrap();
+        assert_eq!(job.message.html, "<p>Hi</p>");
+        assert!(job.message.text.is_empty());
+        assert!(job.message.reply_to.is_none());
     }
 }
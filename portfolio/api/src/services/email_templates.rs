@@ -1,66 +1,663 @@
-use anyhow::Result;
+//! Email templates
+//!
+//! Templates are built into the API from `templates/email` and overridden,
+//! file by file, by the templates of `EMAIL_TEMPLATES_DIR`:
+//!
+//! - `{name}.{locale}.subject.hbs`, `{name}.{locale}.html.hbs` and
+//!   `{name}.{locale}.text.hbs`: the three parts of a template in a locale
+//! - `partials/{partial}.html.hbs` and `partials/{partial}.text.hbs`:
+//!   partials and layouts shared by the HTML and plain-text parts
+//! - `{name}.sample.json`: sample data of the admin preview
+
+use anyhow::{Context, Result};
 use handlebars::Handlebars;
 use once_cell::sync::Lazy;
-use serde_json::json;
+use regex::Regex;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::SystemTime;
+
+use crate::config::Config;
+use crate::services::i18n::match_locale;
+
+/// Templates built into the API, relative path and content
+const BUILTIN: [(&str, &str); 16] = [
+    (
+        "partials/layout.html.hbs",
+        include_str!("../../templates/email/partials/layout.html.hbs"),
+    ),
+    (
+        "partials/signature.text.hbs",
+        include_str!("../../templates/email/partials/signature.text.hbs"),
+    ),
+    (
+        "contact.fr.subject.hbs",
+        include_str!("../../templates/email/contact.fr.subject.hbs"),
+    ),
+    (
+        "contact.fr.html.hbs",
+        include_str!("../../templates/email/contact.fr.html.hbs"),
+    ),
+    (
+        "contact.fr.text.hbs",
+        include_str!("../../templates/email/contact.fr.text.hbs"),
+    ),
+    (
+        "contact.en.subject.hbs",
+        include_str!("../../templates/email/contact.en.subject.hbs"),
+    ),
+    (
+        "contact.en.html.hbs",
+        include_str!("../../templates/email/contact.en.html.hbs"),
+    ),
+    (
+        "contact.en.text.hbs",
+        include_str!("../../templates/email/contact.en.text.hbs"),
+    ),
+    (
+        "contact.sample.json",
+        include_str!("../../templates/email/contact.sample.json"),
+    ),
+    (
+        "auto_reply.fr.subject.hbs",
+        include_str!("../../templates/email/auto_reply.fr.subject.hbs"),
+    ),
+    (
+        "auto_reply.fr.html.hbs",
+        include_str!("../../templates/email/auto_reply.fr.html.hbs"),
+    ),
+    (
+        "auto_reply.fr.text.hbs",
+        include_str!("../../templates/email/auto_reply.fr.text.hbs"),
+    ),
+    (
+        "auto_reply.en.subject.hbs",
+        include_str!("../../templates/email/auto_reply.en.subject.hbs"),
+    ),
+    (
+        "auto_reply.en.html.hbs",
+        include_str!("../../templates/email/auto_reply.en.html.hbs"),
+    ),
+    (
+        "auto_reply.en.text.hbs",
+        include_str!("../../templates/email/auto_reply.en.text.hbs"),
+    ),
+    (
+        "auto_reply.sample.json",
+        include_str!("../../templates/email/auto_reply.sample.json"),
+    ),
+];
+
+static TEMPLATE_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^([a-z0-9_-]+)\.([a-z]{2,3}(?:-[a-z0-9]{2,8})*)\.(subject|html|text)\.hbs$")
+        .unwrap()
+});
+static PARTIAL_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^partials/([a-z0-9_/-]+)\.(html|text)\.hbs$").unwrap());
+static SAMPLE_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"^([a-z0-9_-]+)\.sample\.json$").unwrap());
+
+/// Name of a template part in its registry
+fn key(name: &str, locale: &str, part: &str) -> String {
+    format!("{name}.{locale}.{part}")
+}
+
+/// Rendered email, sent as `multipart/alternative`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenderedEmail {
+    /// Locale of the template variant used
+    pub locale: String,
+    /// Single line subject
+    pub subject: String,
+    pub html: String,
+    pub text: String,
+}
+
+/// Loading options of the templates
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TemplateOptions {
+    /// Directory overriding the built-in templates
+    pub dir: Option<PathBuf>,
+    /// Locale used when a template has no variant in the requested locale
+    pub fallback_locale: String,
+    /// Fails on missing values instead of rendering them empty
+    pub strict: bool,
+    /// Reloads the directory when one of its files changes
+    pub hot_reload: bool,
+}
+
+impl Default for TemplateOptions {
+    fn default() -> Self {
+        Self {
+            dir: None,
+            fallback_locale: "en".to_string(),
+            strict: true,
+            hot_reload: false,
+        }
+    }
+}
+
+impl TemplateOptions {
+    #[must_use]
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            dir: config.email_templates_dir.as_ref().map(PathBuf::from),
+            fallback_locale: config.email_fallback_locale.clone(),
+            strict: config.email_templates_strict,
+            hot_reload: config.email_templates_hot_reload,
+        }
+    }
+}
+
+/// Compiled templates
+struct Catalog {
+    /// HTML parts and partials, escaping every value
+    html: Handlebars<'static>,
+    /// Subjects, plain-text parts and partials, rendered verbatim
+    text: Handlebars<'static>,
+    /// Template name → locales
+    locales: BTreeMap<String, BTreeSet<String>>,
+    samples: BTreeMap<String, Value>,
+}
 
-static TEMPLATES: Lazy<Handlebars<'static>> = Lazy::new(|| {
+fn registry(escaped: bool, strict: bool) -> Handlebars<'static> {
     let mut reg = Handlebars::new();
-    reg.register_template_string(
-        "contact",
-        r"
-        <h2>New contact message</h2>
-        <p><strong>From:</strong> {{name}} ({{email}})</p>
-        <p><strong>Subject:</strong> {{subject}}</p>
-        <hr>
-        <p><strong>Message:</strong></p>
-        <p>{{message}}</p>
-        ",
-    )
-    .expect("Failed to register contact template");
+    reg.set_strict_mode(strict);
+    if !escaped {
+        reg.register_escape_fn(handlebars::no_escape);
+    }
     reg
-});
+}
 
-/// Generates HTML email content from contact template.
-///
-/// # Errors
+impl Catalog {
+    /// Compiles the files, a file replacing the previous ones with the same path
+    fn build(files: &[(String, String)], strict: bool) -> Result<Self> {
+        let files: BTreeMap<&str, &str> = files
+            .iter()
+            .map(|(path, content)| (path.as_str(), content.as_str()))
+            .collect();
+
+        let mut catalog = Self {
+            html: registry(true, strict),
+            text: registry(false, strict),
+            locales: BTreeMap::new(),
+            samples: BTreeMap::new(),
+        };
+        let mut parts: BTreeMap<(String, String), BTreeSet<String>> = BTreeMap::new();
+
+        for (path, content) in files {
+            if let Some(caps) = TEMPLATE_RE.captures(path) {
+                let (name, locale, part) = (&caps[1], &caps[2], &caps[3]);
+                let reg = if part == "html" {
+                    &mut catalog.html
+                } else {
+                    &mut catalog.text
+                };
+                reg.register_template_string(&key(name, locale, part), content)
+                    .with_context(|| format!("Invalid email template {path}"))?;
+                parts
+                    .entry((name.to_string(), locale.to_string()))
+                    .or_default()
+                    .insert(part.to_string());
+            } else if let Some(caps) = PARTIAL_RE.captures(path) {
+                let reg = if &caps[2] == "html" {
+                    &mut catalog.html
+                } else {
+                    &mut catalog.text
+                };
+                reg.register_partial(&caps[1], content)
+                    .with_context(|| format!("Invalid email partial {path}"))?;
+            } else if let Some(caps) = SAMPLE_RE.captures(path) {
+                let sample = serde_json::from_str(content)
+                    .with_context(|| format!("Invalid sample data {path}"))?;
+                catalog.samples.insert(caps[1].to_string(), sample);
+            } else if Path::new(path).extension().is_some_and(|ext| ext == "hbs") {
+                anyhow::bail!("Unexpected email template name: {path}");
+            }
+        }
+
+        for ((name, locale), found) in parts {
+            anyhow::ensure!(
+                found.len() == 3,
+                "Email template {name}.{locale} needs a subject, an html and a text part"
+            );
+            catalog.locales.entry(name).or_default().insert(locale);
+        }
+        Ok(catalog)
+    }
+
+    /// Variant of a template for a locale: the locale, its primary language, then the fallback
+    fn resolve(&self, name: &str, locale: &str, fallback: &str) -> Option<String> {
+        let locales: Vec<String> = self.locales.get(name)?.iter().cloned().collect();
+        match_locale(locale, &locales)
+            .or_else(|| match_locale(fallback, &locales))
+            .map(str::to_string)
+    }
+}
+
+/// Modification stamp of a file of the template directory
+type Stamp = (PathBuf, Option<SystemTime>, u64);
+
+/// Lists the files of the directory, recursively, in path order
+fn walk(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(current) = pending.pop() {
+        for entry in std::fs::read_dir(&current)
+            .with_context(|| format!("Cannot read {}", current.display()))?
+        {
+            let path = entry?.path();
+            if path.is_dir() {
+                pending.push(path);
+            } else {
+                files.push(path);
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+fn stamps(dir: &Path) -> Result<Vec<Stamp>> {
+    walk(dir)?
+        .into_iter()
+        .map(|path| {
+            let metadata = std::fs::metadata(&path)?;
+            Ok((path, metadata.modified().ok(), metadata.len()))
+        })
+        .collect()
+}
+
+/// Built-in files followed by the templates and samples of the directory
+fn read_files(dir: Option<&Path>) -> Result<Vec<(String, String)>> {
+    let mut files: Vec<(String, String)> = BUILTIN
+        .iter()
+        .map(|(path, content)| ((*path).to_string(), (*content).to_string()))
+        .collect();
+
+    if let Some(dir) = dir {
+        for path in walk(dir)? {
+            let is_template = path
+                .extension()
+                .is_some_and(|ext| ext == "hbs" || ext == "json");
+            if !is_template {
+                continue;
+            }
+            let relative = path
+                .strip_prefix(dir)?
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            let content = std::fs::read_to_string(&path)
+                .with_context(|| format!("Cannot read {}", path.display()))?;
+            files.push((relative, content));
+        }
+    }
+    Ok(files)
+}
+
+/// Email templates, localized and rendered with Handlebars
 ///
-/// Returns error if:
-/// - Template not found
-/// - Template rendering fails
-/// - Invalid template data
-pub fn render_contact_template(
-    name: &str,
-    email: &str,
-    subject: &str,
-    message: &str,
-) -> Result<String> {
-    let data = json!({
-        "name": name,
-        "email": email,
-        "subject": subject,
-        "message": message,
-    });
-
-    Ok(TEMPLATES.render("contact", &data)?)
+/// A template has a variant per locale, chosen like the site translations:
+/// the requested locale, its primary language (`fr-CA` → `fr`), then the
+/// fallback locale. With hot reload, the directory is checked for changes
+/// before each rendering; a broken edit is logged and the previous templates
+/// are kept.
+pub struct EmailTemplates {
+    options: TemplateOptions,
+    catalog: RwLock<Arc<Catalog>>,
+    stamps: Mutex<Vec<Stamp>>,
+}
+
+impl EmailTemplates {
+    /// Loads the built-in templates and those of the directory.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if:
+    /// - The directory cannot be read
+    /// - A template or partial does not compile
+    /// - A template misses one of its parts in a locale
+    /// - Sample data is not valid JSON
+    pub fn new(options: TemplateOptions) -> Result<Self> {
+        let stamps = match (&options.dir, options.hot_reload) {
+            (Some(dir), true) => stamps(dir)?,
+            _ => Vec::new(),
+        };
+        let catalog = Catalog::build(&read_files(options.dir.as_deref())?, options.strict)?;
+        Ok(Self {
+            options,
+            catalog: RwLock::new(Arc::new(catalog)),
+            stamps: Mutex::new(stamps),
+        })
+    }
+
+    /// Loads the templates configured with `EMAIL_TEMPLATES_*`.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the templates cannot be loaded (see [`EmailTemplates::new`]).
+    pub fn from_config(config: &Config) -> Result<Self> {
+        Self::new(TemplateOptions::from_config(config))
+    }
+
+    /// Built-in templates only, in strict mode
+    ///
+    /// # Panics
+    ///
+    /// Panics if a built-in template is invalid.
+    #[must_use]
+    pub fn builtin() -> Self {
+        Self::new(TemplateOptions::default()).expect("Invalid built-in email templates")
+    }
+
+    /// Reloads the directory when a file was added, changed or removed
+    fn reload_if_changed(&self) {
+        let Some(dir) = self.options.dir.as_deref() else {
+            return;
+        };
+        let Ok(mut previous) = self.stamps.lock() else {
+            return;
+        };
+        let current = match stamps(dir) {
+            Ok(current) => current,
+            Err(e) => {
+                tracing::warn!("Cannot check the email templates: {e:#}");
+                return;
+            }
+        };
+        if current == *previous {
+            return;
+        }
+
+        match read_files(Some(dir)).and_then(|files| Catalog::build(&files, self.options.strict)) {
+            Ok(catalog) => {
+                if let Ok(mut slot) = self.catalog.write() {
+                    *slot = Arc::new(catalog);
+                    tracing::info!("Email templates reloaded from {}", dir.display());
+                }
+            }
+            Err(e) => tracing::error!("Email templates not reloaded: {e:#}"),
+        }
+        *previous = current;
+    }
+
+    fn catalog(&self) -> Result<Arc<Catalog>> {
+        if self.options.hot_reload {
+            self.reload_if_changed();
+        }
+        Ok(self
+            .catalog
+            .read()
+            .map_err(|_| anyhow::anyhow!("Email templates poisoned"))?
+            .clone())
+    }
+
+    /// Template name → available locales
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the templates are poisoned.
+    pub fn list(&self) -> Result<BTreeMap<String, Vec<String>>> {
+        Ok(self
+            .catalog()?
+            .locales
+            .iter()
+            .map(|(name, locales)| (name.clone(), locales.iter().cloned().collect()))
+            .collect())
+    }
+
+    /// Sample data of a template, for previews
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the templates are poisoned.
+    pub fn sample(&self, name: &str) -> Result<Option<Value>> {
+        Ok(self.catalog()?.samples.get(name).cloned())
+    }
+
+    /// Renders the subject, HTML and plain-text bodies of a template.
+    ///
+    /// Values are escaped in the HTML body only. Line breaks are removed from
+    /// the subject, so that user input cannot add headers.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if:
+    /// - Template not found, in the locale and the fallback locale
+    /// - Template rendering fails
+    /// - Invalid template data (in strict mode, a missing value is an error)
+    pub fn render(&self, name: &str, locale: &str, data: &impl Serialize) -> Result<RenderedEmail> {
+        let catalog = self.catalog()?;
+        let locale = catalog
+            .resolve(name, locale, &self.options.fallback_locale)
+            .ok_or_else(|| anyhow::anyhow!("Email template not found: {name}"))?;
+
+        let subject = catalog.text.render(&key(name, &locale, "subject"), data)?;
+        Ok(RenderedEmail {
+            subject: subject
+                .split(['\r', '\n'])
+                .map(str::trim)
+                .filter(|line| !line.is_empty())
+                .collect::<Vec<_>>()
+                .join(" "),
+            html: catalog.html.render(&key(name, &locale, "html"), data)?,
+            text: catalog.text.render(&key(name, &locale, "text"), data)?,
+            locale,
+        })
+    }
+
+    /// Generates the email notifying a new contact message.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the rendering fails (see [`EmailTemplates::render`]).
+    pub fn render_contact(
+        &self,
+        locale: &str,
+        name: &str,
+        email: &str,
+        subject: &str,
+        message: &str,
+    ) -> Result<RenderedEmail> {
+        let data = json!({
+            "name": name,
+            "email": email,
+            "subject": subject,
+            "message": message,
+        });
+
+        self.render("contact", locale, &data)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn write(dir: &Path, path: &str, content: &str) {
+        let path = dir.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    }
+
     #[test]
     fn test_render_contact_template() {
-        let html = render_contact_template(
-            "John Doe",
-            "john@example.com",
-            "Test Subject",
-            "Test message",
-        )
+        let email = EmailTemplates::builtin()
+            .render_contact(
+                "fr",
+                "John Doe",
+                "john@example.com",
+                "Test Subject",
+                "Test message",
+            )
+            .unwrap();
+
+        assert_eq!(email.locale, "fr");
+        assert_eq!(email.subject, "Nouveau message de contact : Test Subject");
+        assert!(email.html.starts_with("<!DOCTYPE html>"));
+        for body in [&email.html, &email.text] {
+            assert!(body.contains("John Doe"));
+            assert!(body.contains("john@example.com"));
+            assert!(body.contains("Test Subject"));
+            assert!(body.contains("Test message"));
+        }
+        assert!(email.text.contains("Portfolio · Mathieu Piton"));
+    }
+
+    #[test]
+    fn test_hostile_input_is_escaped() {
+        let email = EmailTemplates::builtin()
+            .render_contact(
+                "fr",
+                "<script>alert('x')</script>",
+                "evil\"@example.com",
+                "Hi\r\nBcc: victim@example.com",
+                "<img src=x onerror=alert(1)> & <a href=\"javascript:void(0)\">clic</a>",
+            )
+            .unwrap();
+
+        assert!(!email.html.contains("<script>"));
+        assert!(!email.html.contains("<img"));
+        assert!(!email.html.contains("<a href"));
+        assert!(email
+            .html
+            .contains("&lt;script&gt;alert(&#x27;x&#x27;)&lt;/script&gt;"));
+        assert!(email.html.contains("evil&quot;@example.com"));
+        assert!(email.html.contains("&amp;"));
+
+        assert_eq!(
+            email.subject,
+            "Nouveau message de contact : Hi Bcc: victim@example.com"
+        );
+        assert!(email.text.contains("<img src=x onerror=alert(1)> & "));
+    }
+
+    #[test]
+    fn test_missing_value_is_an_error() {
+        let templates = EmailTemplates::builtin();
+        assert!(templates
+            .render("contact", "fr", &json!({ "name": "John" }))
+            .is_err());
+        assert!(templates.render("unknown", "fr", &json!({})).is_err());
+
+        let lenient = EmailTemplates::new(TemplateOptions {
+            strict: false,
+            ..TemplateOptions::default()
+        })
+        .unwrap();
+        assert!(lenient
+            .render("contact", "fr", &json!({ "name": "John" }))
+            .is_ok());
+    }
+
+    #[test]
+    fn test_locale_fallback() {
+        let templates = EmailTemplates::builtin();
+        let sample = templates.sample("contact").unwrap().unwrap();
+
+        assert_eq!(
+            templates
+                .render("contact", "fr-CA", &sample)
+                .unwrap()
+                .locale,
+            "fr"
+        );
+        let email = templates.render("contact", "de", &sample).unwrap();
+        assert_eq!(email.locale, "en");
+        assert!(email.subject.starts_with("New contact message: "));
+        assert_eq!(
+            templates.list().unwrap()["contact"],
+            vec!["en".to_string(), "fr".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_directory_overrides_and_partials() {
+        let dir = tempfile::tempdir().unwrap();
+        write(
+            dir.path(),
+            "partials/layout.html.hbs",
+            "<main>{{> @partial-block}}</main>",
+        );
+        write(dir.path(), "welcome.en.subject.hbs", "Hello {{name}}");
+        write(
+            dir.path(),
+            "welcome.en.html.hbs",
+            "{{#> layout}}<p>Hi {{name}}</p>{{/layout}}",
+        );
+        write(dir.path(), "welcome.en.text.hbs", "Hi {{name}}");
+
+        let templates = EmailTemplates::new(TemplateOptions {
+            dir: Some(dir.path().to_path_buf()),
+            ..TemplateOptions::default()
+        })
         .unwrap();
+        let email = templates
+            .render("welcome", "en", &json!({ "name": "<Ana>" }))
+            .unwrap();
+        assert_eq!(email.subject, "Hello <Ana>");
+        assert_eq!(email.html, "<main><p>Hi &lt;Ana&gt;</p></main>");
+
+        // Built-in templates use the overridden layout
+        let sample = templates.sample("contact").unwrap().unwrap();
+        let contact = templates.render("contact", "fr", &sample).unwrap();
+        assert!(contact.html.starts_with("<main>"));
+    }
+
+    #[test]
+    fn test_incomplete_or_invalid_directory_is_an_error() {
+        let dir = tempfile::tempdir().unwrap();
+        write(dir.path(), "welcome.en.subject.hbs", "Hello");
+        let options = TemplateOptions {
+            dir: Some(dir.path().to_path_buf()),
+            ..TemplateOptions::default()
+        };
+        assert!(EmailTemplates::new(options.clone()).is_err());
+
+        std::fs::remove_file(dir.path().join("welcome.en.subject.hbs")).unwrap();
+        write(dir.path(), "contact.fr.html.hbs", "{{#if}}");
+        assert!(EmailTemplates::new(options.clone()).is_err());
+
+        std::fs::remove_file(dir.path().join("contact.fr.html.hbs")).unwrap();
+        write(dir.path(), "Contact.FR.html.hbs", "Hi");
+        assert!(EmailTemplates::new(options).is_err());
+    }
+
+    #[test]
+    fn test_hot_reload() {
+        let dir = tempfile::tempdir().unwrap();
+        write(dir.path(), "contact.fr.subject.hbs", "Avant : {{subject}}");
+        let templates = EmailTemplates::new(TemplateOptions {
+            dir: Some(dir.path().to_path_buf()),
+            hot_reload: true,
+            ..TemplateOptions::default()
+        })
+        .unwrap();
+        let sample = templates.sample("contact").unwrap().unwrap();
+        let subject = |templates: &EmailTemplates| {
+            templates.render("contact", "fr", &sample).unwrap().subject
+        };
+        assert_eq!(subject(&templates), "Avant : Proposition de mission");
+
+        write(
+            dir.path(),
+            "contact.fr.subject.hbs",
+            "Après, plus long : {{subject}}",
+        );
+        assert_eq!(
+            subject(&templates),
+            "Après, plus long : Proposition de mission"
+        );
 
-        assert!(html.contains("John Doe"));
-        assert!(html.contains("john@example.com"));
-        assert!(html.contains("Test Subject"));
-        assert!(html.contains("Test message"));
+        // A broken edit keeps the previous templates
+        write(dir.path(), "contact.fr.subject.hbs", "{{#if}}");
+        assert_eq!(
+            subject(&templates),
+            "Après, plus long : Proposition de mission"
+        );
     }
 }
@@ -0,0 +1,135 @@
+use anyhow::Result;
+use async_trait::async_trait;
+
+use super::{EmailTransport, SEND_TIMEOUT};
+use crate::config::Config;
+use crate::services::email_queue::EmailMessage;
+
+/// Sends emails through the Brevo transactional API (`BREVO_API_URL`)
+pub struct BrevoTransport {
+    client: reqwest::Client,
+    base_url: String,
+    api_key: String,
+    sender_name: String,
+    sender_email: String,
+}
+
+impl BrevoTransport {
+    #[must_use]
+    pub fn new(config: &Config) -> Self {
+        Self::with_base_url(
+            &config.brevo_api_url,
+            &config.brevo_api_key,
+            &config.sender_name,
+            &config.sender_email,
+        )
+    }
+
+    #[must_use]
+    pub fn with_base_url(
+        base_url: &str,
+        api_key: &str,
+        sender_name: &str,
+        sender_email: &str,
+    ) -> Self {
+        Self {
+            client: reqwest::Client::builder()
+                .timeout(SEND_TIMEOUT)
+                .build()
+                .unwrap_or_default(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            api_key: api_key.to_string(),
+            sender_name: sender_name.to_string(),
+            sender_email: sender_email.to_string(),
+        }
+    }
+}
+
+#[async_trait]
+impl EmailTransport for BrevoTransport {
+    fn name(&self) -> &'static str {
+        "brevo"
+    }
+
+    /// Envoie un email via le service Brevo.
+    ///
+    /// # Errors
+    ///
+    /// Cette fonction retourne une erreur si :
+    /// - La requête HTTP échoue
+    /// - Le service Brevo retourne une erreur
+    async fn send(&self, message: &EmailMessage) -> Result<()> {
+        let mut email_data = serde_json::json!({
+            "sender": {
+                "name": self.sender_name,
+                "email": self.sender_email
+            },
+            "to": [{
+                "email": message.to,
+                "name": message.to
+            }],
+            "subject": message.subject,
+            "htmlContent": message.html,
+            "textContent": message.text
+        });
+        if let Some(reply_to) = &message.reply_to {
+            email_data["replyTo"] = serde_json::json!({ "email": reply_to });
+        }
+
+        let response = self
+            .client
+            .post(format!("{}/v3/smtp/email", self.base_url))
+            .header("api-key", &self.api_key)
+            .header("Content-Type", "application/json")
+            .json(&email_data)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            anyhow::bail!("Failed to send email: {}", response.status());
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{body_partial_json, header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[tokio::test]
+    async fn test_send_through_configured_url() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/v3/smtp/email"))
+            .and(header("api-key", "brevo_key"))
+            .and(body_partial_json(serde_json::json!({
+                "to": [{ "email": "test@example.com" }],
+                "subject": "Test Subject",
+                "textContent": "Test content",
+                "replyTo": { "email": "visitor@example.com" }
+            })))
+            .respond_with(ResponseTemplate::new(201))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let base_url = format!("{}/", mock_server.uri());
+        let message = EmailMessage {
+            to: "test@example.com".to_string(),
+            subject: "Test Subject".to_string(),
+            html: "<p>Test content</p>".to_string(),
+            text: "Test content".to_string(),
+            reply_to: Some("visitor@example.com".to_string()),
+        };
+        let transport =
+            BrevoTransport::with_base_url(&base_url, "brevo_key", "Test Sender", "test@sender.com");
+        assert!(transport.send(&message).await.is_ok());
+
+        let transport =
+            BrevoTransport::with_base_url(&base_url, "wrong_key", "Test Sender", "test@sender.com");
+        assert!(transport.send(&message).await.is_err());
+    }
+}
@@ -0,0 +1,99 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use lettre::message::Mailbox;
+use mongodb::bson::oid::ObjectId;
+use std::path::PathBuf;
+
+use super::{build_mime, sender_mailbox, EmailTransport};
+use crate::config::Config;
+use crate::services::email_queue::EmailMessage;
+
+/// Writes the emails as `.eml` files of a Maildir (`EMAIL_FILE_DIR`)
+///
+/// Each message is written to `tmp/` then moved to `new/`, so that a mail
+/// client reading the directory never sees a partial file.
+pub struct FileTransport {
+    dir: PathBuf,
+    from: Mailbox,
+}
+
+impl FileTransport {
+    /// # Errors
+    ///
+    /// Returns error if `SENDER_EMAIL` is not a valid address.
+    pub fn new(config: &Config) -> Result<Self> {
+        Ok(Self::in_dir(
+            &config.email_file_dir,
+            sender_mailbox(config)?,
+        ))
+    }
+
+    #[must_use]
+    pub fn in_dir(dir: impl Into<PathBuf>, from: Mailbox) -> Self {
+        Self {
+            dir: dir.into(),
+            from,
+        }
+    }
+}
+
+#[async_trait]
+impl EmailTransport for FileTransport {
+    fn name(&self) -> &'static str {
+        "file"
+    }
+
+    async fn send(&self, message: &EmailMessage) -> Result<()> {
+        let mime = build_mime(&self.from, message)?.formatted();
+        for sub in ["tmp", "new", "cur"] {
+            tokio::fs::create_dir_all(self.dir.join(sub)).await?;
+        }
+
+        let name = format!(
+            "{}.{}.eml",
+            chrono::Utc::now().timestamp_millis(),
+            ObjectId::new().to_hex()
+        );
+        let tmp = self.dir.join("tmp").join(&name);
+        tokio::fs::write(&tmp, mime).await?;
+        tokio::fs::rename(&tmp, self.dir.join("new").join(&name)).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_writes_eml_to_maildir() {
+        let dir = tempfile::tempdir().unwrap();
+        let from = "Portfolio <no-reply@example.com>".parse().unwrap();
+        let message = EmailMessage {
+            to: "owner@example.com".to_string(),
+            subject: "Nouveau message".to_string(),
+            html: "<p>Bonjour</p>".to_string(),
+            text: "Bonjour".to_string(),
+            reply_to: None,
+        };
+
+        FileTransport::in_dir(dir.path(), from)
+            .send(&message)
+            .await
+            .unwrap();
+
+        let files: Vec<_> = std::fs::read_dir(dir.path().join("new"))
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .collect();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].extension().unwrap(), "eml");
+        let eml = std::fs::read_to_string(&files[0]).unwrap();
+        assert!(eml.contains("Subject: Nouveau message"));
+        assert!(eml.contains("<p>Bonjour</p>"));
+        assert_eq!(
+            std::fs::read_dir(dir.path().join("tmp")).unwrap().count(),
+            0
+        );
+    }
+}
@@ -0,0 +1,25 @@
+use anyhow::Result;
+use async_trait::async_trait;
+
+use super::EmailTransport;
+use crate::services::email_queue::EmailMessage;
+
+/// Logs the emails instead of sending them, for development
+pub struct LogTransport;
+
+#[async_trait]
+impl EmailTransport for LogTransport {
+    fn name(&self) -> &'static str {
+        "log"
+    }
+
+    async fn send(&self, message: &EmailMessage) -> Result<()> {
+        tracing::info!(
+            "Email to {} not sent (log transport): {} ({} bytes)",
+            message.to,
+            message.subject,
+            message.html.len()
+        );
+        Ok(())
+    }
+}
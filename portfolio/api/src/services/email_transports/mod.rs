@@ -0,0 +1,160 @@
+//! Transports delivering the emails of the outbox
+//!
+//! The transport is selected with `EMAIL_TRANSPORT`: the Brevo HTTP API
+//! (default), an SMTP server, a local Maildir of `.eml` files or the logs.
+//! Every transport implements [`EmailTransport`].
+
+pub mod brevo;
+pub mod file;
+pub mod log;
+pub mod smtp;
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use lettre::message::{Mailbox, MultiPart};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::config::Config;
+use crate::services::email_queue::EmailMessage;
+
+pub use brevo::BrevoTransport;
+pub use file::FileTransport;
+pub use log::LogTransport;
+pub use smtp::{SmtpTls, SmtpTransport};
+
+/// Timeout of a delivery, well below the lease of an outbox job
+pub(crate) const SEND_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Kind of transport, read from `EMAIL_TRANSPORT`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TransportKind {
+    /// Brevo transactional API
+    #[default]
+    Brevo,
+    Smtp,
+    /// Maildir of `.eml` files
+    File,
+    /// Logs the emails without sending them
+    Log,
+}
+
+impl std::str::FromStr for TransportKind {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "" | "brevo" => Ok(Self::Brevo),
+            "smtp" => Ok(Self::Smtp),
+            "file" | "maildir" => Ok(Self::File),
+            "log" => Ok(Self::Log),
+            other => anyhow::bail!("Unknown email transport: {other}"),
+        }
+    }
+}
+
+/// Delivers one email
+#[async_trait]
+pub trait EmailTransport: Send + Sync {
+    /// Name shown in the logs
+    fn name(&self) -> &'static str;
+
+    /// Sends a message, an error makes the outbox retry it later
+    async fn send(&self, message: &EmailMessage) -> Result<()>;
+}
+
+/// Builds the transport selected by the configuration
+///
+/// The Brevo transport falls back to the logs with the placeholder
+/// `test_key`, so that a development setup never sends emails.
+///
+/// # Errors
+///
+/// Returns error for an unknown transport or an invalid SMTP or sender setup.
+pub fn from_config(config: &Config) -> Result<Arc<dyn EmailTransport>> {
+    let transport: Arc<dyn EmailTransport> = match config.email_transport.parse()? {
+        TransportKind::Brevo if config.brevo_api_key == "test_key" => Arc::new(LogTransport),
+        TransportKind::Brevo => Arc::new(BrevoTransport::new(config)),
+        TransportKind::Smtp => Arc::new(SmtpTransport::new(config)?),
+        TransportKind::File => Arc::new(FileTransport::new(config)?),
+        TransportKind::Log => Arc::new(LogTransport),
+    };
+    Ok(transport)
+}
+
+/// Sender mailbox of the configuration
+pub(crate) fn sender_mailbox(config: &Config) -> Result<Mailbox> {
+    Ok(Mailbox::new(
+        Some(config.sender_name.clone()),
+        config
+            .sender_email
+            .parse()
+            .context("Invalid SENDER_EMAIL")?,
+    ))
+}
+
+/// MIME message of an outbox email, for the SMTP and file transports
+///
+/// The plain-text and HTML bodies are sent as `multipart/alternative`.
+pub(crate) fn build_mime(from: &Mailbox, message: &EmailMessage) -> Result<lettre::Message> {
+    let mut builder = lettre::Message::builder()
+        .from(from.clone())
+        .to(message.to.parse().context("Invalid recipient address")?)
+        .subject(message.subject.clone());
+    if let Some(reply_to) = &message.reply_to {
+        builder = builder.reply_to(reply_to.parse().context("Invalid Reply-To address")?);
+    }
+    Ok(builder.multipart(MultiPart::alternative_plain_html(
+        message.text.clone(),
+        message.html.clone(),
+    ))?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transport_kind() {
+        assert_eq!("".parse::<TransportKind>().unwrap(), TransportKind::Brevo);
+        assert_eq!(
+            "SMTP".parse::<TransportKind>().unwrap(),
+            TransportKind::Smtp
+        );
+        assert_eq!(
+            "maildir".parse::<TransportKind>().unwrap(),
+            TransportKind::File
+        );
+        assert_eq!("log".parse::<TransportKind>().unwrap(), TransportKind::Log);
+        assert!("pigeon".parse::<TransportKind>().is_err());
+    }
+
+    #[test]
+    fn test_build_mime() {
+        let from = Mailbox::new(
+            Some("Portfolio".to_string()),
+            "no-reply@example.com".parse().unwrap(),
+        );
+        let message = EmailMessage {
+            to: "owner@example.com".to_string(),
+            subject: "Nouveau message".to_string(),
+            html: "<p>Bonjour</p>".to_string(),
+            text: "Bonjour".to_string(),
+            reply_to: Some("visitor@example.com".to_string()),
+        };
+
+        let mime = String::from_utf8(build_mime(&from, &message).unwrap().formatted()).unwrap();
+        assert!(mime.contains("From: Portfolio <no-reply@example.com>"));
+        assert!(mime.contains("To: owner@example.com"));
+        assert!(mime.contains("Reply-To: visitor@example.com"));
+        assert!(mime.contains("Content-Type: multipart/alternative"));
+        assert!(mime.contains("Content-Type: text/plain"));
+        assert!(mime.contains("Content-Type: text/html"));
+
+        let invalid = EmailMessage {
+            to: "not an address".to_string(),
+            ..message
+        };
+        assert!(build_mime(&from, &invalid).is_err());
+    }
+}
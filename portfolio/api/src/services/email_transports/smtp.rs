@@ -0,0 +1,112 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use lettre::message::Mailbox;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Tokio1Executor};
+
+use super::{build_mime, sender_mailbox, EmailTransport, SEND_TIMEOUT};
+use crate::config::Config;
+use crate::services::email_queue::EmailMessage;
+
+/// Encryption of the SMTP connection, read from `SMTP_TLS`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SmtpTls {
+    /// Plain connection upgraded with `STARTTLS` (required), port 587
+    #[default]
+    StartTls,
+    /// Implicit TLS, port 465
+    Tls,
+    /// No encryption, for a local relay only, port 25
+    None,
+}
+
+impl std::str::FromStr for SmtpTls {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "" | "starttls" => Ok(Self::StartTls),
+            "tls" | "ssl" => Ok(Self::Tls),
+            "none" => Ok(Self::None),
+            other => anyhow::bail!("Unknown SMTP TLS mode: {other}"),
+        }
+    }
+}
+
+impl SmtpTls {
+    /// Usual port of the mode
+    #[must_use]
+    pub const fn default_port(self) -> u16 {
+        match self {
+            Self::StartTls => 587,
+            Self::Tls => 465,
+            Self::None => 25,
+        }
+    }
+}
+
+/// Sends emails to an SMTP server (`SMTP_HOST`), authenticated when
+/// `SMTP_USERNAME` is set
+pub struct SmtpTransport {
+    mailer: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+}
+
+impl SmtpTransport {
+    /// # Errors
+    ///
+    /// Returns error if `SMTP_HOST` is missing, `SMTP_TLS` is unknown or
+    /// `SENDER_EMAIL` is not a valid address.
+    pub fn new(config: &Config) -> Result<Self> {
+        let host = config
+            .smtp_host
+            .as_deref()
+            .context("SMTP_HOST must be set for the smtp transport")?;
+        let tls: SmtpTls = config.smtp_tls.parse()?;
+
+        let mut builder = match tls {
+            SmtpTls::StartTls => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(host)?,
+            SmtpTls::Tls => AsyncSmtpTransport::<Tokio1Executor>::relay(host)?,
+            SmtpTls::None => AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(host),
+        }
+        .port(config.smtp_port.unwrap_or_else(|| tls.default_port()))
+        .timeout(Some(SEND_TIMEOUT));
+        if let Some(username) = &config.smtp_username {
+            builder = builder.credentials(Credentials::new(
+                username.clone(),
+                config.smtp_password.clone().unwrap_or_default(),
+            ));
+        }
+
+        Ok(Self {
+            mailer: builder.build(),
+            from: sender_mailbox(config)?,
+        })
+    }
+}
+
+#[async_trait]
+impl EmailTransport for SmtpTransport {
+    fn name(&self) -> &'static str {
+        "smtp"
+    }
+
+    async fn send(&self, message: &EmailMessage) -> Result<()> {
+        self.mailer.send(build_mime(&self.from, message)?).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tls_modes() {
+        assert_eq!("".parse::<SmtpTls>().unwrap(), SmtpTls::StartTls);
+        assert_eq!("SSL".parse::<SmtpTls>().unwrap(), SmtpTls::Tls);
+        assert_eq!("none".parse::<SmtpTls>().unwrap(), SmtpTls::None);
+        assert!("maybe".parse::<SmtpTls>().is_err());
+        assert_eq!(SmtpTls::Tls.default_port(), 465);
+    }
+}
@@ -0,0 +1,300 @@
+use anyhow::Result;
+use mongodb::bson::Document;
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Item field a filter rule is evaluated against
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RuleField {
+    Title,
+    Description,
+    Categories,
+}
+
+fn default_fields() -> Vec<RuleField> {
+    vec![
+        RuleField::Title,
+        RuleField::Description,
+        RuleField::Categories,
+    ]
+}
+
+/// Single keyword or regex rule as stored in configuration
+///
+/// Exactly one of `keyword` (case-insensitive substring) or `regex` must be set.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuleSpec {
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub keyword: Option<String>,
+    #[serde(default)]
+    pub regex: Option<String>,
+    #[serde(default = "default_fields")]
+    pub fields: Vec<RuleField>,
+}
+
+/// Include/exclude rule lists as stored in a feed document or the global rules file
+///
+/// ```json
+/// { "include": [{ "keyword": "rust" }],
+///   "exclude": [{ "name": "sponsored", "regex": "(?i)sponsori[sz]ed", "fields": ["title"] }] }
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RulesSpec {
+    #[serde(default)]
+    pub include: Vec<RuleSpec>,
+    #[serde(default)]
+    pub exclude: Vec<RuleSpec>,
+}
+
+#[derive(Debug)]
+enum Matcher {
+    Keyword(String),
+    Regex(Regex),
+}
+
+#[derive(Debug)]
+struct Rule {
+    label: String,
+    matcher: Matcher,
+    fields: Vec<RuleField>,
+}
+
+/// Item content evaluated by a [`RuleSet`]
+#[derive(Debug, Clone, Copy)]
+pub struct FilterInput<'a> {
+    pub title: &'a str,
+    pub description: &'a str,
+    pub categories: &'a [String],
+}
+
+impl Rule {
+    fn compile(scope: &str, spec: &RuleSpec) -> Result<Self> {
+        let (matcher, default_label) = match (&spec.keyword, &spec.regex) {
+            (Some(keyword), None) => (
+                Matcher::Keyword(keyword.to_lowercase()),
+                format!("keyword:{keyword}"),
+            ),
+            (None, Some(pattern)) => (
+                Matcher::Regex(RegexBuilder::new(pattern).size_limit(1 << 20).build()?),
+                format!("regex:{pattern}"),
+            ),
+            _ => anyhow::bail!("rule must define exactly one of `keyword` or `regex`"),
+        };
+
+        let fields = if spec.fields.is_empty() {
+            default_fields()
+        } else {
+            spec.fields.clone()
+        };
+
+        Ok(Self {
+            label: format!("{scope}/{}", spec.name.clone().unwrap_or(default_label)),
+            matcher,
+            fields,
+        })
+    }
+
+    fn matches_text(&self, text: &str) -> bool {
+        match &self.matcher {
+            Matcher::Keyword(keyword) => text.to_lowercase().contains(keyword),
+            Matcher::Regex(regex) => regex.is_match(text),
+        }
+    }
+
+    fn matches(&self, input: &FilterInput<'_>) -> bool {
+        self.fields.iter().any(|field| match field {
+            RuleField::Title => self.matches_text(input.title),
+            RuleField::Description => self.matches_text(input.description),
+            RuleField::Categories => input.categories.iter().any(|c| self.matches_text(c)),
+        })
+    }
+}
+
+/// Compiled include/exclude rules for one scope (global or a single feed)
+///
+/// An item passes when it matches at least one include rule (or there are none)
+/// and no exclude rule.
+#[derive(Debug, Default)]
+pub struct RuleSet {
+    scope: String,
+    include: Vec<Rule>,
+    exclude: Vec<Rule>,
+}
+
+impl RuleSet {
+    /// Compiles a rule specification, skipping invalid rules with a warning
+    #[must_use]
+    pub fn compile(scope: &str, spec: &RulesSpec) -> Self {
+        let compile_all = |specs: &[RuleSpec]| {
+            specs
+                .iter()
+                .filter_map(|spec| match Rule::compile(scope, spec) {
+                    Ok(rule) => Some(rule),
+                    Err(e) => {
+                        tracing::warn!("Ignoring invalid filter rule in {}: {}", scope, e);
+                        None
+                    }
+                })
+                .collect()
+        };
+
+        Self {
+            scope: scope.to_string(),
+            include: compile_all(&spec.include),
+            exclude: compile_all(&spec.exclude),
+        }
+    }
+
+    /// Builds the rule set of a feed from the optional `filters` sub-document
+    #[must_use]
+    pub fn from_feed_document(scope: &str, feed_doc: &Document) -> Self {
+        let Ok(filters) = feed_doc.get_document("filters") else {
+            return Self::default();
+        };
+
+        match mongodb::bson::from_document::<RulesSpec>(filters.clone()) {
+            Ok(spec) => Self::compile(scope, &spec),
+            Err(e) => {
+                tracing::warn!("Ignoring invalid filters for feed {}: {}", scope, e);
+                Self::default()
+            }
+        }
+    }
+
+    /// Loads the global rule set from a JSON file
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the file cannot be read or is not valid JSON.
+    pub fn from_json_file(path: &str) -> Result<Self> {
+        let content = std::fs::read_to_string(path)?;
+        let spec: RulesSpec = serde_json::from_str(&content)?;
+        Ok(Self::compile("global", &spec))
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.include.is_empty() && self.exclude.is_empty()
+    }
+
+    /// Returns the label of the rule rejecting the item, if any
+    #[must_use]
+    pub fn rejection(&self, input: &FilterInput<'_>) -> Option<String> {
+        if let Some(rule) = self.exclude.iter().find(|rule| rule.matches(input)) {
+            return Some(rule.label.clone());
+        }
+
+        if !self.include.is_empty() && !self.include.iter().any(|rule| rule.matches(input)) {
+            return Some(format!("{}/include", self.scope));
+        }
+
+        None
+    }
+}
+
+/// Outcome of a `store_items` run
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SyncReport {
+    /// Items read from all feeds
+    pub fetched: usize,
+    /// Items written to the `portfolio` collection
+    pub stored: usize,
+    /// Number of items dropped, keyed by rule label
+    pub dropped_by_rule: BTreeMap<String, usize>,
+}
+
+impl SyncReport {
+    pub fn record_drop(&mut self, label: String) {
+        *self.dropped_by_rule.entry(label).or_insert(0) += 1;
+    }
+
+    #[must_use]
+    pub fn dropped(&self) -> usize {
+        self.dropped_by_rule.values().sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input<'a>(
+        title: &'a str,
+        description: &'a str,
+        categories: &'a [String],
+    ) -> FilterInput<'a> {
+        FilterInput {
+            title,
+            description,
+            categories,
+        }
+    }
+
+    fn spec(json: &str) -> RulesSpec {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn test_exclude_keyword_is_case_insensitive() {
+        let rules = RuleSet::compile("feed", &spec(r#"{"exclude": [{"keyword": "Sponsored"}]}"#));
+
+        assert_eq!(
+            rules.rejection(&input("SPONSORED: buy now", "", &[])),
+            Some("feed/keyword:Sponsored".to_string())
+        );
+        assert_eq!(rules.rejection(&input("Rust 2024 edition", "", &[])), None);
+    }
+
+    #[test]
+    fn test_exclude_regex_limited_to_fields() {
+        let rules = RuleSet::compile(
+            "feed",
+            &spec(
+                r#"{"exclude": [{"name": "jobs", "regex": "(?i)hiring", "fields": ["categories"]}]}"#,
+            ),
+        );
+        let categories = vec!["Hiring".to_string()];
+
+        assert_eq!(
+            rules.rejection(&input("Post", "", &categories)),
+            Some("feed/jobs".to_string())
+        );
+        assert_eq!(rules.rejection(&input("We are hiring", "", &[])), None);
+    }
+
+    #[test]
+    fn test_include_rules_require_a_match() {
+        let rules = RuleSet::compile("global", &spec(r#"{"include": [{"keyword": "rust"}]}"#));
+
+        assert_eq!(rules.rejection(&input("Async Rust", "", &[])), None);
+        assert_eq!(
+            rules.rejection(&input("Go generics", "", &[])),
+            Some("global/include".to_string())
+        );
+    }
+
+    #[test]
+    fn test_invalid_rules_are_skipped() {
+        let rules = RuleSet::compile(
+            "feed",
+            &spec(r#"{"exclude": [{"regex": "("}, {"keyword": "a", "regex": "b"}]}"#),
+        );
+
+        assert!(rules.is_empty());
+    }
+
+    #[test]
+    fn test_report_counts_drops() {
+        let mut report = SyncReport::default();
+        report.record_drop("global/include".to_string());
+        report.record_drop("global/include".to_string());
+        report.record_drop("feed/jobs".to_string());
+
+        assert_eq!(report.dropped(), 3);
+        assert_eq!(report.dropped_by_rule["global/include"], 2);
+    }
+}
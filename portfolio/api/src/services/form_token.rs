@@ -0,0 +1,272 @@
+use anyhow::Result;
+use chrono::Utc;
+use mongodb::bson::{doc, Document};
+use mongodb::Database;
+use ring::hmac;
+use std::fmt;
+use std::time::Duration;
+
+use crate::config::Config;
+use crate::services::db::is_duplicate_key;
+
+/// Collection of the redeemed tokens, kept until they expire
+const COLLECTION: &str = "contact_tokens";
+
+/// Reason a contact form token is refused
+#[derive(Debug)]
+pub enum TokenError {
+    Missing,
+    /// Malformed or badly signed
+    Invalid,
+    /// Submitted sooner than the minimum fill time
+    TooFast,
+    Expired,
+    /// Already used by a previous submission
+    Replayed,
+    /// The token could not be recorded
+    Storage(anyhow::Error),
+}
+
+impl TokenError {
+    /// Message shown to the visitor
+    #[must_use]
+    pub const fn message(&self) -> &'static str {
+        match self {
+            Self::Missing | Self::Invalid => "Jeton de formulaire invalide",
+            Self::TooFast => "Formulaire envoyé trop rapidement",
+            Self::Expired => "Le formulaire a expiré, veuillez recharger la page",
+            Self::Replayed => "Ce formulaire a déjà été envoyé",
+            Self::Storage(_) => "Erreur lors de la vérification du formulaire",
+        }
+    }
+}
+
+impl fmt::Display for TokenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Storage(e) => write!(f, "{}: {e}", self.message()),
+            _ => f.write_str(self.message()),
+        }
+    }
+}
+
+impl std::error::Error for TokenError {}
+
+/// Secret signing the contact form tokens and challenges
+///
+/// `CONTACT_TOKEN_SECRET`, or a random secret when it is unset: tokens are
+/// then only valid on this instance and until it restarts.
+/// [`Config::new`] only allows this in development.
+#[must_use]
+pub fn contact_secret(config: &Config) -> Vec<u8> {
+    config
+        .contact_token_secret
+        .as_ref()
+        .filter(|secret| !secret.is_empty())
+        .map_or_else(
+            || {
+                tracing::warn!("CONTACT_TOKEN_SECRET is not set, using a random secret");
+                rand::random::<[u8; 32]>().to_vec()
+            },
+            |secret| secret.as_bytes().to_vec(),
+        )
+}
+
+/// Content of a valid token
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenClaims {
+    /// Issue time, in seconds since the epoch
+    pub issued_at: i64,
+    pub nonce: String,
+}
+
+/// Signed tokens proving the contact form was loaded, and when
+///
+/// A token is `{issued_at}.{nonce}.{signature}`, the signature being an
+/// HMAC-SHA256 of the first two parts. It is accepted once, between the
+/// minimum fill time and its maximum age after it was issued; redeemed
+/// nonces are stored in `contact_tokens` until the token expires.
+pub struct FormTokens {
+    db: Database,
+    key: hmac::Key,
+    min_fill: Duration,
+    max_age: Duration,
+}
+
+impl FormTokens {
+    #[must_use]
+    pub fn new(db: Database, secret: &[u8], min_fill: Duration, max_age: Duration) -> Self {
+        Self {
+            db,
+            key: hmac::Key::new(hmac::HMAC_SHA256, secret),
+            min_fill,
+            max_age,
+        }
+    }
+
+    /// Tokens signed with `secret` (see [`contact_secret`])
+    #[must_use]
+    pub fn from_config(db: Database, secret: &[u8], config: &Config) -> Self {
+        Self::new(
+            db,
+            secret,
+            Duration::from_secs(config.contact_min_fill_secs),
+            Duration::from_secs(config.contact_token_max_age_secs),
+        )
+    }
+
+    /// Minimum time between the issue of a token and its submission
+    #[must_use]
+    pub const fn min_fill(&self) -> Duration {
+        self.min_fill
+    }
+
+    /// Lifetime of a token
+    #[must_use]
+    pub const fn max_age(&self) -> Duration {
+        self.max_age
+    }
+
+    fn sign(&self, payload: &str) -> String {
+        hex::encode(hmac::sign(&self.key, payload.as_bytes()))
+    }
+
+    /// Issues a token dated `issued_at` (seconds since the epoch)
+    #[must_use]
+    pub fn issue_at(&self, issued_at: i64) -> String {
+        let payload = format!("{issued_at}.{}", hex::encode(rand::random::<[u8; 16]>()));
+        let signature = self.sign(&payload);
+        format!("{payload}.{signature}")
+    }
+
+    /// Issues a token dated now
+    #[must_use]
+    pub fn issue(&self) -> String {
+        self.issue_at(Utc::now().timestamp())
+    }
+
+    /// Checks the signature and age of a token at `now` (seconds since the epoch)
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TokenError::Invalid`], [`TokenError::TooFast`] or
+    /// [`TokenError::Expired`].
+    pub fn check(&self, token: &str, now: i64) -> Result<TokenClaims, TokenError> {
+        let (payload, signature) = token.trim().rsplit_once('.').ok_or(TokenError::Invalid)?;
+        let signature = hex::decode(signature).map_err(|_| TokenError::Invalid)?;
+        hmac::verify(&self.key, payload.as_bytes(), &signature).map_err(|_| TokenError::Invalid)?;
+
+        let (issued_at, nonce) = payload.split_once('.').ok_or(TokenError::Invalid)?;
+        let issued_at: i64 = issued_at.parse().map_err(|_| TokenError::Invalid)?;
+        let age = now.saturating_sub(issued_at);
+        if age < i64::try_from(self.min_fill.as_secs()).unwrap_or(i64::MAX) {
+            return Err(TokenError::TooFast);
+        }
+        if age > i64::try_from(self.max_age.as_secs()).unwrap_or(i64::MAX) {
+            return Err(TokenError::Expired);
+        }
+
+        Ok(TokenClaims {
+            issued_at,
+            nonce: nonce.to_string(),
+        })
+    }
+
+    /// Checks a submitted token and marks it as used.
+    ///
+    /// # Errors
+    ///
+    /// Returns the reason the token is refused, [`TokenError::Storage`] if
+    /// it cannot be recorded.
+    pub async fn redeem(&self, token: Option<&str>) -> Result<TokenClaims, TokenError> {
+        let token = token
+            .filter(|token| !token.trim().is_empty())
+            .ok_or(TokenError::Missing)?;
+        let claims = self.check(token, Utc::now().timestamp())?;
+
+        let expires_at = mongodb::bson::DateTime::from_millis(
+            (claims.issued_at + i64::try_from(self.max_age.as_secs()).unwrap_or(0))
+                .saturating_mul(1000),
+        );
+        let result = self
+            .db
+            .collection::<Document>(COLLECTION)
+            .insert_one(doc! {
+                "nonce": &claims.nonce,
+                "expires_at": expires_at,
+            })
+            .await;
+        match result {
+            Ok(_) => Ok(claims),
+            Err(e) => {
+                let error = anyhow::Error::from(e);
+                if is_duplicate_key(&error) {
+                    Err(TokenError::Replayed)
+                } else {
+                    Err(TokenError::Storage(error))
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn tokens() -> FormTokens {
+        let client = mongodb::Client::with_uri_str("mongodb://127.0.0.1:27017")
+            .await
+            .unwrap();
+        FormTokens::new(
+            client.database("portfolio_test"),
+            b"secret",
+            Duration::from_secs(3),
+            Duration::from_secs(3600),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_token_lifetime() {
+        let tokens = tokens().await;
+        let token = tokens.issue_at(1_000);
+
+        assert!(matches!(
+            tokens.check(&token, 1_001),
+            Err(TokenError::TooFast)
+        ));
+        let claims = tokens.check(&token, 1_003).unwrap();
+        assert_eq!(claims.issued_at, 1_000);
+        assert_eq!(claims.nonce.len(), 32);
+        assert!(tokens.check(&token, 4_600).is_ok());
+        assert!(matches!(
+            tokens.check(&token, 4_601),
+            Err(TokenError::Expired)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_tampered_token_is_invalid() {
+        let tokens = tokens().await;
+        let token = tokens.issue_at(1_000);
+        let backdated = token.replacen("1000.", "900.", 1);
+
+        for token in [backdated.as_str(), "1000.abc", "garbage", ""] {
+            assert!(matches!(
+                tokens.check(token, 1_010),
+                Err(TokenError::Invalid)
+            ));
+        }
+
+        let other = FormTokens::new(
+            tokens.db.clone(),
+            b"other secret",
+            Duration::from_secs(3),
+            Duration::from_secs(3600),
+        );
+        assert!(matches!(
+            other.check(&token, 1_010),
+            Err(TokenError::Invalid)
+        ));
+    }
+}
@@ -0,0 +1,306 @@
+use anyhow::Result;
+use futures_util::TryStreamExt;
+use mongodb::bson::{doc, Document};
+use mongodb::Database;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+
+/// Collection storing one document per translated key
+const COLLECTION: &str = "translations";
+/// Maximum length of a translated value
+pub const MAX_VALUE_CHARS: usize = 5000;
+
+static NAME_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"^[A-Za-z0-9_.-]{1,100}$").unwrap());
+
+/// Namespace → key → text
+pub type Translations = BTreeMap<String, BTreeMap<String, String>>;
+
+/// Serialized bundle of a locale, ready to be served
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Bundle {
+    pub locale: String,
+    /// JSON body
+    pub body: String,
+    /// Strong `ETag` of the body, quoted
+    pub etag: String,
+}
+
+/// Returns true for a valid namespace or key name
+#[must_use]
+pub fn is_valid_name(name: &str) -> bool {
+    NAME_RE.is_match(name)
+}
+
+/// Picks the best supported locale for a requested locale
+///
+/// The exact locale wins, then its primary language (`fr-CA` → `fr`).
+#[must_use]
+pub fn match_locale<'a>(requested: &str, supported: &'a [String]) -> Option<&'a str> {
+    let requested = requested.trim().to_lowercase().replace('_', "-");
+    let primary = requested.split('-').next().unwrap_or_default();
+    supported
+        .iter()
+        .find(|locale| **locale == requested)
+        .or_else(|| supported.iter().find(|locale| *locale == primary))
+        .map(String::as_str)
+}
+
+/// Negotiates the locale of an `Accept-Language` header
+///
+/// Languages are tried by decreasing quality, in header order for equal
+/// qualities. Returns `None` when no language is supported.
+#[must_use]
+pub fn negotiate<'a>(accept_language: &str, supported: &'a [String]) -> Option<&'a str> {
+    let mut languages: Vec<(&str, f32)> = accept_language
+        .split(',')
+        .filter_map(|entry| {
+            let mut parts = entry.split(';');
+            let tag = parts.next()?.trim();
+            let quality = parts
+                .find_map(|param| param.trim().strip_prefix("q="))
+                .map_or(Some(1.0), |q| q.trim().parse::<f32>().ok())?;
+            (!tag.is_empty() && tag != "*" && quality > 0.0).then_some((tag, quality))
+        })
+        .collect();
+    languages.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+    languages
+        .iter()
+        .find_map(|(tag, _)| match_locale(tag, supported))
+}
+
+/// Builds the bundle of a locale, completed with the default locale
+fn build_bundle(locale: &str, translations: &Translations) -> Bundle {
+    let body = serde_json::to_string(translations).unwrap_or_else(|_| "{}".to_string());
+    let etag = format!(
+        "\"{}\"",
+        hex::encode(&Sha256::digest(body.as_bytes())[..16])
+    );
+    Bundle {
+        locale: locale.to_string(),
+        body,
+        etag,
+    }
+}
+
+/// Site translations stored in `MongoDB`
+///
+/// Each document of the `translations` collection holds one `locale`,
+/// `namespace`, `key` and `value`. Keys missing from a locale fall back to
+/// the default locale (the first of `I18N_LOCALES`). Bundles are cached in
+/// memory until a key is edited.
+pub struct I18nService {
+    db: Database,
+    locales: Vec<String>,
+    cache: RwLock<HashMap<String, Arc<Bundle>>>,
+    /// Incremented on every edit, so that a bundle loaded during an edit is not cached
+    generation: AtomicU64,
+}
+
+impl I18nService {
+    #[must_use]
+    pub fn new(db: Database, locales: Vec<String>) -> Self {
+        Self {
+            db,
+            locales,
+            cache: RwLock::new(HashMap::new()),
+            generation: AtomicU64::new(0),
+        }
+    }
+
+    fn collection(&self) -> mongodb::Collection<Document> {
+        self.db.collection::<Document>(COLLECTION)
+    }
+
+    /// Locale used when nothing better matches
+    #[must_use]
+    pub fn default_locale(&self) -> &str {
+        &self.locales[0]
+    }
+
+    /// Supported locale matching a requested locale (see [`match_locale`])
+    #[must_use]
+    pub fn matching(&self, requested: &str) -> Option<&str> {
+        match_locale(requested, &self.locales)
+    }
+
+    /// Supported locale of an `Accept-Language` header, the default one otherwise
+    #[must_use]
+    pub fn negotiate(&self, accept_language: Option<&str>) -> &str {
+        accept_language
+            .and_then(|header| negotiate(header, &self.locales))
+            .unwrap_or_else(|| self.default_locale())
+    }
+
+    /// Returns true when the locale is one of `I18N_LOCALES`
+    #[must_use]
+    pub fn is_supported(&self, locale: &str) -> bool {
+        self.locales.iter().any(|supported| supported == locale)
+    }
+
+    async fn load(&self, locale: &str) -> Result<Translations> {
+        let docs: Vec<Document> = self
+            .collection()
+            .find(doc! { "locale": locale })
+            .await?
+            .try_collect()
+            .await?;
+
+        let mut translations = Translations::new();
+        for doc in &docs {
+            if let (Ok(namespace), Ok(key), Ok(value)) = (
+                doc.get_str("namespace"),
+                doc.get_str("key"),
+                doc.get_str("value"),
+            ) {
+                translations
+                    .entry(namespace.to_string())
+                    .or_default()
+                    .insert(key.to_string(), value.to_string());
+            }
+        }
+        Ok(translations)
+    }
+
+    /// Returns the bundle of a supported locale, from the cache when possible
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the translations cannot be read.
+    pub async fn bundle(&self, locale: &str) -> Result<Arc<Bundle>> {
+        if let Some(bundle) = self
+            .cache
+            .read()
+            .map_err(|_| anyhow::anyhow!("Translation cache poisoned"))?
+            .get(locale)
+        {
+            return Ok(bundle.clone());
+        }
+
+        let generation = self.generation.load(Ordering::Acquire);
+        let mut translations = self.load(locale).await?;
+        if locale != self.default_locale() {
+            for (namespace, keys) in self.load(self.default_locale()).await? {
+                let entries = translations.entry(namespace).or_default();
+                for (key, value) in keys {
+                    entries.entry(key).or_insert(value);
+                }
+            }
+        }
+
+        let bundle = Arc::new(build_bundle(locale, &translations));
+        let mut cache = self
+            .cache
+            .write()
+            .map_err(|_| anyhow::anyhow!("Translation cache poisoned"))?;
+        if self.generation.load(Ordering::Acquire) == generation {
+            cache.insert(locale.to_string(), bundle.clone());
+        }
+        Ok(bundle)
+    }
+
+    /// Drops every cached bundle, the default locale feeding all the others
+    fn invalidate(&self) {
+        if let Ok(mut cache) = self.cache.write() {
+            self.generation.fetch_add(1, Ordering::AcqRel);
+            cache.clear();
+        }
+    }
+
+    /// Creates or replaces a translated key
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the database update fails.
+    pub async fn set(&self, locale: &str, namespace: &str, key: &str, value: &str) -> Result<()> {
+        self.collection()
+            .update_one(
+                doc! { "locale": locale, "namespace": namespace, "key": key },
+                doc! { "$set": {
+                    "value": value,
+                    "updated_at": mongodb::bson::DateTime::now(),
+                }},
+            )
+            .upsert(true)
+            .await?;
+        self.invalidate();
+        Ok(())
+    }
+
+    /// Deletes a translated key
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the database operation fails. Returns `Ok(false)`
+    /// when the key does not exist.
+    pub async fn delete(&self, locale: &str, namespace: &str, key: &str) -> Result<bool> {
+        let result = self
+            .collection()
+            .delete_one(doc! { "locale": locale, "namespace": namespace, "key": key })
+            .await?;
+        self.invalidate();
+        Ok(result.deleted_count > 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn locales() -> Vec<String> {
+        vec!["fr".to_string(), "en".to_string(), "pt-br".to_string()]
+    }
+
+    #[test]
+    fn test_match_locale() {
+        let supported = locales();
+
+        assert_eq!(match_locale("EN", &supported), Some("en"));
+        assert_eq!(match_locale("fr-CA", &supported), Some("fr"));
+        assert_eq!(match_locale("pt_BR", &supported), Some("pt-br"));
+        assert_eq!(match_locale("de", &supported), None);
+    }
+
+    #[test]
+    fn test_negotiate_by_quality() {
+        let supported = locales();
+
+        assert_eq!(
+            negotiate("de-DE, en;q=0.8, fr;q=0.9", &supported),
+            Some("fr")
+        );
+        assert_eq!(negotiate("en-GB,en;q=0.9", &supported), Some("en"));
+        assert_eq!(negotiate("fr;q=0, de, *;q=0.1", &supported), None);
+        assert_eq!(negotiate("", &supported), None);
+    }
+
+    #[test]
+    fn test_bundle_etag_follows_content() {
+        let mut translations = Translations::new();
+        translations
+            .entry("home".to_string())
+            .or_default()
+            .insert("title".to_string(), "Bonjour".to_string());
+
+        let bundle = build_bundle("fr", &translations);
+        assert_eq!(bundle.body, r#"{"home":{"title":"Bonjour"}}"#);
+        assert_eq!(bundle, build_bundle("fr", &translations));
+
+        translations
+            .get_mut("home")
+            .unwrap()
+            .insert("title".to_string(), "Salut".to_string());
+        assert_ne!(build_bundle("fr", &translations).etag, bundle.etag);
+    }
+
+    #[test]
+    fn test_names() {
+        assert!(is_valid_name("contact.form-title_2"));
+        assert!(!is_valid_name("$where"));
+        assert!(!is_valid_name(""));
+    }
+}
@@ -0,0 +1,207 @@
+use anyhow::Result;
+use futures_util::StreamExt;
+use image::{imageops::FilterType, DynamicImage, ImageReader, Limits};
+use mongodb::bson::{doc, Document};
+use mongodb::Database;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::Cursor;
+use std::time::Duration;
+
+use crate::services::safe_http;
+
+/// Maximum size of a downloaded image
+const MAX_IMAGE_BYTES: usize = 5 * 1024 * 1024;
+/// Maximum decoded dimension, protecting against decompression bombs
+const MAX_DIMENSION: u32 = 8000;
+/// Timeout of a single image download
+const FETCH_TIMEOUT: Duration = Duration::from_secs(10);
+/// Number of images downloaded concurrently
+const CONCURRENT_FETCHES: usize = 4;
+/// Size of the thumbnail used for the dominant color and the blurhash
+const THUMBNAIL_SIZE: u32 = 32;
+
+/// Layout and preview information about an article image
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ImageMetadata {
+    pub width: u32,
+    pub height: u32,
+    /// Most frequent color, as `#rrggbb`
+    pub dominant_color: String,
+    pub blurhash: String,
+}
+
+/// Computes the metadata of an encoded image
+///
+/// # Errors
+///
+/// Returns error if the bytes are not a supported image format, exceed the
+/// dimension limits, or cannot be decoded.
+pub fn analyze(bytes: &[u8]) -> Result<ImageMetadata> {
+    let mut limits = Limits::default();
+    limits.max_image_width = Some(MAX_DIMENSION);
+    limits.max_image_height = Some(MAX_DIMENSION);
+
+    let mut reader = ImageReader::new(Cursor::new(bytes)).with_guessed_format()?;
+    if reader.format().is_none() {
+        anyhow::bail!("Unrecognized image format");
+    }
+    reader.limits(limits);
+    let image = reader.decode()?;
+
+    let thumbnail = image
+        .resize_exact(THUMBNAIL_SIZE, THUMBNAIL_SIZE, FilterType::Triangle)
+        .to_rgba8();
+    let blurhash = blurhash::encode(4, 3, THUMBNAIL_SIZE, THUMBNAIL_SIZE, thumbnail.as_raw())
+        .map_err(|e| anyhow::anyhow!("Blurhash encoding failed: {e:?}"))?;
+
+    Ok(ImageMetadata {
+        width: image.width(),
+        height: image.height(),
+        dominant_color: dominant_color(&DynamicImage::ImageRgba8(thumbnail)),
+        blurhash,
+    })
+}
+
+/// Finds the most frequent color, bucketing channels to 4 bits
+///
+/// Returns the average of the pixels in the most populated bucket. Transparent
+/// pixels are ignored.
+fn dominant_color(image: &DynamicImage) -> String {
+    let mut buckets: HashMap<[u8; 3], (u32, [u32; 3])> = HashMap::new();
+    for pixel in image.to_rgba8().pixels() {
+        let [r, g, b, a] = pixel.0;
+        if a < 128 {
+            continue;
+        }
+        let entry = buckets
+            .entry([r >> 4, g >> 4, b >> 4])
+            .or_insert((0, [0; 3]));
+        entry.0 += 1;
+        entry.1[0] += u32::from(r);
+        entry.1[1] += u32::from(g);
+        entry.1[2] += u32::from(b);
+    }
+
+    let rgb = buckets
+        .into_iter()
+        .max_by(|a, b| a.1 .0.cmp(&b.1 .0).then(b.0.cmp(&a.0)))
+        .map_or([0, 0, 0], |(_, (count, sum))| {
+            sum.map(|channel| (channel / count) as u8)
+        });
+    format!("#{}", hex::encode(rgb))
+}
+
+/// Downloads article images to record their dimensions, dominant color and blurhash
+///
+/// Results are cached in the `image_meta` collection by image URL, including
+/// URLs that turned out not to be images, so each image is downloaded once.
+pub struct ImageMetadataFetcher {
+    db: Database,
+    client: reqwest::Client,
+}
+
+impl ImageMetadataFetcher {
+    #[must_use]
+    pub fn new(db: Database) -> Self {
+        Self {
+            db,
+            client: safe_http::guarded_client(FETCH_TIMEOUT),
+        }
+    }
+
+    /// Downloads an image, up to [`MAX_IMAGE_BYTES`]
+    async fn download(&self, url: &str) -> Result<Vec<u8>> {
+        safe_http::fetch_limited(&self.client, url, "image/*", MAX_IMAGE_BYTES).await
+    }
+
+    /// Returns the metadata of an image, downloading it only if it is not cached
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the cache cannot be read or written. Invalid images are
+    /// cached and reported as `Ok(None)`.
+    pub async fn lookup(&self, url: &str) -> Result<Option<ImageMetadata>> {
+        let cache = self.db.collection::<Document>("image_meta");
+        if let Some(cached) = cache.find_one(doc! { "url": url }).await? {
+            return Ok(mongodb::bson::from_document(cached).ok());
+        }
+
+        let metadata = match self.download(url).await {
+            Ok(bytes) => tokio::task::spawn_blocking(move || analyze(&bytes)).await?,
+            Err(e) => Err(e),
+        };
+
+        let mut entry = match &metadata {
+            Ok(metadata) => mongodb::bson::to_document(metadata)?,
+            Err(e) => {
+                tracing::warn!("Invalid image {}: {}", url, e);
+                doc! { "error": e.to_string() }
+            }
+        };
+        entry.insert("fetched_at", mongodb::bson::DateTime::now());
+        cache
+            .update_one(doc! { "url": url }, doc! { "$set": entry })
+            .upsert(true)
+            .await?;
+
+        Ok(metadata.ok())
+    }
+
+    /// Looks up several images concurrently, keeping only valid ones
+    pub async fn lookup_all(&self, urls: Vec<String>) -> HashMap<String, ImageMetadata> {
+        futures_util::stream::iter(urls)
+            .map(|url| async move {
+                match self.lookup(&url).await {
+                    Ok(Some(metadata)) => Some((url, metadata)),
+                    Ok(None) => None,
+                    Err(e) => {
+                        tracing::error!("Error caching image metadata for {}: {}", url, e);
+                        None
+                    }
+                }
+            })
+            .buffer_unordered(CONCURRENT_FETCHES)
+            .filter_map(|result| async move { result })
+            .collect()
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{ImageFormat, Rgb, RgbImage};
+
+    fn encode_png(image: RgbImage) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        image
+            .write_to(&mut Cursor::new(&mut bytes), ImageFormat::Png)
+            .unwrap();
+        bytes
+    }
+
+    #[test]
+    fn test_analyze_png() {
+        // Mostly red image with a blue stripe
+        let image = RgbImage::from_fn(64, 48, |x, _| {
+            if x < 48 {
+                Rgb([200, 20, 20])
+            } else {
+                Rgb([20, 20, 200])
+            }
+        });
+
+        let metadata = analyze(&encode_png(image)).unwrap();
+
+        assert_eq!((metadata.width, metadata.height), (64, 48));
+        assert_eq!(metadata.dominant_color, "#c81414");
+        assert_eq!(metadata.blurhash.len(), 4 + 2 * 4 * 3);
+    }
+
+    #[test]
+    fn test_rejects_non_images() {
+        assert!(analyze(b"<html>not an image</html>").is_err());
+        assert!(analyze(b"\x89PNG\r\n\x1a\ntruncated").is_err());
+    }
+}
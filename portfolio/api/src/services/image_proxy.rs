@@ -0,0 +1,442 @@
+use anyhow::Result;
+use image::codecs::jpeg::JpegEncoder;
+use image::codecs::webp::WebPEncoder;
+use image::{imageops::FilterType, DynamicImage, ImageReader, Limits};
+use lru::LruCache;
+use mongodb::bson::{doc, Document};
+use mongodb::Database;
+use sha2::{Digest, Sha256};
+use std::io::Cursor;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::services::safe_http;
+
+/// Widths served by the proxy, so that the disk cache stays bounded
+pub const ALLOWED_WIDTHS: [u32; 6] = [160, 320, 480, 640, 960, 1280];
+/// Width served when none is requested
+pub const DEFAULT_WIDTH: u32 = 640;
+
+/// Maximum size of an original image
+const MAX_SOURCE_BYTES: usize = 10 * 1024 * 1024;
+/// Maximum decoded dimension, protecting against decompression bombs
+const MAX_DIMENSION: u32 = 10_000;
+/// Timeout of a single upstream fetch
+const FETCH_TIMEOUT: Duration = Duration::from_secs(10);
+const JPEG_QUALITY: u8 = 80;
+
+/// Identifier of a proxied image, derived from its original URL
+#[must_use]
+pub fn image_hash(url: &str) -> String {
+    hex::encode(&Sha256::digest(url.as_bytes())[..12])
+}
+
+/// Builds the proxy URL of an original image
+#[must_use]
+pub fn proxy_url(api_base_url: &str, hash: &str) -> String {
+    format!("{}/api/img/{hash}", api_base_url.trim_end_matches('/'))
+}
+
+fn is_valid_hash(hash: &str) -> bool {
+    hash.len() == 24 && hash.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+/// Rounds a requested width up to the nearest served width
+#[must_use]
+pub fn snap_width(requested: Option<u32>) -> u32 {
+    let requested = requested.unwrap_or(DEFAULT_WIDTH);
+    ALLOWED_WIDTHS
+        .iter()
+        .copied()
+        .find(|width| *width >= requested)
+        .unwrap_or(ALLOWED_WIDTHS[ALLOWED_WIDTHS.len() - 1])
+}
+
+/// Encoding of a proxied image
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    Jpeg,
+    WebP,
+}
+
+impl OutputFormat {
+    #[must_use]
+    pub fn content_type(self) -> &'static str {
+        match self {
+            Self::Jpeg => "image/jpeg",
+            Self::WebP => "image/webp",
+        }
+    }
+
+    fn extension(self) -> &'static str {
+        match self {
+            Self::Jpeg => "jpg",
+            Self::WebP => "webp",
+        }
+    }
+}
+
+/// Picks the output encoding of an image
+///
+/// The WebP encoder of the `image` crate is lossless only, which makes photos
+/// larger than their JPEG version, so WebP is kept for images with transparency,
+/// when the client accepts it. Everything else is served as JPEG.
+fn output_format(image: &DynamicImage, accepts_webp: bool) -> OutputFormat {
+    if accepts_webp && image.color().has_alpha() {
+        OutputFormat::WebP
+    } else {
+        OutputFormat::Jpeg
+    }
+}
+
+/// Decodes an image, shrinks it to `width` (never enlarging it) and re-encodes it
+///
+/// # Errors
+///
+/// Returns error if the bytes are not a supported image or encoding fails.
+pub fn resize_and_encode(
+    bytes: &[u8],
+    width: u32,
+    accepts_webp: bool,
+) -> Result<(OutputFormat, Vec<u8>)> {
+    let mut limits = Limits::default();
+    limits.max_image_width = Some(MAX_DIMENSION);
+    limits.max_image_height = Some(MAX_DIMENSION);
+
+    let mut reader = ImageReader::new(Cursor::new(bytes)).with_guessed_format()?;
+    if reader.format().is_none() {
+        anyhow::bail!("Unrecognized image format");
+    }
+    reader.limits(limits);
+    let mut image = reader.decode()?;
+
+    if image.width() > width {
+        let height =
+            (u64::from(image.height()) * u64::from(width) / u64::from(image.width())).max(1) as u32;
+        image = image.resize_exact(width, height, FilterType::Lanczos3);
+    }
+
+    let format = output_format(&image, accepts_webp);
+    let mut encoded = Vec::new();
+    match format {
+        OutputFormat::WebP => {
+            WebPEncoder::new_lossless(&mut encoded).encode(
+                image.to_rgba8().as_raw(),
+                image.width(),
+                image.height(),
+                image::ExtendedColorType::Rgba8,
+            )?;
+        }
+        OutputFormat::Jpeg => {
+            let rgb = if image.color().has_alpha() {
+                flatten_on_white(&image)
+            } else {
+                image.to_rgb8()
+            };
+            JpegEncoder::new_with_quality(&mut encoded, JPEG_QUALITY).encode_image(&rgb)?;
+        }
+    }
+
+    Ok((format, encoded))
+}
+
+/// Composites a transparent image on a white background
+fn flatten_on_white(image: &DynamicImage) -> image::RgbImage {
+    let rgba = image.to_rgba8();
+    image::RgbImage::from_fn(rgba.width(), rgba.height(), |x, y| {
+        let [r, g, b, a] = rgba.get_pixel(x, y).0;
+        let blend =
+            |c: u8| ((u32::from(c) * u32::from(a) + 255 * (255 - u32::from(a))) / 255) as u8;
+        image::Rgb([blend(r), blend(g), blend(b)])
+    })
+}
+
+/// Size-bounded directory of cached files, evicting the least recently used ones
+pub struct DiskCache {
+    dir: PathBuf,
+    max_bytes: u64,
+    /// File name -> size, in recency order
+    index: Mutex<(LruCache<String, u64>, u64)>,
+}
+
+impl DiskCache {
+    /// Opens a cache directory, indexing the files it already contains
+    ///
+    /// Existing files are ordered by modification time, and the oldest ones are
+    /// removed if the directory exceeds `max_bytes`.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the directory cannot be created or listed.
+    pub fn open(dir: impl Into<PathBuf>, max_bytes: u64) -> Result<Self> {
+        let dir = dir.into();
+        std::fs::create_dir_all(&dir)?;
+
+        let mut files = Vec::new();
+        for entry in std::fs::read_dir(&dir)? {
+            let entry = entry?;
+            let metadata = entry.metadata()?;
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if !metadata.is_file() || name.ends_with(".tmp") {
+                continue;
+            }
+            files.push((metadata.modified()?, name, metadata.len()));
+        }
+        files.sort();
+
+        let cache = Self {
+            dir,
+            max_bytes,
+            index: Mutex::new((LruCache::unbounded(), 0)),
+        };
+        let evicted = {
+            let mut index = cache.index.lock().unwrap();
+            for (_, name, size) in files {
+                index.0.put(name, size);
+                index.1 += size;
+            }
+            Self::evict(&mut index, max_bytes)
+        };
+        cache.remove_files(&evicted);
+        Ok(cache)
+    }
+
+    fn evict(index: &mut (LruCache<String, u64>, u64), max_bytes: u64) -> Vec<String> {
+        let mut evicted = Vec::new();
+        while index.1 > max_bytes {
+            let Some((name, size)) = index.0.pop_lru() else {
+                break;
+            };
+            index.1 -= size;
+            evicted.push(name);
+        }
+        evicted
+    }
+
+    fn remove_files(&self, names: &[String]) {
+        for name in names {
+            if let Err(e) = std::fs::remove_file(self.dir.join(name)) {
+                tracing::warn!("Error evicting cached image {}: {}", name, e);
+            }
+        }
+    }
+
+    /// Reads a cached file, marking it as recently used
+    pub async fn get(&self, name: &str) -> Option<Vec<u8>> {
+        let known = self.index.lock().unwrap().0.get(name).is_some();
+        if !known {
+            return None;
+        }
+        match tokio::fs::read(self.dir.join(name)).await {
+            Ok(bytes) => Some(bytes),
+            Err(_) => {
+                let mut index = self.index.lock().unwrap();
+                if let Some(size) = index.0.pop(name) {
+                    index.1 -= size;
+                }
+                None
+            }
+        }
+    }
+
+    /// Stores a file, then evicts the least recently used files over the limit
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the file cannot be written.
+    pub async fn put(&self, name: &str, bytes: &[u8]) -> Result<()> {
+        let size = bytes.len() as u64;
+        if size > self.max_bytes {
+            return Ok(());
+        }
+
+        // Write then rename, so that readers never see a partial file
+        let path = self.dir.join(name);
+        let temporary = self.dir.join(format!("{name}.tmp"));
+        tokio::fs::write(&temporary, bytes).await?;
+        tokio::fs::rename(&temporary, &path).await?;
+
+        let evicted = {
+            let mut index = self.index.lock().unwrap();
+            if let Some(previous) = index.0.put(name.to_string(), size) {
+                index.1 -= previous;
+            }
+            index.1 += size;
+            Self::evict(&mut index, self.max_bytes)
+        };
+        self.remove_files(&evicted);
+        Ok(())
+    }
+
+    /// Total size of the cached files
+    #[must_use]
+    pub fn size(&self) -> u64 {
+        self.index.lock().unwrap().1
+    }
+}
+
+/// Resized image returned by the proxy
+pub struct ProxiedImage {
+    pub format: OutputFormat,
+    pub bytes: Vec<u8>,
+}
+
+/// Serves resized copies of the stored articles' images
+///
+/// Only images referenced by a stored article (through its `image_hash`) can be
+/// proxied, which keeps the endpoint from being used as an open proxy.
+pub struct ImageProxy {
+    db: Database,
+    client: reqwest::Client,
+    cache: DiskCache,
+}
+
+impl ImageProxy {
+    /// Creates the proxy and opens its disk cache
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the cache directory cannot be opened.
+    pub fn new(db: Database, cache_dir: &str, cache_max_bytes: u64) -> Result<Self> {
+        Ok(Self {
+            db,
+            client: safe_http::guarded_client(FETCH_TIMEOUT),
+            cache: DiskCache::open(cache_dir, cache_max_bytes)?,
+        })
+    }
+
+    /// Returns the resized image of a hash, fetching the original if needed
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the original cannot be fetched or is not an image.
+    ///
+    /// # Returns
+    /// `None` if no stored article references this hash
+    pub async fn get(
+        &self,
+        hash: &str,
+        width: u32,
+        accepts_webp: bool,
+    ) -> Result<Option<ProxiedImage>> {
+        if !is_valid_hash(hash) {
+            return Ok(None);
+        }
+
+        // Images with transparency depend on the Accept header
+        let candidates: &[OutputFormat] = if accepts_webp {
+            &[OutputFormat::WebP, OutputFormat::Jpeg]
+        } else {
+            &[OutputFormat::Jpeg]
+        };
+        for format in candidates {
+            let name = format!("{hash}-{width}.{}", format.extension());
+            if let Some(bytes) = self.cache.get(&name).await {
+                return Ok(Some(ProxiedImage {
+                    format: *format,
+                    bytes,
+                }));
+            }
+        }
+
+        let Some(url) = self
+            .db
+            .collection::<Document>("portfolio")
+            .find_one(doc! { "image_hash": hash })
+            .await?
+            .and_then(|doc| doc.get_str("image_url").ok().map(str::to_string))
+        else {
+            return Ok(None);
+        };
+
+        let original = safe_http::fetch_limited(&self.client, &url, "image/*", MAX_SOURCE_BYTES)
+            .await
+            .map_err(|e| anyhow::anyhow!("Error fetching {url}: {e}"))?;
+        let (format, bytes) =
+            tokio::task::spawn_blocking(move || resize_and_encode(&original, width, accepts_webp))
+                .await??;
+
+        let name = format!("{hash}-{width}.{}", format.extension());
+        if let Err(e) = self.cache.put(&name, &bytes).await {
+            tracing::error!("Error caching image {}: {}", name, e);
+        }
+
+        Ok(Some(ProxiedImage { format, bytes }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{ImageFormat, Rgba, RgbaImage};
+
+    fn encode_png(image: &DynamicImage) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        image
+            .write_to(&mut Cursor::new(&mut bytes), ImageFormat::Png)
+            .unwrap();
+        bytes
+    }
+
+    #[test]
+    fn test_snap_width() {
+        assert_eq!(snap_width(None), DEFAULT_WIDTH);
+        assert_eq!(snap_width(Some(1)), 160);
+        assert_eq!(snap_width(Some(321)), 480);
+        assert_eq!(snap_width(Some(5000)), 1280);
+    }
+
+    #[test]
+    fn test_resize_keeps_ratio_and_never_enlarges() {
+        let opaque = DynamicImage::new_rgb8(800, 400);
+
+        let (format, bytes) = resize_and_encode(&encode_png(&opaque), 320, true).unwrap();
+        let decoded = image::load_from_memory(&bytes).unwrap();
+        assert_eq!(format, OutputFormat::Jpeg);
+        assert_eq!((decoded.width(), decoded.height()), (320, 160));
+
+        let (_, bytes) = resize_and_encode(&encode_png(&opaque), 1280, false).unwrap();
+        let decoded = image::load_from_memory(&bytes).unwrap();
+        assert_eq!(decoded.width(), 800);
+    }
+
+    #[test]
+    fn test_transparent_images_use_webp_when_accepted() {
+        let transparent =
+            DynamicImage::ImageRgba8(RgbaImage::from_pixel(10, 10, Rgba([255, 0, 0, 0])));
+
+        let (format, _) = resize_and_encode(&encode_png(&transparent), 160, true).unwrap();
+        assert_eq!(format, OutputFormat::WebP);
+
+        let (format, bytes) = resize_and_encode(&encode_png(&transparent), 160, false).unwrap();
+        assert_eq!(format, OutputFormat::Jpeg);
+        let decoded = image::load_from_memory(&bytes).unwrap().to_rgb8();
+        assert!(decoded.get_pixel(5, 5).0.iter().all(|c| *c > 240));
+    }
+
+    #[test]
+    fn test_rejects_non_images() {
+        assert!(resize_and_encode(b"<svg></svg>", 320, true).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_disk_cache_evicts_least_recently_used() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = DiskCache::open(dir.path(), 10).unwrap();
+
+        cache.put("a", &[0; 4]).await.unwrap();
+        cache.put("b", &[0; 4]).await.unwrap();
+        assert!(cache.get("a").await.is_some());
+        cache.put("c", &[0; 4]).await.unwrap();
+
+        assert!(cache.get("a").await.is_some());
+        assert!(cache.get("b").await.is_none());
+        assert!(!dir.path().join("b").exists());
+        assert_eq!(cache.size(), 8);
+
+        // The index is rebuilt from the directory
+        let reopened = DiskCache::open(dir.path(), 4).unwrap();
+        assert_eq!(reopened.size(), 4);
+    }
+}
@@ -0,0 +1,339 @@
+use anyhow::Result;
+use futures_util::{StreamExt, TryStreamExt};
+use mongodb::bson::{doc, Document};
+use mongodb::Database;
+use std::collections::{BTreeMap, HashSet};
+use std::time::Duration;
+
+use crate::config::Config;
+use crate::services::{placeholder, safe_http};
+
+/// Timeout of a single check
+const CHECK_TIMEOUT: Duration = Duration::from_secs(10);
+/// Number of hosts checked concurrently
+const CONCURRENT_HOSTS: usize = 4;
+/// Consecutive failed checks after which a URL is considered dead
+pub const DEAD_AFTER_FAILURES: i32 = 2;
+
+/// Result of a single check
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckOutcome {
+    Alive,
+    /// Missing resource, server error or unreachable host
+    Broken,
+    /// The server refuses bots or rate limits us, or the URL may not be
+    /// fetched, nothing can be concluded
+    Inconclusive,
+}
+
+/// Classifies the HTTP status of a check, `None` meaning the request failed
+#[must_use]
+pub fn classify(status: Option<u16>) -> CheckOutcome {
+    match status {
+        Some(200..=399) => CheckOutcome::Alive,
+        Some(401 | 403 | 405 | 429) => CheckOutcome::Inconclusive,
+        Some(_) | None => CheckOutcome::Broken,
+    }
+}
+
+/// Counts of a checker run
+#[derive(Debug, Default)]
+pub struct LinkCheckReport {
+    pub checked: usize,
+    pub dead_links: usize,
+    pub dead_images: usize,
+}
+
+/// Kind of checked URL
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum UrlKind {
+    Link,
+    Image,
+}
+
+impl UrlKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Link => "link",
+            Self::Image => "image",
+        }
+    }
+}
+
+/// Checks the links and images of the stored articles
+///
+/// Check results are kept in the `link_status` collection, so that they survive
+/// syncs. A URL is dead after [`DEAD_AFTER_FAILURES`] consecutive failed checks:
+/// articles with a dead link are hidden, and dead images are replaced by the
+/// placeholder. Sync re-applies these statuses, and a URL that works again is
+/// restored at the next sync.
+///
+/// Requests are spread over hosts: hosts are checked concurrently, but the URLs
+/// of one host are checked one after the other, with a delay between requests.
+pub struct LinkChecker {
+    db: Database,
+    client: reqwest::Client,
+    public_api_url: String,
+    frontend_url: String,
+    host_delay: Duration,
+}
+
+impl LinkChecker {
+    #[must_use]
+    pub fn new(db: Database, config: &Config) -> Self {
+        Self {
+            db,
+            client: safe_http::guarded_client(CHECK_TIMEOUT),
+            public_api_url: config.public_api_url.clone(),
+            frontend_url: config.frontend_url.clone(),
+            host_delay: Duration::from_millis(config.link_check_host_delay_ms),
+        }
+    }
+
+    /// Returns the URLs currently considered dead
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the statuses cannot be read.
+    pub async fn load_dead_urls(db: &Database) -> Result<HashSet<String>> {
+        let statuses = db.collection::<Document>("link_status");
+        Ok(statuses
+            .find(doc! { "dead": true })
+            .await?
+            .try_collect::<Vec<Document>>()
+            .await?
+            .into_iter()
+            .filter_map(|status| status.get_str("url").ok().map(str::to_string))
+            .collect())
+    }
+
+    /// Checks a URL with HEAD, confirming failures with GET
+    ///
+    /// Some servers reject or mishandle HEAD requests, so only a GET response
+    /// can mark a URL as broken. URLs refused by [`safe_http::check_url`] are
+    /// not requested, and are inconclusive rather than broken.
+    async fn check(&self, url: &str) -> CheckOutcome {
+        let Ok(parsed) = url::Url::parse(url) else {
+            return CheckOutcome::Broken;
+        };
+        if let Err(e) = safe_http::check_url(&parsed) {
+            tracing::debug!("Not checking {}: {}", url, e);
+            return CheckOutcome::Inconclusive;
+        }
+
+        let send = |request: reqwest::RequestBuilder| {
+            request
+                .header("User-Agent", "Mozilla/5.0 (compatible; RSSBot/1.0)")
+                .send()
+        };
+        if let Ok(response) = send(self.client.head(url)).await {
+            if classify(Some(response.status().as_u16())) == CheckOutcome::Alive {
+                return CheckOutcome::Alive;
+            }
+        }
+        match send(self.client.get(url)).await {
+            Ok(response) => classify(Some(response.status().as_u16())),
+            Err(e) => {
+                tracing::debug!("Error checking {}: {}", url, e);
+                classify(None)
+            }
+        }
+    }
+
+    /// Records the outcome of a check and returns whether the URL is now dead
+    async fn record(&self, url: &str, kind: UrlKind, outcome: CheckOutcome) -> Result<bool> {
+        let statuses = self.db.collection::<Document>("link_status");
+        let previous_failures = statuses
+            .find_one(doc! { "url": url })
+            .await?
+            .and_then(|status| status.get_i32("failures").ok())
+            .unwrap_or(0);
+
+        let failures = match outcome {
+            CheckOutcome::Alive => 0,
+            CheckOutcome::Broken => previous_failures + 1,
+            CheckOutcome::Inconclusive => previous_failures,
+        };
+        let dead = failures >= DEAD_AFTER_FAILURES;
+        statuses
+            .update_one(
+                doc! { "url": url },
+                doc! { "$set": {
+                    "kind": kind.as_str(),
+                    "failures": failures,
+                    "dead": dead,
+                    "checked_at": mongodb::bson::DateTime::now(),
+                }},
+            )
+            .upsert(true)
+            .await?;
+        Ok(dead)
+    }
+
+    /// Checks the URLs of one host sequentially, returning the dead ones
+    async fn check_host(&self, urls: Vec<(String, UrlKind)>) -> Vec<(String, UrlKind)> {
+        let mut dead = Vec::new();
+        for (index, (url, kind)) in urls.into_iter().enumerate() {
+            if index > 0 {
+                tokio::time::sleep(self.host_delay).await;
+            }
+            let outcome = self.check(&url).await;
+            match self.record(&url, kind, outcome).await {
+                Ok(true) => dead.push((url, kind)),
+                Ok(false) => {}
+                Err(e) => tracing::error!("Error recording status of {}: {}", url, e),
+            }
+        }
+        dead
+    }
+
+    /// Checks every stored link and image, then hides dead links and swaps dead images
+    ///
+    /// Placeholders and URLs of the site itself are not checked.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the stored articles cannot be read or updated.
+    pub async fn run(&self) -> Result<LinkCheckReport> {
+        let portfolio = self.db.collection::<Document>("portfolio");
+        let placeholder_prefix = format!(
+            "{}/api/placeholder",
+            self.public_api_url.trim_end_matches('/')
+        );
+        let site = url::Url::parse(&self.frontend_url).ok().map(|u| u.origin());
+
+        let mut by_host: BTreeMap<String, Vec<(String, UrlKind)>> = BTreeMap::new();
+        for (field, kind) in [("url", UrlKind::Link), ("image_url", UrlKind::Image)] {
+            for value in portfolio.distinct(field, doc! {}).await? {
+                let Some(url) = value.as_str() else {
+                    continue;
+                };
+                if url.starts_with(&placeholder_prefix) {
+                    continue;
+                }
+                let Ok(parsed) = url::Url::parse(url) else {
+                    continue;
+                };
+                if site.as_ref() == Some(&parsed.origin()) {
+                    continue;
+                }
+                let Some(host) = parsed.host_str().map(str::to_string) else {
+                    continue;
+                };
+                by_host
+                    .entry(host)
+                    .or_default()
+                    .push((url.to_string(), kind));
+            }
+        }
+
+        let mut report = LinkCheckReport {
+            checked: by_host.values().map(Vec::len).sum(),
+            ..LinkCheckReport::default()
+        };
+        tracing::info!(
+            "Checking {} URL(s) on {} host(s)",
+            report.checked,
+            by_host.len()
+        );
+
+        let dead: Vec<(String, UrlKind)> = futures_util::stream::iter(by_host.into_values())
+            .map(|urls| self.check_host(urls))
+            .buffer_unordered(CONCURRENT_HOSTS)
+            .flat_map(futures_util::stream::iter)
+            .collect()
+            .await;
+
+        let dead_links: Vec<&str> = dead
+            .iter()
+            .filter(|(_, kind)| *kind == UrlKind::Link)
+            .map(|(url, _)| url.as_str())
+            .collect();
+        if !dead_links.is_empty() {
+            let result = portfolio
+                .update_many(
+                    doc! { "url": { "$in": &dead_links } },
+                    doc! { "$set": { "hidden": true, "dead_link": true } },
+                )
+                .await?;
+            report.dead_links = result.matched_count as usize;
+        }
+
+        let dead_images: Vec<&str> = dead
+            .iter()
+            .filter(|(_, kind)| *kind == UrlKind::Image)
+            .map(|(url, _)| url.as_str())
+            .collect();
+        if !dead_images.is_empty() {
+            let articles: Vec<Document> = portfolio
+                .find(doc! { "image_url": { "$in": &dead_images } })
+                .await?
+                .try_collect()
+                .await?;
+            for article in articles {
+                let Ok(id) = article.get_object_id("_id") else {
+                    continue;
+                };
+                let fallback = placeholder::placeholder_url(
+                    &self.public_api_url,
+                    article.get_str("title").unwrap_or_default(),
+                    article.get_str("source").unwrap_or_default(),
+                );
+                portfolio
+                    .update_one(
+                        doc! { "_id": id },
+                        doc! {
+                            "$set": { "image_url": fallback, "dead_image": true },
+                            "$unset": {
+                                "image_hash": "",
+                                "image_width": "",
+                                "image_height": "",
+                                "dominant_color": "",
+                                "blurhash": "",
+                            },
+                        },
+                    )
+                    .await?;
+                report.dead_images += 1;
+            }
+        }
+
+        Ok(report)
+    }
+
+    /// Runs the checker every `interval`, for the lifetime of the server
+    pub async fn run_periodically(self, interval: Duration) {
+        let mut ticker = tokio::time::interval(interval);
+        // The first tick completes immediately, skip it to not slow down startup
+        ticker.tick().await;
+        loop {
+            ticker.tick().await;
+            match self.run().await {
+                Ok(report) => tracing::info!(
+                    "Link check: {} URL(s) checked, {} dead link(s), {} dead image(s)",
+                    report.checked,
+                    report.dead_links,
+                    report.dead_images
+                ),
+                Err(e) => tracing::error!("Error checking links: {}", e),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify() {
+        assert_eq!(classify(Some(200)), CheckOutcome::Alive);
+        assert_eq!(classify(Some(301)), CheckOutcome::Alive);
+        assert_eq!(classify(Some(404)), CheckOutcome::Broken);
+        assert_eq!(classify(Some(410)), CheckOutcome::Broken);
+        assert_eq!(classify(Some(503)), CheckOutcome::Broken);
+        assert_eq!(classify(None), CheckOutcome::Broken);
+        assert_eq!(classify(Some(403)), CheckOutcome::Inconclusive);
+        assert_eq!(classify(Some(429)), CheckOutcome::Inconclusive);
+    }
+}
@@ -0,0 +1,198 @@
+//! Markdown rendering shared by the case studies and the blog
+//!
+//! Markdown is rendered to HTML, fenced code blocks are highlighted with
+//! syntect and the result is sanitized with ammonia before being stored.
+
+use once_cell::sync::Lazy;
+use pulldown_cmark::{CodeBlockKind, CowStr, Event, Options, Parser, Tag, TagEnd};
+use syntect::highlighting::ThemeSet;
+use syntect::html::{ClassStyle, ClassedHTMLGenerator};
+use syntect::parsing::SyntaxSet;
+use syntect::util::LinesWithEndings;
+
+/// Prefix of the CSS classes emitted for highlighted code
+const CLASS_STYLE: ClassStyle = ClassStyle::SpacedPrefixed { prefix: "hl-" };
+/// Theme of the stylesheet served with the highlighted code
+const THEME: &str = "InspiredGitHub";
+/// Average reading speed used for the reading time
+const WORDS_PER_MINUTE: usize = 200;
+
+static SYNTAXES: Lazy<SyntaxSet> = Lazy::new(SyntaxSet::load_defaults_newlines);
+static HIGHLIGHT_CSS: Lazy<String> = Lazy::new(|| {
+    let themes = ThemeSet::load_defaults();
+    themes
+        .themes
+        .get(THEME)
+        .and_then(|theme| syntect::html::css_for_theme_with_class_style(theme, CLASS_STYLE).ok())
+        .unwrap_or_default()
+});
+
+fn options() -> Options {
+    Options::ENABLE_TABLES
+        | Options::ENABLE_STRIKETHROUGH
+        | Options::ENABLE_FOOTNOTES
+        | Options::ENABLE_TASKLISTS
+}
+
+/// Highlights a code block, `None` when the language is unknown
+fn highlight(language: &str, code: &str) -> Option<String> {
+    let syntax = SYNTAXES.find_syntax_by_token(language)?;
+    let mut generator = ClassedHTMLGenerator::new_with_class_style(syntax, &SYNTAXES, CLASS_STYLE);
+    for line in LinesWithEndings::from(code) {
+        generator
+            .parse_html_for_line_which_includes_newline(line)
+            .ok()?;
+    }
+    Some(format!(
+        "<pre class=\"hl-code\"><code class=\"language-{language}\">{}</code></pre>\n",
+        generator.finalize()
+    ))
+}
+
+/// Replaces fenced code blocks of known languages by highlighted HTML
+fn highlight_code_blocks<'a>(events: impl Iterator<Item = Event<'a>>) -> Vec<Event<'a>> {
+    let mut output = Vec::new();
+    let mut block: Option<(String, Vec<Event<'a>>, String)> = None;
+
+    for event in events {
+        match (&mut block, event) {
+            (None, Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(info)))) => {
+                let language = info
+                    .split([',', ' '])
+                    .next()
+                    .unwrap_or_default()
+                    .to_string();
+                let start = Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(info)));
+                if language.is_empty() {
+                    output.push(start);
+                } else {
+                    block = Some((language, vec![start], String::new()));
+                }
+            }
+            (Some((_, raw, code)), Event::Text(text)) => {
+                code.push_str(&text);
+                raw.push(Event::Text(text));
+            }
+            (Some(_), Event::End(TagEnd::CodeBlock)) => {
+                if let Some((language, mut raw, code)) = block.take() {
+                    match highlight(&language, &code) {
+                        Some(html) => output.push(Event::Html(CowStr::from(html))),
+                        None => {
+                            raw.push(Event::End(TagEnd::CodeBlock));
+                            output.append(&mut raw);
+                        }
+                    }
+                }
+            }
+            (_, event) => output.push(event),
+        }
+    }
+    output
+}
+
+/// Renders Markdown to HTML safe to inject in the page
+///
+/// Tables, strikethrough, footnotes and task lists are enabled and fenced code
+/// blocks are highlighted (see [`highlight_css`]). The output is sanitized:
+/// scripts, event handlers and `javascript:` URLs are removed and links get
+/// `rel="noopener noreferrer"`.
+#[must_use]
+pub fn render(markdown: &str) -> String {
+    let events = highlight_code_blocks(Parser::new_ext(markdown, options()));
+    let mut html = String::new();
+    pulldown_cmark::html::push_html(&mut html, events.into_iter());
+
+    ammonia::Builder::default()
+        .add_tag_attributes("code", &["class"])
+        .add_tag_attributes("pre", &["class"])
+        .add_tag_attributes("span", &["class"])
+        .clean(&html)
+        .to_string()
+}
+
+/// Stylesheet of the classes emitted for highlighted code
+#[must_use]
+pub fn highlight_css() -> &'static str {
+    &HIGHLIGHT_CSS
+}
+
+/// Plain text of the first paragraph, truncated to `max_chars`
+#[must_use]
+pub fn first_paragraph(markdown: &str, max_chars: usize) -> String {
+    let mut text = String::new();
+    let mut in_paragraph = false;
+    for event in Parser::new_ext(markdown, options()) {
+        match event {
+            Event::Start(Tag::Paragraph) => in_paragraph = true,
+            Event::End(TagEnd::Paragraph) if !text.trim().is_empty() => break,
+            Event::End(TagEnd::Paragraph) => in_paragraph = false,
+            Event::Text(value) | Event::Code(value) if in_paragraph => text.push_str(&value),
+            Event::SoftBreak | Event::HardBreak if in_paragraph => text.push(' '),
+            _ => {}
+        }
+    }
+
+    let text = text.trim();
+    if text.chars().count() > max_chars {
+        let truncated: String = text.chars().take(max_chars.saturating_sub(1)).collect();
+        format!("{}…", truncated.trim_end())
+    } else {
+        text.to_string()
+    }
+}
+
+/// Estimated reading time in minutes, at least one
+#[must_use]
+pub fn reading_time_minutes(markdown: &str) -> u32 {
+    let words = markdown.split_whitespace().count();
+    u32::try_from(words.div_ceil(WORDS_PER_MINUTE).max(1)).unwrap_or(u32::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_strips_unsafe_html() {
+        let html = render(
+            "# Titre\n\n<script>alert(1)</script>\n\n[lien](javascript:alert(1)) \
+             <img src=\"x\" onerror=\"alert(1)\">",
+        );
+
+        assert!(html.contains("<h1>Titre</h1>"));
+        assert!(!html.contains("<script"));
+        assert!(!html.contains("javascript:"));
+        assert!(!html.contains("onerror"));
+    }
+
+    #[test]
+    fn test_render_tables_and_links() {
+        let html = render("| a | b |\n|---|---|\n| 1 | 2 |\n\n[site](https://example.com)");
+
+        assert!(html.contains("<table>"));
+        assert!(html.contains(r#"rel="noopener noreferrer""#));
+    }
+
+    #[test]
+    fn test_render_highlights_known_languages() {
+        let html = render("```rust\nfn main() {}\n```\n\n```nope\n<b>x</b>\n```");
+
+        assert!(html.contains(r#"<code class="language-rust">"#));
+        assert!(html.contains(r#"<span class="hl-"#));
+        assert!(html.contains("&lt;b&gt;x&lt;/b&gt;"));
+        assert!(!highlight_css().is_empty());
+    }
+
+    #[test]
+    fn test_first_paragraph_and_reading_time() {
+        let markdown = "# Titre\n\nUn **premier** paragraphe\nsur deux lignes.\n\nLa suite.";
+
+        assert_eq!(
+            first_paragraph(markdown, 200),
+            "Un premier paragraphe sur deux lignes."
+        );
+        assert_eq!(first_paragraph(markdown, 10), "Un premie…");
+        assert_eq!(reading_time_minutes(markdown), 1);
+        assert_eq!(reading_time_minutes(&"mot ".repeat(450)), 3);
+    }
+}
@@ -1,5 +1,28 @@
+pub mod analytics;
+pub mod bayes;
+pub mod case_studies;
 pub mod contact;
+pub mod contact_throttle;
 pub mod db;
+pub mod email_domains;
 pub mod email_queue;
 pub mod email_templates;
+pub mod email_transports;
+pub mod feed_filter;
+pub mod form_token;
+pub mod i18n;
+pub mod image_meta;
+pub mod image_proxy;
+pub mod link_checker;
+pub mod markdown;
+pub mod opengraph;
+pub mod placeholder;
+pub mod posts;
+pub mod projects;
+pub mod proof_of_work;
+pub mod ranking;
+pub mod related;
 pub mod rss;
+pub mod safe_http;
+pub mod sources;
+pub mod spam;
@@ -0,0 +1,266 @@
+use anyhow::Result;
+use futures_util::StreamExt;
+use mongodb::bson::{doc, Document};
+use mongodb::Database;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::Duration;
+use url::Url;
+
+use crate::services::safe_http;
+
+/// Maximum number of bytes read from an article page
+const MAX_PAGE_BYTES: usize = 512 * 1024;
+/// Timeout of a single page fetch
+const FETCH_TIMEOUT: Duration = Duration::from_secs(5);
+/// Number of pages fetched concurrently
+const CONCURRENT_FETCHES: usize = 4;
+
+static META_TAG_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?is)<meta\b[^>]*>").unwrap());
+static LINK_TAG_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?is)<link\b[^>]*>").unwrap());
+static ATTR_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"(?is)([a-z:_-]+)\s*=\s*(?:"([^"]*)"|'([^']*)'|([^\s"'>]+))"#).unwrap()
+});
+
+/// Metadata read from an article page
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PageMetadata {
+    /// `og:image`, or `twitter:image` as fallback
+    pub image: Option<String>,
+    /// `og:description`
+    pub description: Option<String>,
+    /// `<link rel="icon">`
+    pub icon: Option<String>,
+}
+
+/// Parses the attributes of a single HTML tag, with lowercase names
+fn tag_attributes(tag: &str) -> HashMap<String, String> {
+    ATTR_RE
+        .captures_iter(tag)
+        .filter_map(|cap| {
+            let name = cap.get(1)?.as_str().to_lowercase();
+            let value = cap.get(2).or_else(|| cap.get(3)).or_else(|| cap.get(4))?;
+            Some((name, decode_entities(value.as_str().trim())))
+        })
+        .collect()
+}
+
+/// Decodes the HTML entities commonly found in attribute values
+fn decode_entities(value: &str) -> String {
+    value
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&#x27;", "'")
+        .replace("&apos;", "'")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&amp;", "&")
+}
+
+/// Resolves a possibly relative URL, keeping only http(s) results
+fn resolve(base: &Url, value: &str) -> Option<String> {
+    let resolved = base.join(value).ok()?;
+    matches!(resolved.scheme(), "http" | "https").then(|| resolved.to_string())
+}
+
+/// Extracts OpenGraph / Twitter card metadata and the favicon from a page
+#[must_use]
+pub fn parse_metadata(html: &str, page_url: &str) -> PageMetadata {
+    let Ok(base) = Url::parse(page_url) else {
+        return PageMetadata::default();
+    };
+
+    let mut og_image = None;
+    let mut twitter_image = None;
+    let mut description = None;
+    for tag in META_TAG_RE.find_iter(html) {
+        let attrs = tag_attributes(tag.as_str());
+        let Some(content) = attrs.get("content").filter(|c| !c.is_empty()) else {
+            continue;
+        };
+        let key = attrs
+            .get("property")
+            .or_else(|| attrs.get("name"))
+            .map(|k| k.to_lowercase());
+
+        match key.as_deref() {
+            Some("og:image" | "og:image:url" | "og:image:secure_url") if og_image.is_none() => {
+                og_image = resolve(&base, content);
+            }
+            Some("twitter:image" | "twitter:image:src") if twitter_image.is_none() => {
+                twitter_image = resolve(&base, content);
+            }
+            Some("og:description") if description.is_none() => {
+                description = Some(content.clone());
+            }
+            _ => {}
+        }
+    }
+
+    let icon = LINK_TAG_RE.find_iter(html).find_map(|tag| {
+        let attrs = tag_attributes(tag.as_str());
+        let rel = attrs.get("rel")?.to_lowercase();
+        rel.split_whitespace()
+            .any(|r| r == "icon")
+            .then(|| attrs.get("href").and_then(|href| resolve(&base, href)))
+            .flatten()
+    });
+
+    PageMetadata {
+        image: og_image.or(twitter_image),
+        description,
+        icon,
+    }
+}
+
+/// Fetches article pages to fill missing images and descriptions
+///
+/// Results, including failures, are cached in the `og_cache` collection so that
+/// each URL is scraped at most once.
+pub struct OpenGraphScraper {
+    db: Database,
+    client: reqwest::Client,
+}
+
+impl OpenGraphScraper {
+    #[must_use]
+    pub fn new(db: Database) -> Self {
+        Self {
+            db,
+            client: safe_http::guarded_client(FETCH_TIMEOUT),
+        }
+    }
+
+    /// Downloads the beginning of an HTML page, up to [`MAX_PAGE_BYTES`]
+    async fn fetch_page(&self, url: &str) -> Result<String> {
+        safe_http::check_url(&Url::parse(url)?)?;
+        let mut response = self
+            .client
+            .get(url)
+            .header("User-Agent", "Mozilla/5.0 (compatible; RSSBot/1.0)")
+            .header("Accept", "text/html")
+            .send()
+            .await?
+            .error_for_status()?;
+
+        let is_html = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|v| v.contains("html"));
+        if !is_html {
+            anyhow::bail!("Not an HTML page");
+        }
+
+        let mut body = Vec::new();
+        while let Some(chunk) = response.chunk().await? {
+            let remaining = MAX_PAGE_BYTES - body.len();
+            body.extend_from_slice(&chunk[..chunk.len().min(remaining)]);
+            if body.len() >= MAX_PAGE_BYTES {
+                break;
+            }
+        }
+
+        Ok(String::from_utf8_lossy(&body).into_owned())
+    }
+
+    /// Returns the metadata of a page, scraping it only if it is not cached yet
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the cache cannot be read or written. Fetch failures are
+    /// cached as empty metadata.
+    pub async fn lookup(&self, url: &str) -> Result<PageMetadata> {
+        let cache = self.db.collection::<Document>("og_cache");
+        if let Some(cached) = cache.find_one(doc! { "url": url }).await? {
+            return Ok(mongodb::bson::from_document(cached).unwrap_or_default());
+        }
+
+        let metadata = match self.fetch_page(url).await {
+            Ok(html) => parse_metadata(&html, url),
+            Err(e) => {
+                tracing::warn!("Error scraping {}: {}", url, e);
+                PageMetadata::default()
+            }
+        };
+
+        let mut entry = mongodb::bson::to_document(&metadata)?;
+        entry.insert("fetched_at", mongodb::bson::DateTime::now());
+        cache
+            .update_one(doc! { "url": url }, doc! { "$set": entry })
+            .upsert(true)
+            .await?;
+
+        Ok(metadata)
+    }
+
+    /// Looks up several pages concurrently, skipping the ones that fail
+    pub async fn lookup_all(&self, urls: Vec<String>) -> HashMap<String, PageMetadata> {
+        futures_util::stream::iter(urls)
+            .map(|url| async move {
+                match self.lookup(&url).await {
+                    Ok(metadata) => Some((url, metadata)),
+                    Err(e) => {
+                        tracing::error!("Error caching page metadata for {}: {}", url, e);
+                        None
+                    }
+                }
+            })
+            .buffer_unordered(CONCURRENT_FETCHES)
+            .filter_map(|result| async move { result })
+            .collect()
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_open_graph_tags() {
+        let html = r#"<html><head>
+            <meta property="og:description" content="A &amp; B">
+            <meta content="/img/cover.png" property="og:image" />
+            <link rel="shortcut icon" href="/favicon.ico">
+        </head></html>"#;
+
+        let metadata = parse_metadata(html, "https://blog.example.com/posts/1");
+
+        assert_eq!(
+            metadata.image.as_deref(),
+            Some("https://blog.example.com/img/cover.png")
+        );
+        assert_eq!(metadata.description.as_deref(), Some("A & B"));
+        assert_eq!(
+            metadata.icon.as_deref(),
+            Some("https://blog.example.com/favicon.ico")
+        );
+    }
+
+    #[test]
+    fn test_twitter_image_fallback() {
+        let html = r#"<meta name='twitter:image' content='https://cdn.example.com/t.jpg'>"#;
+
+        let metadata = parse_metadata(html, "https://example.com/");
+
+        assert_eq!(
+            metadata.image.as_deref(),
+            Some("https://cdn.example.com/t.jpg")
+        );
+        assert!(metadata.icon.is_none());
+    }
+
+    #[test]
+    fn test_rejects_non_http_urls() {
+        let html = r#"<meta property="og:image" content="javascript:alert(1)">
+            <link rel="icon" href="data:image/png;base64,AAAA">"#;
+
+        assert_eq!(
+            parse_metadata(html, "https://example.com/"),
+            PageMetadata::default()
+        );
+    }
+}
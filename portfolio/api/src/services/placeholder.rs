@@ -0,0 +1,242 @@
+use anyhow::Result;
+use image::{ImageFormat, Rgb, RgbImage};
+use sha2::{Digest, Sha256};
+use std::io::Cursor;
+
+/// Default placeholder size, matching the article card ratio
+pub const DEFAULT_WIDTH: u32 = 600;
+pub const DEFAULT_HEIGHT: u32 = 400;
+
+/// Maximum number of title characters rendered or put in URLs
+const MAX_TITLE_CHARS: usize = 120;
+const MAX_LINES: usize = 3;
+
+/// Builds the URL of the self-hosted placeholder of an article
+#[must_use]
+pub fn placeholder_url(api_base_url: &str, title: &str, source: &str) -> String {
+    let title: String = title.chars().take(MAX_TITLE_CHARS).collect();
+    format!(
+        "{}/api/placeholder?title={}&source={}",
+        api_base_url.trim_end_matches('/'),
+        urlencoding::encode(&title),
+        urlencoding::encode(source)
+    )
+}
+
+/// Colors derived from the title and source, so each article keeps the same look
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Palette {
+    start: [u8; 3],
+    end: [u8; 3],
+}
+
+impl Palette {
+    fn from_hash(title: &str, source: &str) -> Self {
+        let digest = Sha256::new()
+            .chain_update(title.as_bytes())
+            .chain_update([0])
+            .chain_update(source.as_bytes())
+            .finalize();
+        let hue = f64::from(u16::from_be_bytes([digest[0], digest[1]]) % 360);
+        let shift = 30.0 + f64::from(digest[2] % 90);
+
+        Self {
+            start: hsl_to_rgb(hue, 0.55, 0.38),
+            end: hsl_to_rgb((hue + shift) % 360.0, 0.60, 0.24),
+        }
+    }
+}
+
+fn hsl_to_rgb(hue: f64, saturation: f64, lightness: f64) -> [u8; 3] {
+    let chroma = (1.0 - (2.0 * lightness - 1.0).abs()) * saturation;
+    let h = hue / 60.0;
+    let x = chroma * (1.0 - (h % 2.0 - 1.0).abs());
+    let (r, g, b) = match h as u32 {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+    let m = lightness - chroma / 2.0;
+    let channel = |v: f64| ((v + m) * 255.0).round().clamp(0.0, 255.0) as u8;
+    [channel(r), channel(g), channel(b)]
+}
+
+fn hex_color(rgb: [u8; 3]) -> String {
+    format!("#{}", hex::encode(rgb))
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+/// Splits a title into at most [`MAX_LINES`] lines of `max_chars`, with an ellipsis
+fn wrap_title(title: &str, max_chars: usize) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    let mut current = String::new();
+
+    for word in title.split_whitespace() {
+        let word: String = word.chars().take(max_chars).collect();
+        if !current.is_empty() && current.chars().count() + 1 + word.chars().count() > max_chars {
+            lines.push(std::mem::take(&mut current));
+            if lines.len() == MAX_LINES {
+                break;
+            }
+        }
+        if !current.is_empty() {
+            current.push(' ');
+        }
+        current.push_str(&word);
+    }
+
+    if lines.len() == MAX_LINES {
+        if let Some(last) = lines.last_mut() {
+            last.push('…');
+        }
+    } else if !current.is_empty() {
+        lines.push(current);
+    }
+    lines
+}
+
+/// Readable label of a source: the host of a feed URL, or the text itself
+fn source_label(source: &str) -> String {
+    url::Url::parse(source)
+        .ok()
+        .and_then(|url| {
+            url.host_str()
+                .map(|host| host.trim_start_matches("www.").to_string())
+        })
+        .unwrap_or_else(|| source.to_string())
+}
+
+/// Renders a deterministic SVG placeholder with the title and source
+#[must_use]
+pub fn render_svg(title: &str, source: &str, width: u32, height: u32) -> String {
+    let title: String = title.chars().take(MAX_TITLE_CHARS).collect();
+    let palette = Palette::from_hash(&title, source);
+    let font_size = (width / 16).max(8);
+    let max_chars = ((f64::from(width) * 0.85) / (f64::from(font_size) * 0.55)) as usize;
+    let lines = wrap_title(&title, max_chars.max(4));
+
+    let line_height = f64::from(font_size) * 1.25;
+    let first_line_y = f64::from(height) / 2.0 - line_height * (lines.len() as f64 - 1.0) / 2.0;
+    let text: String = lines
+        .iter()
+        .enumerate()
+        .map(|(i, line)| {
+            format!(
+                r#"<tspan x="50%" y="{:.1}">{}</tspan>"#,
+                first_line_y + line_height * i as f64,
+                escape_xml(line)
+            )
+        })
+        .collect();
+
+    format!(
+        concat!(
+            r##"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}" role="img" aria-label="{label}">"##,
+            r##"<defs><linearGradient id="g" x1="0" y1="0" x2="1" y2="1">"##,
+            r##"<stop offset="0" stop-color="{start}"/><stop offset="1" stop-color="{end}"/>"##,
+            r##"</linearGradient></defs>"##,
+            r##"<rect width="100%" height="100%" fill="url(#g)"/>"##,
+            r##"<text fill="#ffffff" font-family="system-ui, sans-serif" font-size="{size}" font-weight="600" text-anchor="middle" dominant-baseline="middle">{text}</text>"##,
+            r##"<text x="50%" y="{source_y}" fill="#ffffff" fill-opacity="0.7" font-family="system-ui, sans-serif" font-size="{source_size}" text-anchor="middle">{source}</text>"##,
+            r##"</svg>"##
+        ),
+        w = width,
+        h = height,
+        label = escape_xml(&title),
+        start = hex_color(palette.start),
+        end = hex_color(palette.end),
+        size = font_size,
+        text = text,
+        source_y = height.saturating_sub(font_size),
+        source_size = (font_size * 2 / 3).max(6),
+        source = escape_xml(&source_label(source)),
+    )
+}
+
+/// Renders a PNG placeholder with the same gradient as the SVG, without text
+///
+/// # Errors
+///
+/// Returns error if PNG encoding fails.
+pub fn render_png(title: &str, source: &str, width: u32, height: u32) -> Result<Vec<u8>> {
+    let title: String = title.chars().take(MAX_TITLE_CHARS).collect();
+    let palette = Palette::from_hash(&title, source);
+    let span = f64::from((width + height).saturating_sub(2).max(1));
+
+    let image = RgbImage::from_fn(width, height, |x, y| {
+        let t = f64::from(x + y) / span;
+        let mix = |a: u8, b: u8| (f64::from(a) + (f64::from(b) - f64::from(a)) * t).round() as u8;
+        Rgb([
+            mix(palette.start[0], palette.end[0]),
+            mix(palette.start[1], palette.end[1]),
+            mix(palette.start[2], palette.end[2]),
+        ])
+    });
+
+    let mut bytes = Vec::new();
+    image.write_to(&mut Cursor::new(&mut bytes), ImageFormat::Png)?;
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_svg_is_deterministic_and_escaped() {
+        let svg = render_svg("Tom & <Jerry>", "https://www.example.com/feed", 600, 400);
+
+        assert_eq!(
+            svg,
+            render_svg("Tom & <Jerry>", "https://www.example.com/feed", 600, 400)
+        );
+        assert!(svg.contains("Tom &amp; &lt;Jerry&gt;"));
+        assert!(svg.contains(">example.com</text>"));
+        assert!(!svg.contains("<Jerry>"));
+    }
+
+    #[test]
+    fn test_colors_depend_on_title_and_source() {
+        assert_ne!(
+            Palette::from_hash("Rust", "a"),
+            Palette::from_hash("Rust", "b")
+        );
+        assert_eq!(
+            Palette::from_hash("Rust", "a"),
+            Palette::from_hash("Rust", "a")
+        );
+    }
+
+    #[test]
+    fn test_wrap_title_limits_lines() {
+        let lines = wrap_title("one two three four five six seven eight", 9);
+
+        assert_eq!(lines, vec!["one two", "three", "four five…"]);
+    }
+
+    #[test]
+    fn test_png_has_requested_size() {
+        let png = render_png("Title", "source", 32, 16).unwrap();
+        let decoded = image::load_from_memory(&png).unwrap();
+
+        assert_eq!((decoded.width(), decoded.height()), (32, 16));
+    }
+
+    #[test]
+    fn test_placeholder_url() {
+        assert_eq!(
+            placeholder_url("https://api.example.com/", "C'est l'été", "feed"),
+            "https://api.example.com/api/placeholder?title=C%27est%20l%27%C3%A9t%C3%A9&source=feed"
+        );
+    }
+}
@@ -0,0 +1,447 @@
+use anyhow::Result;
+use chrono::{DateTime, NaiveDate, Utc};
+use futures_util::TryStreamExt;
+use mongodb::bson::{doc, Bson, Document};
+use mongodb::options::FindOptions;
+use mongodb::Database;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use validator::{Validate, ValidationError, ValidationErrors};
+
+use crate::models::post::{FrontMatter, Post, PostStatus, TagCount};
+use crate::services::case_studies::normalize_tags;
+use crate::services::markdown;
+
+/// Collection storing the posts
+const COLLECTION: &str = "posts";
+/// Maximum length of a summary derived from the first paragraph
+const SUMMARY_CHARS: usize = 200;
+/// Maximum length of a slug derived from a title
+const MAX_SLUG_CHARS: usize = 100;
+/// Maximum size of a post source
+const MAX_SOURCE_BYTES: usize = 200_000;
+/// Slugs shadowed by other `/api/posts/...` routes
+const RESERVED_SLUGS: [&str; 1] = ["tags"];
+
+static FENCE_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?m)^---[ \t]*\r?$").unwrap());
+
+/// Builds the validation error of a single field
+fn invalid(field: &'static str, code: &'static str, message: String) -> anyhow::Error {
+    let mut errors = ValidationErrors::new();
+    errors.add(
+        field,
+        ValidationError::new(code).with_message(message.into()),
+    );
+    errors.into()
+}
+
+/// Splits a post source into its front matter and Markdown body
+///
+/// # Errors
+///
+/// Returns a `ValidationErrors` error if the front matter is missing or is
+/// not valid YAML.
+pub fn parse_source(source: &str) -> Result<(FrontMatter, &str)> {
+    let source = source.trim_start_matches('\u{feff}');
+    let rest = source
+        .strip_prefix("---\n")
+        .or_else(|| source.strip_prefix("---\r\n"))
+        .ok_or_else(|| {
+            invalid(
+                "front_matter",
+                "front_matter_manquant",
+                "Le post doit commencer par un front matter YAML (---)".to_string(),
+            )
+        })?;
+    let fence = FENCE_RE.find(rest).ok_or_else(|| {
+        invalid(
+            "front_matter",
+            "front_matter_non_termine",
+            "Le front matter doit se terminer par ---".to_string(),
+        )
+    })?;
+
+    let front_matter: FrontMatter = serde_norway::from_str(&rest[..fence.start()])
+        .map_err(|e| invalid("front_matter", "front_matter_invalide", e.to_string()))?;
+    let body = rest[fence.end()..].trim_start_matches(['\r', '\n']);
+    Ok((front_matter, body))
+}
+
+/// Parses a front matter date: RFC 3339, or a day at midnight UTC
+fn parse_date(date: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(date.trim())
+        .map(|date| date.with_timezone(&Utc))
+        .ok()
+        .or_else(|| {
+            NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d")
+                .ok()
+                .and_then(|day| day.and_hms_opt(0, 0, 0))
+                .map(|date| date.and_utc())
+        })
+}
+
+fn to_bson(date: DateTime<Utc>) -> mongodb::bson::DateTime {
+    mongodb::bson::DateTime::from_millis(date.timestamp_millis())
+}
+
+fn to_chrono(date: mongodb::bson::DateTime) -> Option<DateTime<Utc>> {
+    DateTime::from_timestamp_millis(date.timestamp_millis())
+}
+
+/// Converts a stored `posts` document, with the rendered content on demand
+fn to_post(doc: &Document, with_content: bool) -> Post {
+    let published_at = doc
+        .get_datetime("publish_at")
+        .ok()
+        .copied()
+        .and_then(to_chrono);
+    let status = if doc.get_bool("draft").unwrap_or(false) {
+        PostStatus::Draft
+    } else if published_at.is_some_and(|date| date > Utc::now()) {
+        PostStatus::Scheduled
+    } else {
+        PostStatus::Published
+    };
+
+    Post {
+        slug: doc.get_str("slug").unwrap_or_default().to_string(),
+        title: doc.get_str("title").unwrap_or_default().to_string(),
+        summary: doc.get_str("summary").unwrap_or_default().to_string(),
+        tags: doc
+            .get_array("tags")
+            .map(|tags| {
+                tags.iter()
+                    .filter_map(|tag| tag.as_str().map(str::to_string))
+                    .collect()
+            })
+            .unwrap_or_default(),
+        image_url: doc.get_str("image_url").ok().map(str::to_string),
+        content_html: with_content
+            .then(|| doc.get_str("content_html").unwrap_or_default().to_string()),
+        reading_time_minutes: doc
+            .get_i64("reading_time_minutes")
+            .ok()
+            .and_then(|minutes| u32::try_from(minutes).ok())
+            .unwrap_or(1),
+        status,
+        published_at,
+        updated_at: doc
+            .get_datetime("updated_at")
+            .ok()
+            .copied()
+            .and_then(to_chrono)
+            .unwrap_or_default(),
+    }
+}
+
+/// Filter of the posts visible to the public: not drafts, publication date reached
+fn published_filter() -> Document {
+    doc! { "draft": false, "publish_at": { "$lte": mongodb::bson::DateTime::now() } }
+}
+
+/// Blog posts written in Markdown with a YAML front matter
+///
+/// The whole source is stored so it can be edited again; the HTML (with
+/// highlighted code blocks), summary and reading time are computed when the
+/// post is saved. Scheduled posts become visible once their date is reached,
+/// without any background job. Published posts are also ingested by the RSS
+/// sync (see [`crate::services::sources::PostsSource`]).
+pub struct PostService {
+    db: Database,
+}
+
+impl PostService {
+    #[must_use]
+    pub const fn new(db: Database) -> Self {
+        Self { db }
+    }
+
+    fn collection(&self) -> mongodb::Collection<Document> {
+        self.db.collection::<Document>(COLLECTION)
+    }
+
+    async fn find(
+        &self,
+        filter: Document,
+        options: FindOptions,
+        with_content: bool,
+    ) -> Result<Vec<Post>> {
+        let docs: Vec<Document> = self
+            .collection()
+            .find(filter)
+            .with_options(options)
+            .await?
+            .try_collect()
+            .await?;
+        Ok(docs.iter().map(|doc| to_post(doc, with_content)).collect())
+    }
+
+    /// Lists a page of published posts, newest first, optionally for a tag
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the database query fails.
+    pub async fn list(&self, tag: Option<&str>, page: u32, limit: u32) -> Result<Vec<Post>> {
+        let mut filter = published_filter();
+        if let Some(tag) = tag.map(|tag| tag.trim().to_lowercase()) {
+            filter.insert("tags", tag);
+        }
+        let options = FindOptions::builder()
+            .sort(doc! { "publish_at": -1 })
+            .skip(u64::from(page.max(1) - 1) * u64::from(limit))
+            .limit(i64::from(limit))
+            .projection(doc! { "source": 0, "content_html": 0 })
+            .build();
+
+        self.find(filter, options, false).await
+    }
+
+    /// Lists every published post, newest first
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the database query fails.
+    pub async fn published(&self) -> Result<Vec<Post>> {
+        let options = FindOptions::builder()
+            .sort(doc! { "publish_at": -1 })
+            .projection(doc! { "source": 0, "content_html": 0 })
+            .build();
+
+        self.find(published_filter(), options, false).await
+    }
+
+    /// Counts the published posts of each tag, most used first
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the aggregation fails.
+    pub async fn tags(&self) -> Result<Vec<TagCount>> {
+        let pipeline = vec![
+            doc! { "$match": published_filter() },
+            doc! { "$unwind": "$tags" },
+            doc! { "$group": { "_id": "$tags", "count": { "$sum": 1 } } },
+            doc! { "$sort": { "count": -1, "_id": 1 } },
+        ];
+        let docs: Vec<Document> = self
+            .collection()
+            .aggregate(pipeline)
+            .await?
+            .try_collect()
+            .await?;
+
+        Ok(docs
+            .iter()
+            .filter_map(|doc| {
+                Some(TagCount {
+                    tag: doc.get_str("_id").ok()?.to_string(),
+                    count: doc
+                        .get_i32("count")
+                        .ok()
+                        .and_then(|count| u32::try_from(count).ok())?,
+                })
+            })
+            .collect())
+    }
+
+    /// Retrieves a published post with its rendered content
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the database query fails.
+    pub async fn get(&self, slug: &str) -> Result<Option<Post>> {
+        let mut filter = published_filter();
+        filter.insert("slug", slug);
+        let doc = self.collection().find_one(filter).await?;
+        Ok(doc.map(|doc| to_post(&doc, true)))
+    }
+
+    /// Builds the stored fields of a post from its source
+    ///
+    /// `previous` is the stored post on update: its publication date is kept
+    /// when the front matter does not set one.
+    fn fields(source: &str, previous: Option<&Document>) -> Result<Document> {
+        if source.len() > MAX_SOURCE_BYTES {
+            return Err(invalid(
+                "source",
+                "source_trop_longue",
+                "Le post est trop long".to_string(),
+            ));
+        }
+        let (front_matter, body) = parse_source(source)?;
+        front_matter.validate()?;
+
+        let slug = match front_matter.slug.as_deref().filter(|slug| !slug.is_empty()) {
+            Some(slug) => slug.to_string(),
+            None => slug::slugify(&front_matter.title)
+                .chars()
+                .take(MAX_SLUG_CHARS)
+                .collect::<String>()
+                .trim_end_matches('-')
+                .to_string(),
+        };
+        if slug.is_empty() || RESERVED_SLUGS.contains(&slug.as_str()) {
+            return Err(invalid(
+                "slug",
+                "slug_invalide",
+                format!("Le slug \"{slug}\" n'est pas disponible"),
+            ));
+        }
+
+        let publish_at = match front_matter.date.as_deref() {
+            Some(date) => Some(to_bson(parse_date(date).ok_or_else(|| {
+                invalid(
+                    "date",
+                    "date_invalide",
+                    format!("Date de publication invalide : {date}"),
+                )
+            })?)),
+            None => previous
+                .and_then(|doc| doc.get_datetime("publish_at").ok().copied())
+                .or_else(|| (!front_matter.draft).then(mongodb::bson::DateTime::now)),
+        };
+        let summary = front_matter
+            .summary
+            .map(|summary| summary.trim().to_string())
+            .filter(|summary| !summary.is_empty())
+            .unwrap_or_else(|| markdown::first_paragraph(body, SUMMARY_CHARS));
+
+        Ok(doc! {
+            "slug": slug,
+            "source": source,
+            "title": front_matter.title.trim(),
+            "summary": summary,
+            "tags": normalize_tags(&front_matter.tags),
+            "image_url": front_matter.image.map_or(Bson::Null, Bson::String),
+            "draft": front_matter.draft,
+            "publish_at": publish_at.map_or(Bson::Null, Bson::DateTime),
+            "content_html": markdown::render(body),
+            "reading_time_minutes": i64::from(markdown::reading_time_minutes(body)),
+            "updated_at": mongodb::bson::DateTime::now(),
+        })
+    }
+
+    /// Creates a post from its Markdown source
+    ///
+    /// # Errors
+    ///
+    /// Returns a `ValidationErrors` error for an invalid source and a
+    /// duplicate key error (see [`crate::services::db::is_duplicate_key`]) if
+    /// the slug is already used.
+    pub async fn create(&self, source: &str) -> Result<Post> {
+        let mut fields = Self::fields(source, None)?;
+        fields.insert("created_at", mongodb::bson::DateTime::now());
+        self.collection().insert_one(&fields).await?;
+
+        Ok(to_post(&fields, true))
+    }
+
+    /// Replaces the source of a post, which may be renamed through its slug
+    ///
+    /// # Errors
+    ///
+    /// Same as [`Self::create`]. Returns `Ok(None)` when no post has this slug.
+    pub async fn update(&self, slug: &str, source: &str) -> Result<Option<Post>> {
+        let Some(previous) = self.collection().find_one(doc! { "slug": slug }).await? else {
+            return Ok(None);
+        };
+        let mut fields = Self::fields(source, Some(&previous))?;
+        if let Ok(created_at) = previous.get_datetime("created_at") {
+            fields.insert("created_at", *created_at);
+        }
+
+        let result = self
+            .collection()
+            .replace_one(doc! { "slug": slug }, &fields)
+            .await?;
+        Ok((result.matched_count > 0).then(|| to_post(&fields, true)))
+    }
+
+    /// Deletes a post
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the database operation fails. Returns `Ok(false)`
+    /// when no post has this slug.
+    pub async fn delete(&self, slug: &str) -> Result<bool> {
+        let result = self.collection().delete_one(doc! { "slug": slug }).await?;
+        Ok(result.deleted_count > 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = "---\ntitle: Bonjour Rust\ndate: 2024-05-01\ntags: [Rust, axum]\n---\n\nPremier paragraphe.\n\n```rust\nfn main() {}\n```\n";
+
+    #[test]
+    fn test_parse_source() {
+        let (front_matter, body) = parse_source(SOURCE).unwrap();
+
+        assert_eq!(front_matter.title, "Bonjour Rust");
+        assert_eq!(front_matter.tags, vec!["Rust", "axum"]);
+        assert!(!front_matter.draft);
+        assert!(body.starts_with("Premier paragraphe."));
+    }
+
+    #[test]
+    fn test_parse_source_requires_front_matter() {
+        for source in [
+            "# Pas de front matter",
+            "---\ntitle: x\n",
+            "---\n: [\n---\n",
+        ] {
+            let error = parse_source(source).unwrap_err();
+            assert!(error.downcast_ref::<ValidationErrors>().is_some());
+        }
+    }
+
+    #[test]
+    fn test_fields() {
+        let fields = PostService::fields(SOURCE, None).unwrap();
+
+        assert_eq!(fields.get_str("slug").unwrap(), "bonjour-rust");
+        assert_eq!(fields.get_str("summary").unwrap(), "Premier paragraphe.");
+        assert_eq!(
+            fields
+                .get_datetime("publish_at")
+                .unwrap()
+                .timestamp_millis(),
+            parse_date("2024-05-01T00:00:00Z")
+                .unwrap()
+                .timestamp_millis()
+        );
+        assert!(fields
+            .get_str("content_html")
+            .unwrap()
+            .contains("language-rust"));
+
+        let post = to_post(&fields, false);
+        assert_eq!(post.status, PostStatus::Published);
+        assert_eq!(post.tags, vec!["rust", "axum"]);
+        assert!(post.content_html.is_none());
+    }
+
+    #[test]
+    fn test_drafts_and_scheduled_posts() {
+        let draft =
+            PostService::fields("---\ntitle: Brouillon\ndraft: true\n---\nTexte", None).unwrap();
+        assert_eq!(draft.get("publish_at"), Some(&Bson::Null));
+        assert_eq!(to_post(&draft, false).status, PostStatus::Draft);
+
+        let scheduled =
+            PostService::fields("---\ntitle: Plus tard\ndate: 2999-01-01\n---\nTexte", None)
+                .unwrap();
+        assert_eq!(to_post(&scheduled, false).status, PostStatus::Scheduled);
+    }
+
+    #[test]
+    fn test_reserved_and_invalid_fields() {
+        assert!(PostService::fields("---\ntitle: Tags\n---\nTexte", None).is_err());
+        assert!(PostService::fields("---\ntitle: Date\ndate: demain\n---\nTexte", None).is_err());
+        assert!(
+            PostService::fields("---\ntitle: Image\nimage: javascript:x\n---\nTexte", None)
+                .is_err()
+        );
+    }
+}
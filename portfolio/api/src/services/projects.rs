@@ -0,0 +1,355 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use futures_util::TryStreamExt;
+use mongodb::bson::{doc, Bson, Document};
+use mongodb::Database;
+use serde::Deserialize;
+use std::time::Duration;
+
+use crate::config::Config;
+use crate::models::project::{Project, ProjectOverride};
+
+/// Collection storing the synced repositories and their overrides
+const COLLECTION: &str = "github_repos";
+/// Repositories requested per GitHub API page (the API maximum)
+const PER_PAGE: usize = 100;
+/// Upper bound on fetched pages, in case the API keeps returning full pages
+const MAX_PAGES: usize = 10;
+
+/// Repository as returned by the GitHub REST API
+#[derive(Debug, Clone, Deserialize)]
+pub struct Repository {
+    pub name: String,
+    pub description: Option<String>,
+    pub html_url: String,
+    pub homepage: Option<String>,
+    #[serde(default)]
+    pub stargazers_count: u32,
+    pub language: Option<String>,
+    #[serde(default)]
+    pub topics: Vec<String>,
+    pub pushed_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub fork: bool,
+    #[serde(default)]
+    pub archived: bool,
+    #[serde(default)]
+    pub private: bool,
+}
+
+/// Repositories listed by [`fetch_repositories`]
+#[derive(Debug, Clone)]
+pub struct RepositoryListing {
+    pub repositories: Vec<Repository>,
+    /// False when the listing stopped at the page cap with more pages left
+    pub complete: bool,
+}
+
+/// Fetches the public, non-fork, non-archived repositories of a user
+///
+/// # Errors
+///
+/// Returns error if a request fails or a page cannot be parsed.
+pub async fn fetch_repositories(
+    client: &reqwest::Client,
+    api_base_url: &str,
+    username: &str,
+    token: Option<&str>,
+) -> Result<RepositoryListing> {
+    let mut repositories = Vec::new();
+    let mut complete = false;
+
+    for page in 1..=MAX_PAGES {
+        let url = format!(
+            "{}/users/{}/repos",
+            api_base_url.trim_end_matches('/'),
+            urlencoding::encode(username)
+        );
+        let mut request = client
+            .get(&url)
+            .query(&[
+                ("type", "owner"),
+                ("sort", "pushed"),
+                ("per_page", &PER_PAGE.to_string()),
+                ("page", &page.to_string()),
+            ])
+            .header("User-Agent", "Mozilla/5.0 (compatible; RSSBot/1.0)")
+            .header("Accept", "application/vnd.github+json");
+        if let Some(token) = token {
+            request = request.bearer_auth(token);
+        }
+
+        let batch: Vec<Repository> = request.send().await?.error_for_status()?.json().await?;
+        let last_page = batch.len() < PER_PAGE;
+        repositories.extend(
+            batch
+                .into_iter()
+                .filter(|repo| !repo.fork && !repo.archived && !repo.private),
+        );
+        if last_page {
+            complete = true;
+            break;
+        }
+    }
+
+    Ok(RepositoryListing {
+        repositories,
+        complete,
+    })
+}
+
+/// Converts a stored `github_repos` document into the public representation
+fn to_project(doc: &Document) -> Project {
+    let description = doc
+        .get_str("blurb")
+        .ok()
+        .filter(|blurb| !blurb.is_empty())
+        .or_else(|| doc.get_str("description").ok())
+        .unwrap_or_default()
+        .to_string();
+
+    Project {
+        name: doc.get_str("name").unwrap_or_default().to_string(),
+        description,
+        url: doc.get_str("url").unwrap_or_default().to_string(),
+        homepage: doc
+            .get_str("homepage")
+            .ok()
+            .filter(|homepage| !homepage.is_empty())
+            .map(str::to_string),
+        stars: doc
+            .get_i64("stars")
+            .ok()
+            .and_then(|stars| u32::try_from(stars).ok())
+            .unwrap_or(0),
+        language: doc.get_str("language").ok().map(str::to_string),
+        topics: doc
+            .get_array("topics")
+            .map(|topics| {
+                topics
+                    .iter()
+                    .filter_map(|topic| topic.as_str().map(str::to_string))
+                    .collect()
+            })
+            .unwrap_or_default(),
+        pushed_at: doc
+            .get_datetime("pushed_at")
+            .ok()
+            .and_then(|date| DateTime::from_timestamp_millis(date.timestamp_millis()))
+            .unwrap_or_default(),
+        pinned: doc.get_bool("pinned").unwrap_or(false),
+    }
+}
+
+/// Projects showcase backed by the configured user's GitHub repositories
+///
+/// Repositories are upserted by name in the `github_repos` collection. Sync only
+/// writes the GitHub fields, so the manual overrides (`hidden`, `pinned`,
+/// `blurb`) stored on the same documents are kept. Repositories that are no
+/// longer listed are marked `stale` and hidden rather than removed, so their
+/// overrides come back with them.
+pub struct ProjectService {
+    db: Database,
+    config: Config,
+    client: reqwest::Client,
+}
+
+impl ProjectService {
+    #[must_use]
+    pub fn new(db: Database, config: Config) -> Self {
+        Self {
+            db,
+            config,
+            client: reqwest::Client::builder()
+                .timeout(Duration::from_secs(10))
+                .build()
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Pulls the repositories of `GITHUB_USERNAME` and stores them
+    ///
+    /// # Errors
+    ///
+    /// Returns error if no user is configured, the GitHub API request fails or
+    /// a database operation fails.
+    ///
+    /// Repositories missing from the listing are only marked stale when the
+    /// listing is complete and not empty, so that a truncated or empty answer
+    /// does not hide the whole showcase.
+    ///
+    /// # Returns
+    /// Number of repositories stored
+    pub async fn sync(&self) -> Result<usize> {
+        let Some(username) = &self.config.github_username else {
+            anyhow::bail!("GITHUB_USERNAME is not set");
+        };
+        let listing = fetch_repositories(
+            &self.client,
+            &self.config.github_api_url,
+            username,
+            self.config.github_token.as_deref(),
+        )
+        .await?;
+        let repositories = &listing.repositories;
+
+        let collection = self.db.collection::<Document>(COLLECTION);
+        let now = mongodb::bson::DateTime::now();
+        for repo in repositories {
+            let pushed_at = repo
+                .pushed_at
+                .map(|date| {
+                    Bson::DateTime(mongodb::bson::DateTime::from_millis(
+                        date.timestamp_millis(),
+                    ))
+                })
+                .unwrap_or(Bson::Null);
+            collection
+                .update_one(
+                    doc! { "name": &repo.name },
+                    doc! { "$set": {
+                        "description": repo.description.as_deref().unwrap_or_default(),
+                        "url": &repo.html_url,
+                        "homepage": repo.homepage.as_deref(),
+                        "stars": i64::from(repo.stargazers_count),
+                        "language": repo.language.as_deref(),
+                        "topics": &repo.topics,
+                        "pushed_at": pushed_at,
+                        "synced_at": now,
+                        "stale": false,
+                    }},
+                )
+                .upsert(true)
+                .await?;
+        }
+
+        if !listing.complete || repositories.is_empty() {
+            tracing::warn!(
+                "GitHub listing of {} is empty or truncated, missing projects are kept",
+                username
+            );
+        } else {
+            let names: Vec<&str> = repositories.iter().map(|repo| repo.name.as_str()).collect();
+            collection
+                .update_many(
+                    doc! { "name": { "$nin": &names } },
+                    doc! { "$set": { "stale": true } },
+                )
+                .await?;
+        }
+
+        Ok(repositories.len())
+    }
+
+    /// Retrieves the visible projects: pinned first, then by stars and last push
+    pub async fn get_projects(&self) -> Vec<Project> {
+        let collection = self.db.collection::<Document>(COLLECTION);
+        let options = mongodb::options::FindOptions::builder()
+            .sort(doc! { "pinned": -1, "stars": -1, "pushed_at": -1 })
+            .build();
+
+        match collection
+            .find(doc! { "hidden": { "$ne": true }, "stale": { "$ne": true } })
+            .with_options(options)
+            .await
+        {
+            Ok(cursor) => cursor
+                .try_collect::<Vec<Document>>()
+                .await
+                .map(|docs| docs.iter().map(to_project).collect())
+                .unwrap_or_else(|e| {
+                    tracing::error!("Error reading projects: {}", e);
+                    Vec::new()
+                }),
+            Err(e) => {
+                tracing::error!("Error querying projects: {}", e);
+                Vec::new()
+            }
+        }
+    }
+
+    /// Applies manual overrides to a project
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the database update fails. Returns `Ok(false)` when no
+    /// stored project has this name.
+    pub async fn update_override(&self, name: &str, update: &ProjectOverride) -> Result<bool> {
+        let mut set = Document::new();
+        let mut unset = Document::new();
+        if let Some(hidden) = update.hidden {
+            set.insert("hidden", hidden);
+        }
+        if let Some(pinned) = update.pinned {
+            set.insert("pinned", pinned);
+        }
+        match update.blurb.as_deref().map(str::trim) {
+            Some("") => {
+                unset.insert("blurb", "");
+            }
+            Some(blurb) => {
+                set.insert("blurb", blurb);
+            }
+            None => {}
+        }
+
+        let mut changes = Document::new();
+        if !set.is_empty() {
+            changes.insert("$set", set);
+        }
+        if !unset.is_empty() {
+            changes.insert("$unset", unset);
+        }
+
+        let collection = self.db.collection::<Document>(COLLECTION);
+        if changes.is_empty() {
+            return Ok(collection.find_one(doc! { "name": name }).await?.is_some());
+        }
+        let result = collection
+            .update_one(doc! { "name": name }, changes)
+            .await?;
+        Ok(result.matched_count > 0)
+    }
+
+    /// Syncs the repositories every `interval`, starting immediately
+    pub async fn run_periodically(self: std::sync::Arc<Self>, interval: Duration) {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            match self.sync().await {
+                Ok(count) => tracing::info!("Synced {} GitHub project(s)", count),
+                Err(e) => tracing::error!("Error syncing GitHub projects: {}", e),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_blurb_overrides_description() {
+        let project = to_project(&doc! {
+            "name": "folio",
+            "description": "From GitHub",
+            "blurb": "Hand-written",
+            "stars": 12_i64,
+            "homepage": "",
+            "topics": ["rust", "axum"],
+        });
+
+        assert_eq!(project.description, "Hand-written");
+        assert_eq!(project.stars, 12);
+        assert!(project.homepage.is_none());
+        assert_eq!(project.topics, vec!["rust", "axum"]);
+    }
+
+    #[test]
+    fn test_empty_blurb_keeps_description() {
+        let project =
+            to_project(&doc! { "name": "folio", "description": "From GitHub", "blurb": "" });
+
+        assert_eq!(project.description, "From GitHub");
+    }
+}
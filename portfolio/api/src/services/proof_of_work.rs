@@ -0,0 +1,394 @@
+use anyhow::Result;
+use chrono::Utc;
+use mongodb::bson::{doc, Document};
+use mongodb::Database;
+use ring::{digest, hmac};
+use std::fmt;
+use std::time::Duration;
+
+use crate::config::Config;
+use crate::services::db::is_duplicate_key;
+
+/// Collection of the solved challenges, used for replay protection and load
+const COLLECTION: &str = "contact_challenges";
+
+/// Longest accepted solution
+const MAX_SOLUTION_LEN: usize = 64;
+
+/// Reason a proof of work is refused
+#[derive(Debug)]
+pub enum ChallengeError {
+    Missing,
+    /// Malformed or badly signed challenge
+    Invalid,
+    Expired,
+    /// The solution does not reach the difficulty of the challenge
+    Unsolved,
+    /// Already used by a previous submission
+    Replayed,
+    /// The challenge could not be recorded
+    Storage(anyhow::Error),
+}
+
+impl ChallengeError {
+    /// Message shown to the visitor
+    #[must_use]
+    pub const fn message(&self) -> &'static str {
+        match self {
+            Self::Missing | Self::Invalid => "Défi anti-spam invalide",
+            Self::Expired => "Le défi anti-spam a expiré, veuillez réessayer",
+            Self::Unsolved => "Le défi anti-spam n'est pas résolu",
+            Self::Replayed => "Ce défi anti-spam a déjà été utilisé",
+            Self::Storage(_) => "Erreur lors de la vérification du défi anti-spam",
+        }
+    }
+}
+
+impl fmt::Display for ChallengeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Storage(e) => write!(f, "{}: {e}", self.message()),
+            _ => f.write_str(self.message()),
+        }
+    }
+}
+
+impl std::error::Error for ChallengeError {}
+
+/// Content of a valid challenge
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Challenge {
+    /// Issue time, in seconds since the epoch
+    pub issued_at: i64,
+    /// Leading zero bits required in the hash of the solution
+    pub difficulty: u32,
+    pub nonce: String,
+}
+
+/// Difficulty policy of the challenges
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Difficulty {
+    /// Difficulty under normal load
+    pub base: u32,
+    pub max: u32,
+    /// Solved challenges per window from which the difficulty is raised
+    pub spike_threshold: u64,
+    pub window: Duration,
+}
+
+impl Difficulty {
+    /// Difficulty for `recent` solved challenges in the window
+    ///
+    /// One bit, doubling the expected work, is added when the threshold is
+    /// reached, then one more each time the volume doubles again.
+    #[must_use]
+    pub fn for_volume(&self, recent: u64) -> u32 {
+        if self.spike_threshold == 0 || recent < self.spike_threshold {
+            return self.base.min(self.max);
+        }
+        let extra = 1 + (recent / self.spike_threshold).ilog2();
+        self.base.saturating_add(extra).min(self.max)
+    }
+}
+
+/// Number of leading zero bits of a hash
+fn leading_zero_bits(hash: &[u8]) -> u32 {
+    let mut bits = 0;
+    for byte in hash {
+        bits += byte.leading_zeros();
+        if *byte != 0 {
+            break;
+        }
+    }
+    bits
+}
+
+/// Returns true when `solution` solves `challenge` at `difficulty`
+///
+/// The SHA-256 hash of `{challenge}:{solution}` must start with
+/// `difficulty` zero bits.
+#[must_use]
+pub fn is_solution(challenge: &str, solution: &str, difficulty: u32) -> bool {
+    let hash = digest::digest(
+        &digest::SHA256,
+        format!("{challenge}:{solution}").as_bytes(),
+    );
+    leading_zero_bits(hash.as_ref()) >= difficulty
+}
+
+/// Hashcash-style challenges the contact form must solve
+///
+/// A challenge is `{issued_at}.{difficulty}.{nonce}.{signature}`, the
+/// signature being an HMAC-SHA256 of the first three parts. The browser
+/// searches a solution for which [`is_solution`] holds; each challenge is
+/// accepted once before its lifetime ends, solved challenges being stored
+/// in `contact_challenges`. Their recent count sets the difficulty of the
+/// next challenges, so that a spike of submissions makes them costlier.
+pub struct ProofOfWork {
+    db: Database,
+    key: hmac::Key,
+    difficulty: Difficulty,
+    ttl: Duration,
+}
+
+impl ProofOfWork {
+    #[must_use]
+    pub fn new(db: Database, secret: &[u8], difficulty: Difficulty, ttl: Duration) -> Self {
+        Self {
+            db,
+            key: hmac::Key::new(hmac::HMAC_SHA256, secret),
+            difficulty,
+            ttl,
+        }
+    }
+
+    /// Challenges signed with `secret` and the `CONTACT_POW_*` settings
+    #[must_use]
+    pub fn from_config(db: Database, secret: &[u8], config: &Config) -> Self {
+        Self::new(
+            db,
+            secret,
+            Difficulty {
+                base: config.contact_pow_difficulty,
+                max: config.contact_pow_max_difficulty,
+                spike_threshold: config.contact_pow_spike_threshold,
+                window: Duration::from_secs(config.contact_pow_window_secs),
+            },
+            Duration::from_secs(config.contact_pow_ttl_secs),
+        )
+    }
+
+    /// Lifetime of a challenge
+    #[must_use]
+    pub const fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    fn sign(&self, payload: &str) -> String {
+        // Prefixed so that a challenge can never pass for a form token
+        hex::encode(hmac::sign(&self.key, format!("pow:{payload}").as_bytes()))
+    }
+
+    /// Issues a challenge of `difficulty` dated `issued_at` (seconds since the epoch)
+    #[must_use]
+    pub fn issue_at(&self, issued_at: i64, difficulty: u32) -> String {
+        let payload = format!(
+            "{issued_at}.{difficulty}.{}",
+            hex::encode(rand::random::<[u8; 16]>())
+        );
+        let signature = self.sign(&payload);
+        format!("{payload}.{signature}")
+    }
+
+    /// Issues a challenge dated now, at the difficulty of the current load.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the recent challenges cannot be counted.
+    pub async fn issue(&self) -> Result<(String, u32)> {
+        let difficulty = self.current_difficulty().await?;
+        Ok((
+            self.issue_at(Utc::now().timestamp(), difficulty),
+            difficulty,
+        ))
+    }
+
+    /// Difficulty for the challenges solved during the last window.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the challenges cannot be counted.
+    pub async fn current_difficulty(&self) -> Result<u32> {
+        let since =
+            Utc::now() - chrono::Duration::from_std(self.difficulty.window).unwrap_or_default();
+        let recent = self
+            .db
+            .collection::<Document>(COLLECTION)
+            .count_documents(doc! {
+                "solved_at": { "$gte": mongodb::bson::DateTime::from_millis(since.timestamp_millis()) }
+            })
+            .await?;
+        Ok(self.difficulty.for_volume(recent))
+    }
+
+    /// Checks the signature, age and solution of a challenge at `now`
+    /// (seconds since the epoch)
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ChallengeError::Invalid`], [`ChallengeError::Expired`] or
+    /// [`ChallengeError::Unsolved`].
+    pub fn check(
+        &self,
+        challenge: &str,
+        solution: &str,
+        now: i64,
+    ) -> Result<Challenge, ChallengeError> {
+        let challenge = challenge.trim();
+        let (payload, signature) = challenge.rsplit_once('.').ok_or(ChallengeError::Invalid)?;
+        let signature = hex::decode(signature).map_err(|_| ChallengeError::Invalid)?;
+        hmac::verify(&self.key, format!("pow:{payload}").as_bytes(), &signature)
+            .map_err(|_| ChallengeError::Invalid)?;
+
+        let mut parts = payload.splitn(3, '.');
+        let (Some(issued_at), Some(difficulty), Some(nonce)) =
+            (parts.next(), parts.next(), parts.next())
+        else {
+            return Err(ChallengeError::Invalid);
+        };
+        let issued_at: i64 = issued_at.parse().map_err(|_| ChallengeError::Invalid)?;
+        let difficulty: u32 = difficulty.parse().map_err(|_| ChallengeError::Invalid)?;
+        if now.saturating_sub(issued_at) > i64::try_from(self.ttl.as_secs()).unwrap_or(i64::MAX) {
+            return Err(ChallengeError::Expired);
+        }
+
+        if solution.len() > MAX_SOLUTION_LEN || !is_solution(challenge, solution, difficulty) {
+            return Err(ChallengeError::Unsolved);
+        }
+
+        Ok(Challenge {
+            issued_at,
+            difficulty,
+            nonce: nonce.to_string(),
+        })
+    }
+
+    /// Checks a submitted solution and marks its challenge as used.
+    ///
+    /// # Errors
+    ///
+    /// Returns the reason the proof is refused, [`ChallengeError::Storage`]
+    /// if it cannot be recorded.
+    pub async fn redeem(
+        &self,
+        challenge: Option<&str>,
+        solution: Option<&str>,
+    ) -> Result<Challenge, ChallengeError> {
+        let (Some(challenge), Some(solution)) = (
+            challenge.filter(|challenge| !challenge.trim().is_empty()),
+            solution,
+        ) else {
+            return Err(ChallengeError::Missing);
+        };
+        let now = Utc::now();
+        let claims = self.check(challenge, solution, now.timestamp())?;
+
+        // Kept until the challenge expires, and for the load window
+        let keep = self.ttl.max(self.difficulty.window);
+        let expires_at = mongodb::bson::DateTime::from_millis(
+            now.timestamp_millis()
+                .saturating_add(i64::try_from(keep.as_millis()).unwrap_or(i64::MAX)),
+        );
+        let result = self
+            .db
+            .collection::<Document>(COLLECTION)
+            .insert_one(doc! {
+                "nonce": &claims.nonce,
+                "difficulty": i64::from(claims.difficulty),
+                "solved_at": mongodb::bson::DateTime::from_millis(now.timestamp_millis()),
+                "expires_at": expires_at,
+            })
+            .await;
+        match result {
+            Ok(_) => Ok(claims),
+            Err(e) => {
+                let error = anyhow::Error::from(e);
+                if is_duplicate_key(&error) {
+                    Err(ChallengeError::Replayed)
+                } else {
+                    Err(ChallengeError::Storage(error))
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DIFFICULTY: Difficulty = Difficulty {
+        base: 16,
+        max: 20,
+        spike_threshold: 10,
+        window: Duration::from_secs(600),
+    };
+
+    async fn challenges() -> ProofOfWork {
+        let client = mongodb::Client::with_uri_str("mongodb://127.0.0.1:27017")
+            .await
+            .unwrap();
+        ProofOfWork::new(
+            client.database("portfolio_test"),
+            b"secret",
+            DIFFICULTY,
+            Duration::from_secs(600),
+        )
+    }
+
+    fn solve(challenge: &str, difficulty: u32) -> String {
+        (0u64..)
+            .map(|counter| counter.to_string())
+            .find(|solution| is_solution(challenge, solution, difficulty))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_adaptive_difficulty() {
+        assert_eq!(DIFFICULTY.for_volume(0), 16);
+        assert_eq!(DIFFICULTY.for_volume(9), 16);
+        assert_eq!(DIFFICULTY.for_volume(10), 17);
+        assert_eq!(DIFFICULTY.for_volume(19), 17);
+        assert_eq!(DIFFICULTY.for_volume(20), 18);
+        assert_eq!(DIFFICULTY.for_volume(40), 19);
+        assert_eq!(DIFFICULTY.for_volume(10_000), 20);
+    }
+
+    #[test]
+    fn test_leading_zero_bits() {
+        assert_eq!(leading_zero_bits(&[0x00, 0x00, 0xff]), 16);
+        assert_eq!(leading_zero_bits(&[0x00, 0x1f]), 11);
+        assert_eq!(leading_zero_bits(&[0x80]), 0);
+        assert_eq!(leading_zero_bits(&[0x00]), 8);
+    }
+
+    #[tokio::test]
+    async fn test_solved_challenge() {
+        let challenges = challenges().await;
+        let challenge = challenges.issue_at(1_000, 8);
+        let solution = solve(&challenge, 8);
+
+        let claims = challenges.check(&challenge, &solution, 1_010).unwrap();
+        assert_eq!(claims.issued_at, 1_000);
+        assert_eq!(claims.difficulty, 8);
+        assert_eq!(claims.nonce.len(), 32);
+
+        let wrong = (0u64..)
+            .map(|counter| counter.to_string())
+            .find(|solution| !is_solution(&challenge, solution, 8))
+            .unwrap();
+        assert!(matches!(
+            challenges.check(&challenge, &wrong, 1_010),
+            Err(ChallengeError::Unsolved)
+        ));
+        assert!(matches!(
+            challenges.check(&challenge, &solution, 1_601),
+            Err(ChallengeError::Expired)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_tampered_challenge_is_invalid() {
+        let challenges = challenges().await;
+        let challenge = challenges.issue_at(1_000, 8);
+        // Lowering the difficulty breaks the signature
+        let easier = challenge.replacen("1000.8.", "1000.0.", 1);
+
+        for challenge in [easier.as_str(), "1000.0.abc", "garbage", ""] {
+            assert!(matches!(
+                challenges.check(challenge, "0", 1_010),
+                Err(ChallengeError::Invalid)
+            ));
+        }
+    }
+}
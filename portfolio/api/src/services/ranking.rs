@@ -0,0 +1,284 @@
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use std::collections::{HashMap, VecDeque};
+use std::env;
+
+/// Ordering applied to the aggregated RSS listing
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RankingMode {
+    /// Strict publication date, most recent first
+    #[default]
+    Recent,
+    /// Recency decay weighted per source, with a diversity constraint
+    Ranked,
+}
+
+impl std::str::FromStr for RankingMode {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "recent" => Ok(Self::Recent),
+            "ranked" => Ok(Self::Ranked),
+            other => anyhow::bail!("Unknown ranking mode: {other}"),
+        }
+    }
+}
+
+/// Deployment settings of the ranked listing
+///
+/// # Environment Variables
+/// - `RSS_RANKING_MODE`: default mode, `recent` or `ranked` (default: `recent`)
+/// - `RSS_RANKING_HALF_LIFE_HOURS`: age at which an item's score is halved (default: 48)
+/// - `RSS_SOURCE_WEIGHTS`: `feed_url=weight` pairs separated by commas (default weight: 1)
+/// - `RSS_MAX_CONSECUTIVE_PER_SOURCE`: longest run of items from one source (default: 2)
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct RankingConfig {
+    pub default_mode: RankingMode,
+    pub half_life_hours: f64,
+    pub source_weights: HashMap<String, f64>,
+    pub max_consecutive_per_source: usize,
+}
+
+impl Default for RankingConfig {
+    fn default() -> Self {
+        Self {
+            default_mode: RankingMode::Recent,
+            half_life_hours: 48.0,
+            source_weights: HashMap::new(),
+            max_consecutive_per_source: 2,
+        }
+    }
+}
+
+impl RankingConfig {
+    /// Reads the ranking settings from the environment
+    ///
+    /// # Panics
+    ///
+    /// Panics if one of the variables is set with an invalid value.
+    #[must_use]
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+
+        Self {
+            default_mode: env::var("RSS_RANKING_MODE")
+                .map(|v| {
+                    v.parse()
+                        .expect("RSS_RANKING_MODE must be recent or ranked")
+                })
+                .unwrap_or(defaults.default_mode),
+            half_life_hours: env::var("RSS_RANKING_HALF_LIFE_HOURS")
+                .map(|v| {
+                    v.parse::<f64>()
+                        .ok()
+                        .filter(|h| *h > 0.0)
+                        .expect("RSS_RANKING_HALF_LIFE_HOURS must be a positive number")
+                })
+                .unwrap_or(defaults.half_life_hours),
+            source_weights: env::var("RSS_SOURCE_WEIGHTS")
+                .map(|v| parse_source_weights(&v))
+                .unwrap_or_default(),
+            max_consecutive_per_source: env::var("RSS_MAX_CONSECUTIVE_PER_SOURCE")
+                .map(|v| {
+                    v.parse::<usize>()
+                        .ok()
+                        .filter(|n| *n > 0)
+                        .expect("RSS_MAX_CONSECUTIVE_PER_SOURCE must be a positive integer")
+                })
+                .unwrap_or(defaults.max_consecutive_per_source),
+        }
+    }
+
+    fn weight(&self, source: &str) -> f64 {
+        self.source_weights.get(source).copied().unwrap_or(1.0)
+    }
+}
+
+/// Parses `url=weight` pairs, ignoring malformed entries
+fn parse_source_weights(value: &str) -> HashMap<String, f64> {
+    value
+        .split(',')
+        .filter_map(|pair| {
+            let (source, weight) = pair.rsplit_once('=')?;
+            let source = source.trim();
+            if source.is_empty() {
+                return None;
+            }
+            let weight = weight.trim().parse::<f64>().ok().filter(|w| *w > 0.0)?;
+            Some((source.to_string(), weight))
+        })
+        .collect()
+}
+
+/// Item metadata needed to rank the listing
+#[derive(Debug, Clone)]
+pub struct Candidate<K> {
+    pub key: K,
+    pub source: String,
+    pub pub_date: DateTime<Utc>,
+    pub pinned: bool,
+    /// Stable tie-breaker (item id)
+    pub tie_breaker: String,
+}
+
+/// Orders the whole listing so that pages can be sliced from it
+///
+/// The score is `weight * 0.5^(age / half_life)`. Since every item ages at the
+/// same rate, the relative order does not depend on the current time, which keeps
+/// pagination stable between requests. Pinned items stay first, by recency.
+#[must_use]
+pub fn rank<K>(candidates: Vec<Candidate<K>>, config: &RankingConfig) -> Vec<Candidate<K>> {
+    // log2 of the score, shifted by a constant: log2(weight) + timestamp / half_life
+    let log_score = |c: &Candidate<K>| {
+        config.weight(&c.source).log2()
+            + c.pub_date.timestamp() as f64 / (config.half_life_hours * 3600.0)
+    };
+
+    let (mut pinned, mut others): (Vec<_>, Vec<_>) = candidates.into_iter().partition(|c| c.pinned);
+
+    pinned.sort_by(|a, b| {
+        b.pub_date
+            .cmp(&a.pub_date)
+            .then_with(|| a.tie_breaker.cmp(&b.tie_breaker))
+    });
+    others.sort_by(|a, b| {
+        log_score(b)
+            .total_cmp(&log_score(a))
+            .then_with(|| b.pub_date.cmp(&a.pub_date))
+            .then_with(|| a.tie_breaker.cmp(&b.tie_breaker))
+    });
+
+    pinned.extend(diversify(others, config.max_consecutive_per_source));
+    pinned
+}
+
+/// Reorders items so that no source appears more than `max_run` times in a row
+///
+/// Greedily takes the best remaining item allowed after the current run. When
+/// only the current source is left, the constraint cannot be met and the
+/// remaining items are appended in order.
+fn diversify<K>(items: Vec<Candidate<K>>, max_run: usize) -> Vec<Candidate<K>> {
+    let mut remaining: VecDeque<Candidate<K>> = items.into();
+    let mut result: Vec<Candidate<K>> = Vec::with_capacity(remaining.len());
+    let mut run = 0;
+
+    while !remaining.is_empty() {
+        let last_source = result.last().map(|c| c.source.as_str());
+        let index = if run >= max_run.max(1) {
+            remaining
+                .iter()
+                .position(|c| Some(c.source.as_str()) != last_source)
+                .unwrap_or(0)
+        } else {
+            0
+        };
+
+        let Some(next) = remaining.remove(index) else {
+            break;
+        };
+        run = if Some(next.source.as_str()) == last_source {
+            run + 1
+        } else {
+            1
+        };
+        result.push(next);
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn candidate(key: u32, source: &str, hours_ago: i64, pinned: bool) -> Candidate<u32> {
+        Candidate {
+            key,
+            source: source.to_string(),
+            pub_date: DateTime::from_timestamp(1_700_000_000, 0).unwrap()
+                - Duration::hours(hours_ago),
+            pinned,
+            tie_breaker: key.to_string(),
+        }
+    }
+
+    fn keys(ranked: &[Candidate<u32>]) -> Vec<u32> {
+        ranked.iter().map(|c| c.key).collect()
+    }
+
+    #[test]
+    fn test_recency_without_weights() {
+        let config = RankingConfig::default();
+        let ranked = rank(
+            vec![
+                candidate(1, "a", 10, false),
+                candidate(2, "b", 1, false),
+                candidate(3, "c", 5, false),
+            ],
+            &config,
+        );
+
+        assert_eq!(keys(&ranked), vec![2, 3, 1]);
+    }
+
+    #[test]
+    fn test_source_weight_outranks_recency() {
+        let config = RankingConfig {
+            source_weights: parse_source_weights("a=4, b=1"),
+            ..RankingConfig::default()
+        };
+        // "a" is 48h (one half-life) older but weighs 4x more
+        let ranked = rank(
+            vec![candidate(1, "b", 0, false), candidate(2, "a", 48, false)],
+            &config,
+        );
+
+        assert_eq!(keys(&ranked), vec![2, 1]);
+    }
+
+    #[test]
+    fn test_diversity_limits_consecutive_items() {
+        let config = RankingConfig {
+            max_consecutive_per_source: 2,
+            ..RankingConfig::default()
+        };
+        let ranked = rank(
+            vec![
+                candidate(1, "a", 1, false),
+                candidate(2, "a", 2, false),
+                candidate(3, "a", 3, false),
+                candidate(4, "a", 4, false),
+                candidate(5, "b", 5, false),
+            ],
+            &config,
+        );
+
+        assert_eq!(keys(&ranked), vec![1, 2, 5, 3, 4]);
+    }
+
+    #[test]
+    fn test_pinned_items_come_first() {
+        let config = RankingConfig::default();
+        let ranked = rank(
+            vec![candidate(1, "a", 1, false), candidate(2, "b", 100, true)],
+            &config,
+        );
+
+        assert_eq!(keys(&ranked), vec![2, 1]);
+    }
+
+    #[test]
+    fn test_invalid_weights_are_ignored() {
+        let weights = parse_source_weights("a=2,b=x,c=-1,=3,https://x.dev/feed?a=b=0.5");
+
+        assert_eq!(weights.get("a"), Some(&2.0));
+        assert_eq!(weights.get("https://x.dev/feed?a=b"), Some(&0.5));
+        assert!(!weights.contains_key("b"));
+        assert!(!weights.contains_key("c"));
+        assert_eq!(weights.len(), 2);
+    }
+}
@@ -0,0 +1,185 @@
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::collections::{HashMap, HashSet};
+
+/// Maximum number of related items kept per item
+pub const MAX_RELATED: usize = 10;
+
+/// Similarity below which two items are not considered related
+const MIN_SIMILARITY: f64 = 0.08;
+
+static TAG_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"<[^>]*>").unwrap());
+static WORD_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"[\p{L}\p{N}][\p{L}\p{N}+#]*").unwrap());
+
+static STOP_WORDS: Lazy<HashSet<&'static str>> = Lazy::new(|| {
+    [
+        // English
+        "the", "and", "for", "with", "that", "this", "from", "are", "was", "were", "you", "your",
+        "how", "what", "why", "when", "who", "which", "not", "but", "all", "can", "has", "have",
+        "its", "our", "out", "new", "more", "about", "into", "than", "then", "they", "their",
+        "will", "would", "been", "also", "just", "one", "use", "using", "read", "post",
+        // French
+        "les", "des", "une", "pour", "dans", "par", "sur", "est", "qui", "que", "quoi", "avec",
+        "pas", "plus", "son", "ses", "aux", "mais", "nous", "vous", "ils", "elle", "sont", "ont",
+        "été", "être", "cette", "ces", "leur", "comme", "tout", "lire", "suite",
+    ]
+    .into_iter()
+    .collect()
+});
+
+/// Text of an item used to compute similarity
+#[derive(Debug, Clone, Copy)]
+pub struct RelatedInput<'a> {
+    pub title: &'a str,
+    pub description: &'a str,
+    pub categories: &'a [String],
+}
+
+/// Term frequencies of an item, with title words and categories weighted higher
+fn term_frequencies(input: &RelatedInput<'_>) -> HashMap<String, f64> {
+    let mut terms = HashMap::new();
+    let mut add_words = |text: &str, weight: f64| {
+        for word in WORD_RE.find_iter(&text.to_lowercase()) {
+            let word = word.as_str();
+            if word.chars().count() >= 3 && !STOP_WORDS.contains(word) {
+                *terms.entry(word.to_string()).or_insert(0.0) += weight;
+            }
+        }
+    };
+
+    add_words(input.title, 2.0);
+    add_words(&TAG_RE.replace_all(input.description, " "), 1.0);
+    for category in input.categories {
+        let category = category.trim().to_lowercase();
+        if !category.is_empty() {
+            *terms.entry(format!("#{category}")).or_insert(0.0) += 3.0;
+        }
+    }
+
+    terms
+}
+
+/// Computes, for every item, the most similar other items
+///
+/// Items are represented as TF-IDF vectors over their title, description (HTML
+/// stripped) and categories, compared with cosine similarity. Returns, for each
+/// input index, up to [`MAX_RELATED`] `(index, similarity)` pairs, best first.
+#[must_use]
+pub fn compute(items: &[RelatedInput<'_>]) -> Vec<Vec<(usize, f64)>> {
+    let frequencies: Vec<HashMap<String, f64>> = items.iter().map(term_frequencies).collect();
+
+    let mut document_frequency: HashMap<&str, usize> = HashMap::new();
+    for terms in &frequencies {
+        for term in terms.keys() {
+            *document_frequency.entry(term.as_str()).or_insert(0) += 1;
+        }
+    }
+
+    let count = items.len() as f64;
+    let vectors: Vec<Vec<(&str, f64)>> = frequencies
+        .iter()
+        .map(|terms| {
+            let mut vector: Vec<(&str, f64)> = terms
+                .iter()
+                .map(|(term, tf)| {
+                    let df = document_frequency[term.as_str()] as f64;
+                    (term.as_str(), (1.0 + tf.ln()) * (count / df).ln())
+                })
+                .filter(|(_, weight)| *weight > 0.0)
+                .collect();
+            let norm = vector.iter().map(|(_, w)| w * w).sum::<f64>().sqrt();
+            for (_, weight) in &mut vector {
+                *weight /= norm;
+            }
+            vector
+        })
+        .collect();
+
+    // Inverted index: term -> (item, weight)
+    let mut postings: HashMap<&str, Vec<(usize, f64)>> = HashMap::new();
+    for (index, vector) in vectors.iter().enumerate() {
+        for (term, weight) in vector {
+            postings.entry(term).or_default().push((index, *weight));
+        }
+    }
+
+    vectors
+        .iter()
+        .enumerate()
+        .map(|(index, vector)| {
+            let mut scores: HashMap<usize, f64> = HashMap::new();
+            for (term, weight) in vector {
+                for (other, other_weight) in &postings[term] {
+                    if *other != index {
+                        *scores.entry(*other).or_insert(0.0) += weight * other_weight;
+                    }
+                }
+            }
+
+            let mut related: Vec<(usize, f64)> = scores
+                .into_iter()
+                .filter(|(_, score)| *score >= MIN_SIMILARITY)
+                .collect();
+            related.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+            related.truncate(MAX_RELATED);
+            related
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input<'a>(
+        title: &'a str,
+        description: &'a str,
+        categories: &'a [String],
+    ) -> RelatedInput<'a> {
+        RelatedInput {
+            title,
+            description,
+            categories,
+        }
+    }
+
+    #[test]
+    fn test_similar_items_are_related() {
+        let rust = vec!["Rust".to_string()];
+        let items = [
+            input(
+                "Async Rust with Tokio",
+                "<p>Tokio runtime internals</p>",
+                &rust,
+            ),
+            input(
+                "Tokio tasks explained",
+                "Spawning tasks on the Tokio runtime",
+                &rust,
+            ),
+            input("Baking sourdough bread", "Flour, water and patience", &[]),
+        ];
+
+        let related = compute(&items);
+
+        assert_eq!(related[0].first().map(|r| r.0), Some(1));
+        assert_eq!(related[1].first().map(|r| r.0), Some(0));
+        assert!(related[2].is_empty());
+    }
+
+    #[test]
+    fn test_html_and_stop_words_are_ignored() {
+        let terms = term_frequencies(&input("The new release", "<a href=\"x\">and</a>", &[]));
+
+        assert!(terms.contains_key("release"));
+        assert!(!terms.contains_key("the"));
+        assert!(!terms.contains_key("href"));
+        assert!(!terms.contains_key("and"));
+    }
+
+    #[test]
+    fn test_empty_input() {
+        assert!(compute(&[]).is_empty());
+        assert_eq!(compute(&[input("", "", &[])]), vec![Vec::new()]);
+    }
+}
@@ -1,17 +1,33 @@
-use crate::{config::Config, models::rss::RssItem};
+use crate::services::feed_filter::{FilterInput, RuleSet, SyncReport};
+use crate::services::image_meta::ImageMetadataFetcher;
+use crate::services::image_proxy;
+use crate::services::link_checker::LinkChecker;
+use crate::services::opengraph::OpenGraphScraper;
+use crate::services::placeholder;
+use crate::services::ranking::{self, Candidate};
+use crate::services::related::{self, RelatedInput};
+use crate::services::sources::{self, ContentSource, PostsSource, SourceItem, SourceKind};
+use crate::{
+    config::Config,
+    models::rss::{ModerationUpdate, RssItem},
+};
 use anyhow::Result;
 use chrono::{DateTime, Utc};
 use futures_util::TryStreamExt;
-use mongodb::bson::{doc, Bson, Document};
+use mongodb::bson::{doc, oid::ObjectId, Bson, Document};
 use mongodb::Database;
-use once_cell::sync::Lazy;
-use regex::Regex;
-use rss::{Channel, Item};
 use serde::{Deserialize, Serialize};
-use urlencoding;
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
 
-static IMG_SRC_RE: Lazy<Regex> =
-    Lazy::new(|| Regex::new(r#"(?i)<img[^>]+src=["'](https?://[^"']+)["']"#).unwrap());
+/// Derives the stable identifier of an item from its URL
+///
+/// Stored items are replaced on every sync, so the `MongoDB` `_id` cannot be used
+/// to reference them across runs.
+#[must_use]
+pub fn item_id(url: &str) -> String {
+    hex::encode(&Sha256::digest(url.as_bytes())[..12])
+}
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 /// RSS feed metadata container for source tracking
@@ -24,6 +40,68 @@ pub struct Feed {
     pub updated_at: DateTime<Utc>,
 }
 
+/// Path of the blog on the front-end, prefix of the post URLs
+const BLOG_PATH: &str = "/blog";
+
+/// Articles collected during a sync, with the state needed to admit them
+struct SyncBatch<'a> {
+    moderation: &'a HashMap<String, Document>,
+    dead_urls: &'a HashSet<String>,
+    report: SyncReport,
+    articles: Vec<(DateTime<Utc>, Document)>,
+}
+
+impl SyncBatch<'_> {
+    /// Filters the items of a source and converts them into articles
+    ///
+    /// Items rejected by one of `rules` are only counted in the report.
+    /// Moderation flags are re-applied and items with a dead link are hidden.
+    fn push_items(&mut self, source_link: &str, rules: &[&RuleSet], items: Vec<SourceItem>) {
+        for item in items {
+            self.report.fetched += 1;
+
+            let input = FilterInput {
+                title: &item.title,
+                description: &item.description,
+                categories: &item.categories,
+            };
+            if let Some(label) = rules.iter().find_map(|rules| rules.rejection(&input)) {
+                tracing::debug!("Dropping \"{}\" (rule {})", item.title, label);
+                self.report.record_drop(label);
+                continue;
+            }
+            let pub_date = item.pub_date.unwrap_or_else(Utc::now);
+
+            let id = item_id(&item.url);
+            let mut article = doc! {
+                "item_id": &id,
+                "source": source_link,
+                "title": &item.title,
+                "url": &item.url,
+                "pub_date": Bson::DateTime(mongodb::bson::DateTime::from_millis(pub_date.timestamp_millis())),
+                "description": &item.description,
+                "categories": &item.categories,
+            };
+            if let Some(image_url) = item.image_url {
+                article.insert("image_url", image_url);
+            }
+            if let Some(flags) = self.moderation.get(&id) {
+                for field in ["hidden", "pinned", "featured"] {
+                    if let Ok(value) = flags.get_bool(field) {
+                        article.insert(field, value);
+                    }
+                }
+            }
+            if self.dead_urls.contains(&item.url) {
+                article.insert("hidden", true);
+                article.insert("dead_link", true);
+            }
+
+            self.articles.push((pub_date, article));
+        }
+    }
+}
+
 /// RSS feed processing service with MongoDB integration
 ///
 /// # Features
@@ -49,59 +127,67 @@ impl FeedService {
         }
     }
 
-    /// Extracts image URL from RSS item enclosure
-    fn extract_from_enclosure(item: &Item) -> Option<String> {
-        item.enclosure()
-            .filter(|enclosure| enclosure.mime_type.starts_with("image/"))
-            .map(|enclosure| enclosure.url.clone())
-    }
-
-    /// Extracts image URL from RSS item media extension (content)
-    fn extract_from_media_content(item: &Item) -> Option<String> {
-        item.extensions
-            .get("media")?
-            .get("content")?
-            .first()?
-            .attrs
-            .get("url")
-            .cloned()
-    }
-
-    /// Extracts image URL from RSS item media extension (thumbnail)
-    fn extract_from_media_thumbnail(item: &Item) -> Option<String> {
-        item.extensions
-            .get("media")?
-            .get("thumbnail")?
-            .first()?
-            .attrs
-            .get("url")
-            .cloned()
-    }
-
-    /// Extracts image URL from HTML description using regex
-    fn extract_from_html_description(item: &Item) -> Option<String> {
-        item.description()
-            .and_then(|description| IMG_SRC_RE.captures(description))
-            .and_then(|cap| cap.get(1))
-            .map(|src| src.as_str().to_string())
+    /// Listing order used when a request does not specify one
+    #[must_use]
+    pub fn default_ranking_mode(&self) -> ranking::RankingMode {
+        self.config.ranking.default_mode
     }
 
-    /// Extracts the image URL from an RSS article item
-    ///
-    /// Tries multiple sources in order:
-    /// 1. RSS enclosure (image type)
-    /// 2. Media extension content
-    /// 3. Media extension thumbnail
-    /// 4. HTML description (regex search)
-    fn extract_image_url(item: &Item) -> Option<String> {
-        Self::extract_from_enclosure(item)
-            .or_else(|| Self::extract_from_media_content(item))
-            .or_else(|| Self::extract_from_media_thumbnail(item))
-            .or_else(|| Self::extract_from_html_description(item))
+    /// Converts a stored `portfolio` document into the public item representation
+    fn to_rss_item(doc: &Document, api_base_url: &str) -> RssItem {
+        let url = doc.get_str("url").unwrap_or_default().to_string();
+        RssItem {
+            id: doc
+                .get_str("item_id")
+                .map(str::to_string)
+                .unwrap_or_else(|_| item_id(&url)),
+            title: doc.get_str("title").unwrap_or_default().to_string(),
+            pub_date: doc
+                .get_datetime("pub_date")
+                .ok()
+                .map(|bson_dt| {
+                    DateTime::from_timestamp_millis(bson_dt.timestamp_millis())
+                        .unwrap_or_else(Utc::now)
+                })
+                .or_else(|| {
+                    // Fallback for legacy string format
+                    doc.get_str("pub_date")
+                        .ok()
+                        .and_then(|date_str| DateTime::parse_from_rfc3339(date_str).ok())
+                        .map(|dt| dt.with_timezone(&Utc))
+                })
+                .unwrap_or_else(Utc::now),
+            description: doc.get_str("description").unwrap_or_default().to_string(),
+            image_url: match (doc.get_str("image_hash"), doc.get_str("image_url")) {
+                (Ok(hash), _) => image_proxy::proxy_url(api_base_url, hash),
+                (_, Ok(image_url)) => image_url.to_string(),
+                _ => placeholder::placeholder_url(
+                    api_base_url,
+                    doc.get_str("title").unwrap_or("Article"),
+                    doc.get_str("source").unwrap_or_default(),
+                ),
+            },
+            icon_url: doc.get_str("icon_url").ok().map(str::to_string),
+            image_width: doc
+                .get_i64("image_width")
+                .ok()
+                .and_then(|v| u32::try_from(v).ok()),
+            image_height: doc
+                .get_i64("image_height")
+                .ok()
+                .and_then(|v| u32::try_from(v).ok()),
+            dominant_color: doc.get_str("dominant_color").ok().map(str::to_string),
+            blurhash: doc.get_str("blurhash").ok().map(str::to_string),
+            pinned: doc.get_bool("pinned").unwrap_or(false),
+            featured: doc.get_bool("featured").unwrap_or(false),
+            url,
+        }
     }
 
     /// Retrieves paginated RSS feed items from database
     ///
+    /// Hidden items are skipped and pinned items come first.
+    ///
     /// # Arguments
     /// * `page` - Pagination page number (1-based)
     /// * `limit` - Items per page
@@ -115,39 +201,18 @@ impl FeedService {
         let options = mongodb::options::FindOptions::builder()
             .skip(skip as u64)
             .limit(limit as i64)
-            .sort(doc! { "pub_date": -1 })
+            .sort(doc! { "pinned": -1, "pub_date": -1 })
             .build();
 
-        match collection.find(doc! {}).with_options(options).await {
+        match collection
+            .find(doc! { "hidden": { "$ne": true } })
+            .with_options(options)
+            .await
+        {
             Ok(cursor) => {
                 let docs: Vec<Document> = cursor.try_collect().await.unwrap_or_default();
-                docs.into_iter()
-                    .map(|doc| RssItem {
-                        title: doc.get_str("title").unwrap_or_default().to_string(),
-                        url: doc.get_str("url").unwrap_or_default().to_string(),
-                        pub_date: doc
-                            .get_datetime("pub_date")
-                            .ok()
-                            .map(|bson_dt| {
-                                DateTime::from_timestamp_millis(bson_dt.timestamp_millis())
-                                    .unwrap_or_else(Utc::now)
-                            })
-                            .or_else(|| {
-                                // Fallback for legacy string format
-                                doc.get_str("pub_date")
-                                    .ok()
-                                    .and_then(|date_str| {
-                                        DateTime::parse_from_rfc3339(date_str).ok()
-                                    })
-                                    .map(|dt| dt.with_timezone(&Utc))
-                            })
-                            .unwrap_or_else(Utc::now),
-                        description: doc.get_str("description").unwrap_or_default().to_string(),
-                        image_url: doc
-                            .get_str("image_url")
-                            .unwrap_or("https://placehold.co/600x400/grey/white/png?text=Article")
-                            .to_string(),
-                    })
+                docs.iter()
+                    .map(|doc| Self::to_rss_item(doc, &self.config.public_api_url))
                     .collect()
             }
             Err(e) => {
@@ -157,31 +222,399 @@ impl FeedService {
         }
     }
 
-    /// Fetches and parses an RSS feed
-    async fn fetch_rss(&self, url: &str) -> Result<Channel> {
-        let content = self
-            .client
-            .get(url)
-            .header("User-Agent", "Mozilla/5.0 (compatible; RSSBot/1.0)")
-            .send()
+    /// Retrieves a page of the listing ordered by the configured ranking
+    ///
+    /// The whole non-hidden listing is ranked from a lightweight projection,
+    /// then only the requested page is loaded. See [`ranking::rank`].
+    pub async fn get_ranked_feeds(&self, page: u32, limit: u32) -> Vec<RssItem> {
+        let collection = self.db.collection::<Document>("portfolio");
+        let projection =
+            doc! { "_id": 1, "item_id": 1, "url": 1, "source": 1, "pub_date": 1, "pinned": 1 };
+
+        let docs: Vec<Document> = match collection
+            .find(doc! { "hidden": { "$ne": true } })
+            .projection(projection)
+            .await
+        {
+            Ok(cursor) => cursor.try_collect().await.unwrap_or_default(),
+            Err(e) => {
+                tracing::error!("Error fetching RSS feeds for ranking: {}", e);
+                return Vec::new();
+            }
+        };
+
+        let candidates = docs
+            .iter()
+            .filter_map(|doc| {
+                let key = doc.get_object_id("_id").ok()?;
+                let item = Self::to_rss_item(doc, &self.config.public_api_url);
+                Some(Candidate {
+                    key,
+                    source: doc.get_str("source").unwrap_or_default().to_string(),
+                    pub_date: item.pub_date,
+                    pinned: item.pinned,
+                    tie_breaker: item.id,
+                })
+            })
+            .collect();
+
+        let page_keys: Vec<ObjectId> = ranking::rank(candidates, &self.config.ranking)
+            .into_iter()
+            .skip(((page.max(1) - 1) * limit) as usize)
+            .take(limit as usize)
+            .map(|c| c.key)
+            .collect();
+        if page_keys.is_empty() {
+            return Vec::new();
+        }
+
+        let page_docs: Vec<Document> =
+            match collection.find(doc! { "_id": { "$in": &page_keys } }).await {
+                Ok(cursor) => cursor.try_collect().await.unwrap_or_default(),
+                Err(e) => {
+                    tracing::error!("Error fetching ranked RSS feeds: {}", e);
+                    return Vec::new();
+                }
+            };
+
+        let mut by_key: HashMap<ObjectId, Document> = page_docs
+            .into_iter()
+            .filter_map(|doc| Some((doc.get_object_id("_id").ok()?, doc)))
+            .collect();
+        page_keys
+            .iter()
+            .filter_map(|key| by_key.remove(key))
+            .map(|doc| Self::to_rss_item(&doc, &self.config.public_api_url))
+            .collect()
+    }
+
+    /// Retrieves the items related to an item, most similar first
+    ///
+    /// Relations are computed by `store_items`; hidden items are skipped.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if a database operation fails. Returns `Ok(None)` when no
+    /// stored item has this id.
+    pub async fn get_related(&self, id: &str, limit: u32) -> Result<Option<Vec<RssItem>>> {
+        let collection = self.db.collection::<Document>("portfolio");
+        let Some(item) = collection.find_one(doc! { "item_id": id }).await? else {
+            return Ok(None);
+        };
+
+        let related_ids = Self::string_array(&item, "related");
+        if related_ids.is_empty() {
+            return Ok(Some(Vec::new()));
+        }
+
+        let docs: Vec<Document> = collection
+            .find(doc! { "item_id": { "$in": &related_ids }, "hidden": { "$ne": true } })
+            .await?
+            .try_collect()
+            .await?;
+        let mut by_id: HashMap<String, Document> = docs
+            .into_iter()
+            .filter_map(|doc| Some((doc.get_str("item_id").ok()?.to_string(), doc)))
+            .collect();
+
+        Ok(Some(
+            related_ids
+                .iter()
+                .filter_map(|related_id| by_id.remove(related_id))
+                .take(limit as usize)
+                .map(|doc| Self::to_rss_item(&doc, &self.config.public_api_url))
+                .collect(),
+        ))
+    }
+
+    /// Reads an array of strings from a document, ignoring other values
+    fn string_array(doc: &Document, key: &str) -> Vec<String> {
+        doc.get_array(key)
+            .map(|values| {
+                values
+                    .iter()
+                    .filter_map(|value| value.as_str().map(str::to_string))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Fills missing images and descriptions from the article pages' metadata
+    ///
+    /// Also records the site icon. Pages are fetched through [`OpenGraphScraper`],
+    /// which caches results so that each URL is scraped at most once.
+    async fn enrich_from_pages(&self, articles: &mut [(DateTime<Utc>, Document)]) {
+        let needs_enrichment = |doc: &Document| {
+            !doc.contains_key("image_url")
+                || doc.get_str("description").unwrap_or_default().is_empty()
+        };
+
+        let mut urls: Vec<String> = articles
+            .iter()
+            .filter(|(_, doc)| needs_enrichment(doc))
+            .filter_map(|(_, doc)| doc.get_str("url").ok())
+            .filter(|url| url.starts_with("http://") || url.starts_with("https://"))
+            .map(str::to_string)
+            .collect();
+        urls.sort();
+        urls.dedup();
+        if urls.is_empty() {
+            return;
+        }
+
+        tracing::info!("Looking up page metadata for {} article(s)", urls.len());
+        let scraper = OpenGraphScraper::new(self.db.clone());
+        let metadata = scraper.lookup_all(urls).await;
+
+        for (_, doc) in articles.iter_mut() {
+            let Some(page) = doc.get_str("url").ok().and_then(|url| metadata.get(url)) else {
+                continue;
+            };
+            if let (false, Some(image)) = (doc.contains_key("image_url"), &page.image) {
+                doc.insert("image_url", image);
+            }
+            if let (true, Some(description)) = (
+                doc.get_str("description").unwrap_or_default().is_empty(),
+                &page.description,
+            ) {
+                doc.insert("description", description);
+            }
+            if let Some(icon) = &page.icon {
+                doc.insert("icon_url", icon);
+            }
+        }
+    }
+
+    /// Removes the images found dead by the [`LinkChecker`], so that the fallback applies
+    fn drop_dead_images(articles: &mut [(DateTime<Utc>, Document)], dead_urls: &HashSet<String>) {
+        for (_, doc) in articles.iter_mut() {
+            let is_dead = doc
+                .get_str("image_url")
+                .is_ok_and(|url| dead_urls.contains(url));
+            if is_dead {
+                doc.remove("image_url");
+                doc.insert("dead_image", true);
+            }
+        }
+    }
+
+    /// Records the proxy hash of the third-party images, served through `/api/img`
+    fn link_proxied_images(articles: &mut [(DateTime<Utc>, Document)]) {
+        for (_, doc) in articles.iter_mut() {
+            let hash = doc
+                .get_str("image_url")
+                .ok()
+                .filter(|url| url.starts_with("http://") || url.starts_with("https://"))
+                .map(image_proxy::image_hash);
+            if let Some(hash) = hash {
+                doc.insert("image_hash", hash);
+            }
+        }
+    }
+
+    /// Gives the self-hosted placeholder image to the articles still without one
+    fn apply_image_fallback(&self, articles: &mut [(DateTime<Utc>, Document)]) {
+        for (_, doc) in articles.iter_mut() {
+            if !doc.contains_key("image_url") {
+                let placeholder = placeholder::placeholder_url(
+                    &self.config.public_api_url,
+                    doc.get_str("title").unwrap_or_default(),
+                    doc.get_str("source").unwrap_or_default(),
+                );
+                doc.insert("image_url", placeholder);
+            }
+        }
+    }
+
+    /// Records the size, dominant color and blurhash of the stored articles' images
+    ///
+    /// Runs after [`Self::store_items`]. Images are downloaded through
+    /// [`ImageMetadataFetcher`], which caches results by URL, so only new images are
+    /// fetched on each sync. Self-hosted placeholders are skipped.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the stored articles cannot be read or updated.
+    ///
+    /// # Returns
+    /// Number of articles updated
+    pub async fn enrich_images(&self) -> Result<u64> {
+        let collection = self.db.collection::<Document>("portfolio");
+        let placeholder_prefix = format!(
+            "{}/api/placeholder",
+            self.config.public_api_url.trim_end_matches('/')
+        );
+
+        let mut urls: Vec<String> = collection
+            .distinct("image_url", doc! { "image_width": { "$exists": false } })
             .await?
-            .bytes()
+            .into_iter()
+            .filter_map(|url| url.as_str().map(str::to_string))
+            .filter(|url| url.starts_with("http://") || url.starts_with("https://"))
+            .filter(|url| !url.starts_with(&placeholder_prefix))
+            .collect();
+        urls.sort();
+        if urls.is_empty() {
+            return Ok(0);
+        }
+
+        tracing::info!("Looking up image metadata for {} image(s)", urls.len());
+        let fetcher = ImageMetadataFetcher::new(self.db.clone());
+        let metadata = fetcher.lookup_all(urls).await;
+
+        let mut updated = 0;
+        for (url, image) in metadata {
+            let result = collection
+                .update_many(
+                    doc! { "image_url": &url },
+                    doc! { "$set": {
+                        "image_width": i64::from(image.width),
+                        "image_height": i64::from(image.height),
+                        "dominant_color": &image.dominant_color,
+                        "blurhash": &image.blurhash,
+                    }},
+                )
+                .await?;
+            updated += result.modified_count;
+        }
+
+        Ok(updated)
+    }
+
+    /// Stores on each article the ids of its most similar articles
+    fn link_related(articles: &mut [(DateTime<Utc>, Document)]) {
+        let ids: Vec<String> = articles
+            .iter()
+            .map(|(_, doc)| doc.get_str("item_id").unwrap_or_default().to_string())
+            .collect();
+        let categories: Vec<Vec<String>> = articles
+            .iter()
+            .map(|(_, doc)| Self::string_array(doc, "categories"))
+            .collect();
+        let inputs: Vec<RelatedInput<'_>> = articles
+            .iter()
+            .zip(&categories)
+            .map(|((_, doc), categories)| RelatedInput {
+                title: doc.get_str("title").unwrap_or_default(),
+                description: doc.get_str("description").unwrap_or_default(),
+                categories,
+            })
+            .collect();
+        let related = related::compute(&inputs);
+
+        for (index, ((_, doc), related)) in articles.iter_mut().zip(related).enumerate() {
+            let mut related_ids: Vec<&str> = Vec::new();
+            for (other, _) in related {
+                let other_id = ids[other].as_str();
+                // The same article may be listed by several feeds
+                if other_id != ids[index] && !related_ids.contains(&other_id) {
+                    related_ids.push(other_id);
+                }
+            }
+            doc.insert("related", related_ids);
+        }
+    }
+
+    /// Retrieves the featured, non-hidden items, most recent first
+    pub async fn get_featured(&self, limit: u32) -> Vec<RssItem> {
+        let collection = self.db.collection::<Document>("portfolio");
+
+        match collection
+            .find(doc! { "featured": true, "hidden": { "$ne": true } })
+            .sort(doc! { "pub_date": -1 })
+            .limit(i64::from(limit))
+            .await
+        {
+            Ok(cursor) => {
+                let docs: Vec<Document> = cursor.try_collect().await.unwrap_or_default();
+                docs.iter()
+                    .map(|doc| Self::to_rss_item(doc, &self.config.public_api_url))
+                    .collect()
+            }
+            Err(e) => {
+                tracing::error!("Error fetching featured RSS items: {}", e);
+                Vec::new()
+            }
+        }
+    }
+
+    /// Applies editorial flags to an item
+    ///
+    /// Flags are recorded in the `rss_moderation` collection, keyed by item id,
+    /// so that `store_items` can restore them after each re-sync.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if a database operation fails. Returns `Ok(false)` when no
+    /// stored item has this id.
+    pub async fn moderate_item(&self, id: &str, update: &ModerationUpdate) -> Result<bool> {
+        let items = self.db.collection::<Document>("portfolio");
+        let Some(item) = items.find_one(doc! { "item_id": id }).await? else {
+            return Ok(false);
+        };
+
+        let mut flags = Document::new();
+        for (field, value) in [
+            ("hidden", update.hidden),
+            ("pinned", update.pinned),
+            ("featured", update.featured),
+        ] {
+            if let Some(value) = value {
+                flags.insert(field, value);
+            }
+        }
+        if flags.is_empty() {
+            return Ok(true);
+        }
+
+        let mut moderation = flags.clone();
+        moderation.insert("url", item.get_str("url").unwrap_or_default());
+        moderation.insert("updated_at", mongodb::bson::DateTime::now());
+
+        self.db
+            .collection::<Document>("rss_moderation")
+            .update_one(doc! { "item_id": id }, doc! { "$set": moderation })
+            .upsert(true)
+            .await?;
+        items
+            .update_many(doc! { "item_id": id }, doc! { "$set": flags })
+            .await?;
+
+        Ok(true)
+    }
+
+    /// Loads the editorial flags of every moderated item, keyed by item id
+    async fn load_moderation(&self) -> Result<HashMap<String, Document>> {
+        let cursor = self
+            .db
+            .collection::<Document>("rss_moderation")
+            .find(doc! {})
             .await?;
+        let docs: Vec<Document> = cursor.try_collect().await?;
 
-        let channel = Channel::read_from(&content[..])?;
-        Ok(channel)
+        Ok(docs
+            .into_iter()
+            .filter_map(|doc| {
+                let id = doc.get_str("item_id").ok()?.to_string();
+                Some((id, doc))
+            })
+            .collect())
     }
 
     /// Synchronizes RSS feeds from external sources to local database
     ///
     /// # Workflow
     /// 1. Connects to configured RSS source database
-    /// 2. Processes each feed URL sequentially
-    /// 3. Performs multi-stage parsing:
-    ///    - Channel metadata extraction
+    /// 2. Processes each feed sequentially, through the [`sources::ContentSource`]
+    ///    of its `kind` (RSS/Atom, Mastodon outbox or GitHub releases):
     ///    - Item content parsing
     ///    - Image URL detection
-    /// 4. Stores normalized data with TTL indexes
+    /// 3. Adds the published posts of the built-in blog (see [`PostsSource`])
+    /// 4. Applies the global and per-feed (`filters` field) include/exclude rules
+    /// 5. Optionally fills missing images and descriptions from the article pages
+    /// 6. Hides articles with dead links and drops dead images (see [`LinkChecker`])
+    /// 7. Links each article to its most similar articles (see [`related::compute`])
+    /// 8. Stores normalized data with TTL indexes
     ///
     /// # Error Handling
     /// Returns error if:
@@ -192,7 +625,22 @@ impl FeedService {
     /// # Notes
     /// - Failed individual feeds are logged but don't abort the process
     /// - Uses 90-day TTL for automatic data cleanup
-    pub async fn store_items(&self) -> Result<()> {
+    /// - The returned report counts the items dropped by each filter rule
+    pub async fn store_items(&self) -> Result<SyncReport> {
+        let global_rules = match &self.config.rss_global_rules_path {
+            Some(path) => RuleSet::from_json_file(path)
+                .map_err(|e| anyhow::anyhow!("Invalid global rules file {path}: {e}"))?,
+            None => RuleSet::default(),
+        };
+        let moderation = self.load_moderation().await?;
+        let dead_urls = LinkChecker::load_dead_urls(&self.db).await?;
+        let mut batch = SyncBatch {
+            moderation: &moderation,
+            dead_urls: &dead_urls,
+            report: SyncReport::default(),
+            articles: Vec::new(),
+        };
+
         // Connect to the source database
         let source_client = mongodb::Client::with_uri_str(&self.config.rss_source_url)
             .await?
@@ -204,46 +652,23 @@ impl FeedService {
 
         let target_collection = self.db.collection::<Document>("portfolio");
 
-        // Collection to store all articles
-        let mut all_articles = Vec::new();
-
         // Process each feed
         while let Some(feed_doc) = feeds_cursor.try_next().await? {
             let feed_link = feed_doc.get_str("link").unwrap_or_default();
             tracing::info!("Processing feed: {}", feed_link);
 
-            // Fetch and parse the RSS feed
-            match self.fetch_rss(feed_link).await {
-                Ok(channel) => {
-                    for item in channel.items().iter() {
-                        let title = item.title().unwrap_or_default();
-                        let link = item.link().unwrap_or_default();
-                        let description = item.description().unwrap_or_default();
-                        let pub_date = item
-                            .pub_date()
-                            .and_then(|date_str| DateTime::parse_from_rfc2822(date_str).ok())
-                            .map(|dt| dt.with_timezone(&Utc))
-                            .unwrap_or_else(Utc::now);
-
-                        let image_url = Self::extract_image_url(item).unwrap_or_else(|| {
-                            format!(
-                                "https://placehold.co/600x400/grey/white/png?text={}",
-                                urlencoding::encode(title)
-                            )
-                        });
-
-                        all_articles.push((
-                            pub_date,
-                            doc! {
-                                "title": title,
-                                "url": link,
-                                "pub_date": Bson::DateTime(mongodb::bson::DateTime::from_millis(pub_date.timestamp_millis())),
-                                "description": description,
-                                "image_url": image_url,
-                            },
-                        ));
-                    }
+            let feed_rules = RuleSet::from_feed_document(feed_link, &feed_doc);
+            let source = match SourceKind::from_feed_document(&feed_doc) {
+                Ok(kind) => sources::for_kind(kind, &self.config),
+                Err(e) => {
+                    tracing::error!("Skipping feed {}: {}", feed_link, e);
+                    continue;
                 }
+            };
+
+            // Fetch and normalize the source items
+            match source.fetch(&self.client, feed_link).await {
+                Ok(items) => batch.push_items(feed_link, &[&global_rules, &feed_rules], items),
                 Err(e) => {
                     tracing::error!("Error fetching feed {}: {}", feed_link, e);
                     continue;
@@ -251,9 +676,38 @@ impl FeedService {
             }
         }
 
+        // Posts of the built-in blog, which are not filtered
+        let blog_url = format!(
+            "{}{BLOG_PATH}",
+            self.config.frontend_url.trim_end_matches('/')
+        );
+        match PostsSource::new(self.db.clone())
+            .fetch(&self.client, &blog_url)
+            .await
+        {
+            Ok(items) => batch.push_items(&blog_url, &[], items),
+            Err(e) => tracing::error!("Error reading blog posts: {}", e),
+        }
+
+        let SyncBatch {
+            mut report,
+            articles: mut all_articles,
+            ..
+        } = batch;
+
         // Sort articles by date in descending order
         all_articles.sort_by(|a, b| b.0.cmp(&a.0));
 
+        if self.config.rss_og_enrichment {
+            self.enrich_from_pages(&mut all_articles).await;
+        }
+        Self::drop_dead_images(&mut all_articles, &dead_urls);
+        if self.config.image_proxy_cache_dir.is_some() {
+            Self::link_proxied_images(&mut all_articles);
+        }
+        self.apply_image_fallback(&mut all_articles);
+        Self::link_related(&mut all_articles);
+
         // Remove old articles
         target_collection.delete_many(doc! {}).await?;
 
@@ -261,13 +715,21 @@ impl FeedService {
         if !all_articles.is_empty() {
             let articles_to_insert: Vec<Document> =
                 all_articles.into_iter().map(|(_, doc)| doc).collect();
+            let count = articles_to_insert.len();
 
             match target_collection.insert_many(articles_to_insert).await {
-                Ok(_) => tracing::info!("Articles inserted successfully"),
+                Ok(_) => {
+                    report.stored = count;
+                    tracing::info!("Articles inserted successfully");
+                }
                 Err(e) => tracing::error!("Error inserting articles: {}", e),
             }
         }
 
-        Ok(())
+        for (label, count) in &report.dropped_by_rule {
+            tracing::info!("Filter rule {} dropped {} item(s)", label, count);
+        }
+
+        Ok(report)
     }
 }
@@ -0,0 +1,204 @@
+use anyhow::Result;
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::sync::Arc;
+use std::time::Duration;
+use url::Url;
+
+/// Maximum number of redirects followed
+const MAX_REDIRECTS: usize = 3;
+
+/// Whether an address can be reached on the public Internet
+///
+/// Rejects loopback, private, link-local, shared, documentation, multicast and
+/// reserved ranges, so that fetched URLs cannot target internal services.
+#[must_use]
+pub fn is_public_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => is_public_ipv4(ip),
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(mapped) => is_public_ipv4(mapped),
+            None => is_public_ipv6(ip),
+        },
+    }
+}
+
+fn is_public_ipv4(ip: Ipv4Addr) -> bool {
+    let [a, b, ..] = ip.octets();
+    !(ip.is_unspecified()
+        || ip.is_loopback()
+        || ip.is_private()
+        || ip.is_link_local()
+        || ip.is_broadcast()
+        || ip.is_documentation()
+        || ip.is_multicast()
+        || a == 0
+        // Shared address space (100.64.0.0/10)
+        || (a == 100 && (b & 0xc0) == 64)
+        // Reserved (240.0.0.0/4)
+        || a >= 240)
+}
+
+fn is_public_ipv6(ip: Ipv6Addr) -> bool {
+    let first = ip.segments()[0];
+    !(ip.is_unspecified()
+        || ip.is_loopback()
+        || ip.is_multicast()
+        // Unique local (fc00::/7)
+        || (first & 0xfe00) == 0xfc00
+        // Link-local (fe80::/10)
+        || (first & 0xffc0) == 0xfe80
+        // Documentation (2001:db8::/32)
+        || (first == 0x2001 && ip.segments()[1] == 0x0db8))
+}
+
+/// Checks that a URL is an http(s) URL which does not target a private host
+///
+/// # Errors
+///
+/// Returns error if the scheme is not http(s), the host is missing, is
+/// `localhost`, or is a non-public IP address.
+pub fn check_url(url: &Url) -> Result<()> {
+    if !matches!(url.scheme(), "http" | "https") {
+        anyhow::bail!("Unsupported scheme: {}", url.scheme());
+    }
+    match url.host() {
+        None => anyhow::bail!("Missing host"),
+        Some(url::Host::Domain(domain)) => {
+            let domain = domain.trim_end_matches('.').to_lowercase();
+            if domain == "localhost" || domain.ends_with(".localhost") {
+                anyhow::bail!("Local host not allowed");
+            }
+        }
+        Some(url::Host::Ipv4(ip)) if !is_public_ipv4(ip) => {
+            anyhow::bail!("Private address not allowed: {ip}");
+        }
+        Some(url::Host::Ipv6(ip)) if !is_public_ip(IpAddr::V6(ip)) => {
+            anyhow::bail!("Private address not allowed: {ip}");
+        }
+        Some(_) => {}
+    }
+    Ok(())
+}
+
+/// DNS resolver which drops non-public addresses
+///
+/// Checking the resolved addresses, rather than the host name, also covers
+/// names pointing to internal services and DNS rebinding.
+struct PublicOnlyResolver;
+
+impl Resolve for PublicOnlyResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        Box::pin(async move {
+            let addrs: Vec<SocketAddr> = tokio::net::lookup_host((name.as_str(), 0))
+                .await?
+                .filter(|addr| is_public_ip(addr.ip()))
+                .collect();
+            if addrs.is_empty() {
+                return Err(format!("No public address for {}", name.as_str()).into());
+            }
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+}
+
+/// Builds an HTTP client for fetching third-party URLs
+///
+/// The client only connects to public addresses, checks every redirect target
+/// and ignores proxy environment variables.
+#[must_use]
+pub fn guarded_client(timeout: Duration) -> reqwest::Client {
+    reqwest::Client::builder()
+        .timeout(timeout)
+        .no_proxy()
+        .dns_resolver(Arc::new(PublicOnlyResolver))
+        .redirect(reqwest::redirect::Policy::custom(|attempt| {
+            if attempt.previous().len() >= MAX_REDIRECTS {
+                attempt.error("Too many redirects")
+            } else if let Err(e) = check_url(attempt.url()) {
+                attempt.error(e.to_string())
+            } else {
+                attempt.follow()
+            }
+        }))
+        .build()
+        .unwrap_or_default()
+}
+
+/// Downloads a URL with a guarded client, failing beyond `max_bytes`
+///
+/// # Errors
+///
+/// Returns error if the URL is rejected by [`check_url`], the request fails,
+/// the status is not a success, or the body is larger than `max_bytes`.
+pub async fn fetch_limited(
+    client: &reqwest::Client,
+    url: &str,
+    accept: &str,
+    max_bytes: usize,
+) -> Result<Vec<u8>> {
+    check_url(&Url::parse(url)?)?;
+
+    let mut response = client
+        .get(url)
+        .header("User-Agent", "Mozilla/5.0 (compatible; RSSBot/1.0)")
+        .header("Accept", accept)
+        .send()
+        .await?
+        .error_for_status()?;
+
+    if response
+        .content_length()
+        .is_some_and(|length| length > max_bytes as u64)
+    {
+        anyhow::bail!("Response too large");
+    }
+
+    let mut body = Vec::new();
+    while let Some(chunk) = response.chunk().await? {
+        if body.len() + chunk.len() > max_bytes {
+            anyhow::bail!("Response too large");
+        }
+        body.extend_from_slice(&chunk);
+    }
+    Ok(body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_private_addresses_are_rejected() {
+        for ip in [
+            "127.0.0.1",
+            "10.1.2.3",
+            "172.16.0.1",
+            "192.168.1.1",
+            "169.254.169.254",
+            "100.64.0.1",
+            "0.0.0.0",
+            "::1",
+            "fd00::1",
+            "fe80::1",
+            "::ffff:127.0.0.1",
+        ] {
+            assert!(!is_public_ip(ip.parse().unwrap()), "{ip} should be private");
+        }
+        assert!(is_public_ip("93.184.216.34".parse().unwrap()));
+        assert!(is_public_ip("2606:4700::1111".parse().unwrap()));
+    }
+
+    #[test]
+    fn test_check_url() {
+        let check = |url: &str| check_url(&Url::parse(url).unwrap());
+
+        assert!(check("https://example.com/a.png").is_ok());
+        assert!(check("http://93.184.216.34/").is_ok());
+        assert!(check("file:///etc/passwd").is_err());
+        assert!(check("http://localhost:8080/").is_err());
+        assert!(check("http://api.localhost/").is_err());
+        assert!(check("http://127.0.0.1/").is_err());
+        assert!(check("http://[::1]/").is_err());
+    }
+}
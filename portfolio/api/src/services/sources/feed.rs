@@ -0,0 +1,235 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use rss::{Channel, Item};
+
+use super::{fetch_bytes, ContentSource, SourceItem};
+
+static IMG_SRC_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"(?i)<img[^>]+src=["'](https?://[^"']+)["']"#).unwrap());
+
+/// RSS 2.0 or Atom feed
+///
+/// The document is parsed as RSS first, then as Atom.
+pub struct FeedSource;
+
+/// Extracts image URL from RSS item enclosure
+fn extract_from_enclosure(item: &Item) -> Option<String> {
+    item.enclosure()
+        .filter(|enclosure| enclosure.mime_type.starts_with("image/"))
+        .map(|enclosure| enclosure.url.clone())
+}
+
+/// Extracts image URL from RSS item media extension (content)
+fn extract_from_media_content(item: &Item) -> Option<String> {
+    item.extensions
+        .get("media")?
+        .get("content")?
+        .first()?
+        .attrs
+        .get("url")
+        .cloned()
+}
+
+/// Extracts image URL from RSS item media extension (thumbnail)
+fn extract_from_media_thumbnail(item: &Item) -> Option<String> {
+    item.extensions
+        .get("media")?
+        .get("thumbnail")?
+        .first()?
+        .attrs
+        .get("url")
+        .cloned()
+}
+
+/// Extracts image URL from HTML using regex
+fn extract_from_html(html: &str) -> Option<String> {
+    IMG_SRC_RE
+        .captures(html)
+        .and_then(|cap| cap.get(1))
+        .map(|src| src.as_str().to_string())
+}
+
+/// Extracts the image URL from an RSS article item
+///
+/// Tries multiple sources in order:
+/// 1. RSS enclosure (image type)
+/// 2. Media extension content
+/// 3. Media extension thumbnail
+/// 4. HTML description (regex search)
+fn extract_image_url(item: &Item) -> Option<String> {
+    extract_from_enclosure(item)
+        .or_else(|| extract_from_media_content(item))
+        .or_else(|| extract_from_media_thumbnail(item))
+        .or_else(|| item.description().and_then(extract_from_html))
+}
+
+fn from_rss_item(item: &Item) -> SourceItem {
+    SourceItem {
+        title: item.title().unwrap_or_default().to_string(),
+        url: item.link().unwrap_or_default().to_string(),
+        pub_date: item
+            .pub_date()
+            .and_then(|date_str| DateTime::parse_from_rfc2822(date_str).ok())
+            .map(|dt| dt.with_timezone(&Utc)),
+        description: item.description().unwrap_or_default().to_string(),
+        categories: item
+            .categories()
+            .iter()
+            .map(|category| category.name().to_string())
+            .collect(),
+        image_url: extract_image_url(item),
+    }
+}
+
+/// Extracts the image URL from an Atom entry
+///
+/// Tries the media extension, then image enclosure links, then the HTML content.
+fn extract_atom_image(entry: &atom_syndication::Entry, html: &str) -> Option<String> {
+    let media = entry.extensions().get("media");
+    ["thumbnail", "content"]
+        .iter()
+        .find_map(|name| media?.get(*name)?.first()?.attrs().get("url").cloned())
+        .or_else(|| {
+            entry
+                .links()
+                .iter()
+                .find(|link| {
+                    link.rel() == "enclosure"
+                        && link.mime_type().is_some_and(|t| t.starts_with("image/"))
+                })
+                .map(|link| link.href().to_string())
+        })
+        .or_else(|| extract_from_html(html))
+}
+
+fn from_atom_entry(entry: &atom_syndication::Entry) -> SourceItem {
+    let description = entry
+        .summary()
+        .map(|summary| summary.as_str().to_string())
+        .or_else(|| {
+            entry
+                .content()
+                .and_then(|content| content.value().map(str::to_string))
+        })
+        .unwrap_or_default();
+    let url = entry
+        .links()
+        .iter()
+        .find(|link| link.rel() == "alternate")
+        .or_else(|| entry.links().first())
+        .map(|link| link.href().to_string())
+        .unwrap_or_default();
+
+    SourceItem {
+        title: entry.title().as_str().to_string(),
+        url,
+        pub_date: Some(
+            entry
+                .published()
+                .copied()
+                .unwrap_or_else(|| *entry.updated())
+                .with_timezone(&Utc),
+        ),
+        image_url: extract_atom_image(entry, &description),
+        categories: entry
+            .categories()
+            .iter()
+            .map(|category| {
+                category
+                    .label()
+                    .unwrap_or_else(|| category.term())
+                    .to_string()
+            })
+            .collect(),
+        description,
+    }
+}
+
+/// Parses an RSS or Atom document
+///
+/// # Errors
+///
+/// Returns error if the document is neither valid RSS nor valid Atom.
+pub fn parse(content: &[u8]) -> Result<Vec<SourceItem>> {
+    if let Ok(channel) = Channel::read_from(content) {
+        return Ok(channel.items().iter().map(from_rss_item).collect());
+    }
+    let feed = atom_syndication::Feed::read_from(content)
+        .map_err(|e| anyhow::anyhow!("Neither RSS nor Atom: {e}"))?;
+    Ok(feed.entries().iter().map(from_atom_entry).collect())
+}
+
+#[async_trait]
+impl ContentSource for FeedSource {
+    async fn fetch(&self, client: &reqwest::Client, location: &str) -> Result<Vec<SourceItem>> {
+        let content = fetch_bytes(
+            client,
+            location,
+            "application/rss+xml, application/atom+xml, application/xml;q=0.9, */*;q=0.8",
+        )
+        .await?;
+        parse(&content)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_atom() {
+        let atom = r#"<?xml version="1.0" encoding="utf-8"?>
+            <feed xmlns="http://www.w3.org/2005/Atom">
+              <title>Blog</title><id>urn:blog</id><updated>2024-05-01T10:00:00Z</updated>
+              <entry>
+                <title>Hello Atom</title><id>urn:1</id>
+                <updated>2024-05-02T10:00:00Z</updated>
+                <published>2024-05-01T08:00:00Z</published>
+                <link rel="alternate" href="https://blog.example.com/hello"/>
+                <link rel="enclosure" type="image/png" href="https://blog.example.com/cover.png"/>
+                <category term="rust"/>
+                <summary>First post</summary>
+              </entry>
+            </feed>"#;
+
+        let items = parse(atom.as_bytes()).unwrap();
+
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].title, "Hello Atom");
+        assert_eq!(items[0].url, "https://blog.example.com/hello");
+        assert_eq!(
+            items[0].pub_date.unwrap().to_rfc3339(),
+            "2024-05-01T08:00:00+00:00"
+        );
+        assert_eq!(
+            items[0].image_url.as_deref(),
+            Some("https://blog.example.com/cover.png")
+        );
+        assert_eq!(items[0].categories, vec!["rust"]);
+        assert_eq!(items[0].description, "First post");
+    }
+
+    #[test]
+    fn test_parse_rss_image_from_description() {
+        let rss = r#"<rss version="2.0"><channel><title>t</title><link>l</link><description>d</description>
+            <item><title>Post</title><link>https://example.com/p</link>
+            <description>&lt;img src="https://example.com/i.jpg"&gt;</description></item>
+            </channel></rss>"#;
+
+        let items = parse(rss.as_bytes()).unwrap();
+
+        assert_eq!(
+            items[0].image_url.as_deref(),
+            Some("https://example.com/i.jpg")
+        );
+        assert!(items[0].pub_date.is_none());
+    }
+
+    #[test]
+    fn test_parse_invalid_document() {
+        assert!(parse(b"<html></html>").is_err());
+    }
+}
@@ -0,0 +1,129 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+
+use super::{fetch_bytes, ContentSource, SourceItem};
+
+/// Maximum length of the release notes kept as description
+const MAX_DESCRIPTION_CHARS: usize = 1000;
+
+/// Published releases of a GitHub repository
+///
+/// The feed `link` is the repository URL (`https://github.com/owner/repo`) or
+/// `owner/repo`. Drafts are skipped.
+pub struct GitHubReleasesSource {
+    api_base_url: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Release {
+    name: Option<String>,
+    tag_name: String,
+    html_url: String,
+    body: Option<String>,
+    #[serde(default)]
+    draft: bool,
+    #[serde(default)]
+    prerelease: bool,
+    published_at: Option<DateTime<Utc>>,
+}
+
+/// Extracts `owner/repo` from a repository URL or slug
+///
+/// # Errors
+///
+/// Returns error if the location does not name a repository.
+pub fn repository(location: &str) -> Result<String> {
+    let path = location
+        .trim()
+        .trim_start_matches("https://")
+        .trim_start_matches("http://")
+        .trim_start_matches("www.")
+        .trim_start_matches("github.com/")
+        .trim_end_matches('/')
+        .trim_end_matches(".git");
+    let mut parts = path.split('/');
+    match (parts.next(), parts.next()) {
+        (Some(owner), Some(repo)) if !owner.is_empty() && !repo.is_empty() => {
+            Ok(format!("{owner}/{repo}"))
+        }
+        _ => anyhow::bail!("Not a GitHub repository: {location}"),
+    }
+}
+
+impl GitHubReleasesSource {
+    #[must_use]
+    pub fn new(api_base_url: impl Into<String>) -> Self {
+        Self {
+            api_base_url: api_base_url.into(),
+        }
+    }
+
+    fn to_item(repository: &str, release: Release) -> Option<SourceItem> {
+        if release.draft {
+            return None;
+        }
+        let name = release
+            .name
+            .filter(|name| !name.trim().is_empty())
+            .unwrap_or_else(|| release.tag_name.clone());
+        let repo_name = repository.rsplit('/').next().unwrap_or(repository);
+        let mut categories = vec!["release".to_string()];
+        if release.prerelease {
+            categories.push("prerelease".to_string());
+        }
+
+        Some(SourceItem {
+            title: if name.contains(repo_name) {
+                name
+            } else {
+                format!("{repo_name} {name}")
+            },
+            url: release.html_url,
+            pub_date: release.published_at,
+            description: release
+                .body
+                .unwrap_or_default()
+                .chars()
+                .take(MAX_DESCRIPTION_CHARS)
+                .collect(),
+            categories,
+            image_url: None,
+        })
+    }
+}
+
+#[async_trait]
+impl ContentSource for GitHubReleasesSource {
+    async fn fetch(&self, client: &reqwest::Client, location: &str) -> Result<Vec<SourceItem>> {
+        let repository = repository(location)?;
+        let url = format!(
+            "{}/repos/{repository}/releases",
+            self.api_base_url.trim_end_matches('/')
+        );
+        let releases: Vec<Release> = serde_json::from_slice(
+            &fetch_bytes(client, &url, "application/vnd.github+json").await?,
+        )?;
+
+        Ok(releases
+            .into_iter()
+            .filter_map(|release| Self::to_item(&repository, release))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_repository() {
+        assert_eq!(
+            repository("https://github.com/rust-lang/rust/").unwrap(),
+            "rust-lang/rust"
+        );
+        assert_eq!(repository("tokio-rs/axum").unwrap(), "tokio-rs/axum");
+        assert!(repository("https://github.com/rust-lang").is_err());
+    }
+}
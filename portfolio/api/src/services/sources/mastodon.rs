@@ -0,0 +1,217 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde_json::Value;
+use url::Url;
+
+use super::{fetch_bytes, ContentSource, SourceItem};
+
+const ACTIVITY_JSON: &str = "application/activity+json";
+/// Maximum length of the title derived from a post
+const MAX_TITLE_CHARS: usize = 80;
+
+static TAG_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"<[^>]*>").unwrap());
+static SPACES_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"\s+").unwrap());
+
+/// Public posts of a Mastodon account, read from its `ActivityPub` outbox
+///
+/// The feed `link` is either the outbox URL (`https://host/users/name/outbox`)
+/// or the profile URL (`https://host/@name`). Boosts and replies are skipped.
+pub struct MastodonSource;
+
+/// Returns the outbox URL of a profile or outbox URL
+///
+/// # Errors
+///
+/// Returns error if the URL cannot be parsed.
+pub fn outbox_url(location: &str) -> Result<String> {
+    let mut url = Url::parse(location)?;
+    let path = url.path().trim_end_matches('/').to_string();
+    if path.ends_with("/outbox") {
+        return Ok(url.to_string());
+    }
+    if let Some(name) = path.strip_prefix("/@") {
+        url.set_path(&format!("/users/{name}/outbox"));
+    } else {
+        url.set_path(&format!("{path}/outbox"));
+    }
+    url.set_query(None);
+    Ok(url.to_string())
+}
+
+/// Converts post HTML into a one-line title
+fn title_from_html(html: &str) -> String {
+    let html = html.replace("</p>", " ");
+    let text = TAG_RE
+        .replace_all(&html, "")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&amp;", "&");
+    let text = SPACES_RE.replace_all(text.trim(), " ");
+
+    if text.chars().count() > MAX_TITLE_CHARS {
+        let truncated: String = text.chars().take(MAX_TITLE_CHARS - 1).collect();
+        format!("{}…", truncated.trim_end())
+    } else {
+        text.into_owned()
+    }
+}
+
+/// Normalizes the `Note` of a `Create` activity
+fn from_activity(activity: &Value) -> Option<SourceItem> {
+    if activity.get("type")?.as_str()? != "Create" {
+        return None;
+    }
+    let note = activity.get("object")?;
+    if note.get("type")?.as_str()? != "Note" || !note["inReplyTo"].is_null() {
+        return None;
+    }
+
+    let content = note.get("content")?.as_str()?.to_string();
+    let url = note
+        .get("url")
+        .and_then(Value::as_str)
+        .or_else(|| note.get("id").and_then(Value::as_str))?
+        .to_string();
+    let title = note
+        .get("summary")
+        .and_then(Value::as_str)
+        .filter(|summary| !summary.is_empty())
+        .map_or_else(|| title_from_html(&content), str::to_string);
+
+    let categories = note
+        .get("tag")
+        .and_then(Value::as_array)
+        .map(|tags| {
+            tags.iter()
+                .filter(|tag| tag["type"] == "Hashtag")
+                .filter_map(|tag| tag["name"].as_str())
+                .map(|name| name.trim_start_matches('#').to_string())
+                .collect()
+        })
+        .unwrap_or_default();
+    let image_url = note
+        .get("attachment")
+        .and_then(Value::as_array)
+        .and_then(|attachments| {
+            attachments.iter().find_map(|attachment| {
+                attachment["mediaType"]
+                    .as_str()
+                    .filter(|media_type| media_type.starts_with("image/"))
+                    .and(attachment["url"].as_str())
+                    .map(str::to_string)
+            })
+        });
+
+    Some(SourceItem {
+        title,
+        url,
+        pub_date: note
+            .get("published")
+            .and_then(Value::as_str)
+            .and_then(|date| DateTime::parse_from_rfc3339(date).ok())
+            .map(|date| date.with_timezone(&Utc)),
+        description: content,
+        categories,
+        image_url,
+    })
+}
+
+/// Normalizes the activities of an outbox page
+#[must_use]
+pub fn parse_page(page: &Value) -> Vec<SourceItem> {
+    page.get("orderedItems")
+        .and_then(Value::as_array)
+        .map(|activities| activities.iter().filter_map(from_activity).collect())
+        .unwrap_or_default()
+}
+
+#[async_trait]
+impl ContentSource for MastodonSource {
+    async fn fetch(&self, client: &reqwest::Client, location: &str) -> Result<Vec<SourceItem>> {
+        let outbox_url = outbox_url(location)?;
+        let outbox: Value =
+            serde_json::from_slice(&fetch_bytes(client, &outbox_url, ACTIVITY_JSON).await?)?;
+
+        // The first page is either embedded or referenced by URL
+        let page = match outbox.get("first") {
+            Some(Value::String(page_url)) => {
+                serde_json::from_slice(&fetch_bytes(client, page_url, ACTIVITY_JSON).await?)?
+            }
+            Some(page) => page.clone(),
+            None => outbox,
+        };
+
+        Ok(parse_page(&page))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_outbox_url() {
+        assert_eq!(
+            outbox_url("https://mastodon.social/@alice").unwrap(),
+            "https://mastodon.social/users/alice/outbox"
+        );
+        assert_eq!(
+            outbox_url("https://mastodon.social/users/alice/outbox").unwrap(),
+            "https://mastodon.social/users/alice/outbox"
+        );
+    }
+
+    #[test]
+    fn test_parse_page_skips_boosts_and_replies() {
+        let page = json!({
+            "orderedItems": [
+                {
+                    "type": "Create",
+                    "object": {
+                        "type": "Note",
+                        "url": "https://mastodon.social/@alice/1",
+                        "published": "2024-05-01T08:00:00Z",
+                        "content": "<p>Shipping a new <a href=\"x\">#rust</a> crate &amp; more</p>",
+                        "inReplyTo": null,
+                        "tag": [{ "type": "Hashtag", "name": "#rust" }],
+                        "attachment": [{ "mediaType": "image/png", "url": "https://files.example/a.png" }]
+                    }
+                },
+                { "type": "Announce", "object": "https://elsewhere.example/2" },
+                {
+                    "type": "Create",
+                    "object": {
+                        "type": "Note",
+                        "id": "https://mastodon.social/users/alice/statuses/3",
+                        "content": "<p>reply</p>",
+                        "inReplyTo": "https://elsewhere.example/2"
+                    }
+                }
+            ]
+        });
+
+        let items = parse_page(&page);
+
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].title, "Shipping a new #rust crate & more");
+        assert_eq!(items[0].categories, vec!["rust"]);
+        assert_eq!(
+            items[0].image_url.as_deref(),
+            Some("https://files.example/a.png")
+        );
+    }
+
+    #[test]
+    fn test_long_titles_are_truncated() {
+        let title = title_from_html(&format!("<p>{}</p>", "word ".repeat(40)));
+
+        assert!(title.ends_with('…'));
+        assert!(title.chars().count() <= MAX_TITLE_CHARS);
+    }
+}
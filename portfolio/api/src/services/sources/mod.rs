@@ -0,0 +1,126 @@
+//! Content sources ingested by the sync
+//!
+//! Each entry of the `feeds` collection names a source with its `link` and its
+//! `kind` (`rss` when missing). The posts of the built-in blog are always added
+//! through [`PostsSource`]. Every source is normalized into [`SourceItem`].
+
+pub mod feed;
+pub mod github;
+pub mod mastodon;
+pub mod posts;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use mongodb::bson::Document;
+use serde::Deserialize;
+
+use crate::config::Config;
+
+pub use feed::FeedSource;
+pub use github::GitHubReleasesSource;
+pub use mastodon::MastodonSource;
+pub use posts::PostsSource;
+
+/// Item of any source, before filtering and storage
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SourceItem {
+    pub title: String,
+    pub url: String,
+    pub pub_date: Option<DateTime<Utc>>,
+    /// HTML or plain text summary
+    pub description: String,
+    pub categories: Vec<String>,
+    pub image_url: Option<String>,
+}
+
+/// Kind of source, read from the `kind` field of a feed document
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SourceKind {
+    /// RSS 2.0 or Atom feed
+    #[default]
+    Rss,
+    /// `ActivityPub` outbox of a Mastodon account
+    Mastodon,
+    /// Releases of a GitHub repository
+    GithubReleases,
+}
+
+impl std::str::FromStr for SourceKind {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "rss" | "atom" => Ok(Self::Rss),
+            "mastodon" => Ok(Self::Mastodon),
+            "github_releases" => Ok(Self::GithubReleases),
+            other => anyhow::bail!("Unknown source kind: {other}"),
+        }
+    }
+}
+
+impl SourceKind {
+    /// Reads the kind of a feed document, defaulting to RSS
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the `kind` field names an unknown kind.
+    pub fn from_feed_document(feed: &Document) -> Result<Self> {
+        feed.get_str("kind").map_or(Ok(Self::Rss), str::parse)
+    }
+}
+
+/// Source of items, fetched on every sync
+#[async_trait]
+pub trait ContentSource: Send + Sync {
+    /// Fetches the latest items published at `location` (the feed `link`)
+    async fn fetch(&self, client: &reqwest::Client, location: &str) -> Result<Vec<SourceItem>>;
+}
+
+/// Builds the source implementing a kind
+#[must_use]
+pub fn for_kind(kind: SourceKind, config: &Config) -> Box<dyn ContentSource> {
+    match kind {
+        SourceKind::Rss => Box::new(FeedSource),
+        SourceKind::Mastodon => Box::new(MastodonSource),
+        SourceKind::GithubReleases => {
+            Box::new(GitHubReleasesSource::new(config.github_api_url.clone()))
+        }
+    }
+}
+
+/// Downloads a source document
+pub(crate) async fn fetch_bytes(
+    client: &reqwest::Client,
+    url: &str,
+    accept: &str,
+) -> Result<Vec<u8>> {
+    let response = client
+        .get(url)
+        .header("User-Agent", "Mozilla/5.0 (compatible; RSSBot/1.0)")
+        .header("Accept", accept)
+        .send()
+        .await?
+        .error_for_status()?;
+    Ok(response.bytes().await?.to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mongodb::bson::doc;
+
+    #[test]
+    fn test_kind_from_feed_document() {
+        assert_eq!(
+            SourceKind::from_feed_document(&doc! { "link": "x" }).unwrap(),
+            SourceKind::Rss
+        );
+        assert_eq!(
+            SourceKind::from_feed_document(&doc! { "kind": "github_releases" }).unwrap(),
+            SourceKind::GithubReleases
+        );
+        assert!(SourceKind::from_feed_document(&doc! { "kind": "gopher" }).is_err());
+    }
+}
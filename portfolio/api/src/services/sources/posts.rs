@@ -0,0 +1,45 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use mongodb::Database;
+
+use super::{ContentSource, SourceItem};
+use crate::services::posts::PostService;
+
+/// Published posts of the built-in blog
+///
+/// Unlike the other sources, the posts are read from the local `posts`
+/// collection. The `location` is the public URL of the blog, to which the post
+/// slugs are appended.
+pub struct PostsSource {
+    posts: PostService,
+}
+
+impl PostsSource {
+    #[must_use]
+    pub const fn new(db: Database) -> Self {
+        Self {
+            posts: PostService::new(db),
+        }
+    }
+}
+
+#[async_trait]
+impl ContentSource for PostsSource {
+    async fn fetch(&self, _client: &reqwest::Client, location: &str) -> Result<Vec<SourceItem>> {
+        let base_url = location.trim_end_matches('/');
+        Ok(self
+            .posts
+            .published()
+            .await?
+            .into_iter()
+            .map(|post| SourceItem {
+                url: format!("{base_url}/{}", post.slug),
+                title: post.title,
+                pub_date: post.published_at,
+                description: post.summary,
+                categories: post.tags,
+                image_url: post.image_url,
+            })
+            .collect())
+    }
+}
@@ -0,0 +1,643 @@
+use anyhow::Result;
+use futures_util::TryStreamExt;
+use mongodb::bson::{doc, oid::ObjectId, Document};
+use mongodb::Database;
+use once_cell::sync::Lazy;
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, RwLock};
+
+use crate::config::Config;
+use crate::services::bayes::{tokenize, NaiveBayes};
+
+/// Collection of the messages labelled by an administrator, one per contact
+const TRAINING_COLLECTION: &str = "spam_training";
+
+static LINK_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)\b(?:https?://|www\.)([a-z0-9\p{L}-]+(?:\.[a-z0-9\p{L}-]+)+)").unwrap()
+});
+
+const EN_STOPWORDS: [&str; 24] = [
+    "the", "and", "is", "are", "you", "your", "with", "for", "this", "that", "have", "will",
+    "would", "can", "about", "from", "not", "we", "our", "to", "of", "it", "be", "on",
+];
+const FR_STOPWORDS: [&str; 24] = [
+    "le", "la", "les", "et", "est", "sont", "vous", "votre", "avec", "pour", "ce", "cette", "que",
+    "qui", "pas", "nous", "notre", "une", "des", "du", "dans", "je", "sur", "mais",
+];
+
+fn default_keywords() -> Vec<KeywordSpec> {
+    [
+        "viagra",
+        "cialis",
+        "casino",
+        "crypto",
+        "bitcoin",
+        "forex",
+        "backlinks",
+        "seo services",
+        "first page of google",
+        "guest post",
+        "web traffic",
+        "payday",
+        "lottery",
+        "inheritance",
+        "investment opportunity",
+        "earn money",
+        "work from home",
+        "100% free",
+        "gagner de l'argent",
+        "crédit rapide",
+        "référencement garanti",
+    ]
+    .into_iter()
+    .map(|keyword| KeywordSpec {
+        keyword: Some(keyword.to_string()),
+        regex: None,
+        score: default_keyword_score(),
+    })
+    .collect()
+}
+
+fn default_blocked_domains() -> Vec<String> {
+    [
+        "bit.ly",
+        "tinyurl.com",
+        "t.ly",
+        "goo.gl",
+        "is.gd",
+        "cutt.ly",
+        "rebrand.ly",
+    ]
+    .into_iter()
+    .map(str::to_string)
+    .collect()
+}
+
+const fn default_keyword_score() -> f64 {
+    2.0
+}
+
+/// Keyword or regex adding to the score of the messages it matches
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeywordSpec {
+    /// Case-insensitive substring
+    #[serde(default)]
+    pub keyword: Option<String>,
+    #[serde(default)]
+    pub regex: Option<String>,
+    #[serde(default = "default_keyword_score")]
+    pub score: f64,
+}
+
+/// Points of the other signals
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SpamWeights {
+    /// Per link to a blocked domain
+    pub blocked_domain: f64,
+    /// Per link to a domain neither blocked nor trusted
+    pub unknown_link: f64,
+    /// Message written in another language than the form
+    pub language_mismatch: f64,
+    /// Words mixing scripts, or invisible characters
+    pub confusables: f64,
+    /// Points of a certain spam for the classifier, removed for a certain ham
+    pub bayes: f64,
+}
+
+impl Default for SpamWeights {
+    fn default() -> Self {
+        Self {
+            blocked_domain: 4.0,
+            unknown_link: 1.0,
+            language_mismatch: 1.5,
+            confusables: 3.0,
+            bayes: 4.0,
+        }
+    }
+}
+
+/// Spam rules as stored in the `CONTACT_SPAM_RULES_PATH` file
+///
+/// Missing lists keep their built-in defaults.
+///
+/// ```json
+/// { "keywords": [{ "keyword": "casino", "score": 3 }, { "regex": "(?i)\\bseo\\b" }],
+///   "blocked_domains": ["bit.ly"], "trusted_domains": ["github.com"],
+///   "weights": { "unknown_link": 0.5 } }
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpamRulesSpec {
+    #[serde(default = "default_keywords")]
+    pub keywords: Vec<KeywordSpec>,
+    /// Domains matched with their subdomains
+    #[serde(default = "default_blocked_domains")]
+    pub blocked_domains: Vec<String>,
+    #[serde(default)]
+    pub trusted_domains: Vec<String>,
+    #[serde(default)]
+    pub weights: SpamWeights,
+}
+
+impl Default for SpamRulesSpec {
+    fn default() -> Self {
+        Self {
+            keywords: default_keywords(),
+            blocked_domains: default_blocked_domains(),
+            trusted_domains: Vec::new(),
+            weights: SpamWeights::default(),
+        }
+    }
+}
+
+#[derive(Debug)]
+enum Matcher {
+    Keyword(String),
+    Regex(Regex),
+}
+
+#[derive(Debug)]
+struct KeywordRule {
+    label: String,
+    matcher: Matcher,
+    score: f64,
+}
+
+/// Message evaluated by [`SpamRules`]
+#[derive(Debug, Clone, Copy)]
+pub struct SpamInput<'a> {
+    pub subject: &'a str,
+    pub message: &'a str,
+    /// Language of the form, if sent
+    pub language: Option<&'a str>,
+}
+
+impl SpamInput<'_> {
+    /// Tokens of the classifier: words and linked domains
+    #[must_use]
+    pub fn tokens(&self) -> Vec<String> {
+        let mut tokens = tokenize(self.subject);
+        tokens.extend(tokenize(self.message));
+        tokens.extend(
+            link_domains(self.message)
+                .into_iter()
+                .map(|domain| format!("domain:{domain}")),
+        );
+        tokens
+    }
+}
+
+/// Score of a message and the signals behind it
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SpamReport {
+    pub score: f64,
+    pub reasons: Vec<String>,
+}
+
+impl SpamReport {
+    fn add(&mut self, score: f64, reason: String) {
+        self.score += score;
+        self.reasons.push(reason);
+    }
+}
+
+/// Domains of the links of a text, lowercased
+#[must_use]
+pub fn link_domains(text: &str) -> Vec<String> {
+    LINK_RE
+        .captures_iter(text)
+        .map(|caps| caps[1].trim_end_matches('.').to_lowercase())
+        .collect()
+}
+
+fn matches_domain(domain: &str, list: &[String]) -> bool {
+    list.iter().any(|listed| {
+        domain == listed
+            || domain
+                .strip_suffix(listed.as_str())
+                .is_some_and(|sub| sub.ends_with('.'))
+    })
+}
+
+/// Guesses the language of a text from its stopwords: `fr`, `en`, or
+/// `other` for a text mostly written in a non-Latin script
+#[must_use]
+pub fn detect_language(text: &str) -> Option<&'static str> {
+    let letters = text.chars().filter(|c| c.is_alphabetic()).count();
+    let latin = text
+        .chars()
+        .filter(|c| c.is_alphabetic() && is_latin(*c))
+        .count();
+    if letters >= 10 && latin * 2 < letters {
+        return Some("other");
+    }
+
+    let tokens = tokenize(text);
+    let count = |stopwords: &[&str]| {
+        tokens
+            .iter()
+            .filter(|token| stopwords.contains(&token.as_str()))
+            .count()
+    };
+    let (en, fr) = (count(&EN_STOPWORDS), count(&FR_STOPWORDS));
+    if en + fr < 3 {
+        None
+    } else if en >= 2 * fr.max(1) {
+        Some("en")
+    } else if fr >= 2 * en.max(1) {
+        Some("fr")
+    } else {
+        None
+    }
+}
+
+const fn is_latin(c: char) -> bool {
+    matches!(c, 'A'..='Z' | 'a'..='z' | '\u{C0}'..='\u{24F}' | '\u{1E00}'..='\u{1EFF}')
+}
+
+/// Greek, Cyrillic, fullwidth or mathematical letter that can pass for a Latin one
+const fn is_lookalike(c: char) -> bool {
+    matches!(
+        c,
+        '\u{370}'..='\u{3FF}'
+            | '\u{400}'..='\u{4FF}'
+            | '\u{FF21}'..='\u{FF3A}'
+            | '\u{FF41}'..='\u{FF5A}'
+            | '\u{1D400}'..='\u{1D7FF}'
+    )
+}
+
+/// Returns true for words mixing Latin and lookalike letters, or invisible characters
+#[must_use]
+pub fn has_confusables(text: &str) -> bool {
+    let invisible = text.chars().any(|c| {
+        matches!(
+            c,
+            '\u{200B}'..='\u{200F}' | '\u{2060}' | '\u{FEFF}' | '\u{AD}'
+        )
+    });
+    invisible
+        || text
+            .split_whitespace()
+            .any(|word| word.chars().any(is_latin) && word.chars().any(is_lookalike))
+}
+
+/// Compiled spam rules
+#[derive(Debug)]
+pub struct SpamRules {
+    keywords: Vec<KeywordRule>,
+    blocked_domains: Vec<String>,
+    trusted_domains: Vec<String>,
+    weights: SpamWeights,
+}
+
+impl Default for SpamRules {
+    fn default() -> Self {
+        Self::compile(&SpamRulesSpec::default())
+    }
+}
+
+impl SpamRules {
+    /// Compiles a rule specification, skipping invalid keywords with a warning
+    #[must_use]
+    pub fn compile(spec: &SpamRulesSpec) -> Self {
+        let keywords = spec
+            .keywords
+            .iter()
+            .filter_map(|keyword| {
+                let (matcher, label) = match (&keyword.keyword, &keyword.regex) {
+                    (Some(word), None) => (
+                        Matcher::Keyword(word.to_lowercase()),
+                        format!("keyword:{word}"),
+                    ),
+                    (None, Some(pattern)) => {
+                        match RegexBuilder::new(pattern).size_limit(1 << 20).build() {
+                            Ok(regex) => (Matcher::Regex(regex), format!("regex:{pattern}")),
+                            Err(e) => {
+                                tracing::warn!("Ignoring invalid spam regex {}: {}", pattern, e);
+                                return None;
+                            }
+                        }
+                    }
+                    _ => {
+                        tracing::warn!(
+                            "Ignoring spam keyword without exactly one of `keyword` or `regex`"
+                        );
+                        return None;
+                    }
+                };
+                Some(KeywordRule {
+                    label,
+                    matcher,
+                    score: keyword.score,
+                })
+            })
+            .collect();
+        let normalize = |domains: &[String]| {
+            domains
+                .iter()
+                .map(|domain| domain.trim().trim_end_matches('.').to_lowercase())
+                .filter(|domain| !domain.is_empty())
+                .collect()
+        };
+
+        Self {
+            keywords,
+            blocked_domains: normalize(&spec.blocked_domains),
+            trusted_domains: normalize(&spec.trusted_domains),
+            weights: spec.weights.clone(),
+        }
+    }
+
+    /// Loads rules from a JSON file
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the file cannot be read or is not a valid rules document.
+    pub fn from_json_file(path: &str) -> Result<Self> {
+        let content = std::fs::read_to_string(path)?;
+        let spec: SpamRulesSpec = serde_json::from_str(&content)?;
+        Ok(Self::compile(&spec))
+    }
+
+    /// Scores a message, with the classifier when it is trained
+    #[must_use]
+    pub fn evaluate(&self, input: &SpamInput<'_>, model: &NaiveBayes) -> SpamReport {
+        let mut report = SpamReport::default();
+        let text = format!("{}\n{}", input.subject, input.message);
+        let lowercase = text.to_lowercase();
+
+        for rule in &self.keywords {
+            let matched = match &rule.matcher {
+                Matcher::Keyword(keyword) => lowercase.contains(keyword),
+                Matcher::Regex(regex) => regex.is_match(&text),
+            };
+            if matched {
+                report.add(rule.score, rule.label.clone());
+            }
+        }
+
+        for domain in link_domains(input.message) {
+            if matches_domain(&domain, &self.blocked_domains) {
+                report.add(
+                    self.weights.blocked_domain,
+                    format!("blocked_domain:{domain}"),
+                );
+            } else if !matches_domain(&domain, &self.trusted_domains) {
+                report.add(self.weights.unknown_link, format!("link:{domain}"));
+            }
+        }
+
+        if let (Some(expected), Some(detected)) = (input.language, detect_language(input.message)) {
+            let expected = expected
+                .split(['-', '_'])
+                .next()
+                .unwrap_or_default()
+                .to_lowercase();
+            if !expected.is_empty() && expected != detected {
+                report.add(
+                    self.weights.language_mismatch,
+                    format!("language:{detected}≠{expected}"),
+                );
+            }
+        }
+
+        if has_confusables(&text) {
+            report.add(self.weights.confusables, "confusables".to_string());
+        }
+
+        if let Some(probability) = model.spam_probability(&input.tokens()) {
+            report.add(
+                self.weights.bayes * (2.0 * probability - 1.0),
+                format!("bayes:{probability:.2}"),
+            );
+        }
+
+        report
+    }
+}
+
+/// Content-based spam filter of the contact form
+///
+/// Messages scoring at least the threshold are quarantined. The naive Bayes
+/// classifier is rebuilt from the `spam_training` collection, which holds the
+/// tokens of every message labelled by an administrator.
+pub struct SpamFilter {
+    db: Database,
+    rules: SpamRules,
+    threshold: f64,
+    model: RwLock<Option<Arc<NaiveBayes>>>,
+}
+
+impl SpamFilter {
+    #[must_use]
+    pub fn new(db: Database, rules: SpamRules, threshold: f64) -> Self {
+        Self {
+            db,
+            rules,
+            threshold,
+            model: RwLock::new(None),
+        }
+    }
+
+    /// Filter with the rules of `CONTACT_SPAM_RULES_PATH`, the built-in ones otherwise
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the rules file is invalid.
+    pub fn from_config(db: Database, config: &Config) -> Result<Self> {
+        let rules = match &config.contact_spam_rules_path {
+            Some(path) => SpamRules::from_json_file(path)
+                .map_err(|e| anyhow::anyhow!("Invalid spam rules file {path}: {e}"))?,
+            None => SpamRules::default(),
+        };
+        Ok(Self::new(db, rules, config.contact_spam_threshold))
+    }
+
+    /// Score from which a message is quarantined
+    #[must_use]
+    pub const fn threshold(&self) -> f64 {
+        self.threshold
+    }
+
+    fn collection(&self) -> mongodb::Collection<Document> {
+        self.db.collection::<Document>(TRAINING_COLLECTION)
+    }
+
+    async fn model(&self) -> Result<Arc<NaiveBayes>> {
+        if let Some(model) = self
+            .model
+            .read()
+            .map_err(|_| anyhow::anyhow!("Spam model poisoned"))?
+            .as_ref()
+        {
+            return Ok(model.clone());
+        }
+
+        let docs: Vec<Document> = self.collection().find(doc! {}).await?.try_collect().await?;
+        let mut model = NaiveBayes::default();
+        for doc in &docs {
+            let tokens: Vec<String> = doc
+                .get_array("tokens")
+                .map(|tokens| {
+                    tokens
+                        .iter()
+                        .filter_map(|token| token.as_str().map(str::to_string))
+                        .collect()
+                })
+                .unwrap_or_default();
+            model.train(&tokens, doc.get_bool("spam").unwrap_or(false));
+        }
+
+        let model = Arc::new(model);
+        *self
+            .model
+            .write()
+            .map_err(|_| anyhow::anyhow!("Spam model poisoned"))? = Some(model.clone());
+        Ok(model)
+    }
+
+    /// Scores a message.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the training messages cannot be read.
+    pub async fn score(&self, input: &SpamInput<'_>) -> Result<SpamReport> {
+        Ok(self.rules.evaluate(input, &*self.model().await?))
+    }
+
+    /// Labels a message as spam or ham, replacing a previous label of the same contact.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the label cannot be stored.
+    pub async fn train(
+        &self,
+        contact_id: ObjectId,
+        input: &SpamInput<'_>,
+        spam: bool,
+    ) -> Result<()> {
+        self.collection()
+            .update_one(
+                doc! { "contact_id": contact_id },
+                doc! { "$set": {
+                    "tokens": input.tokens(),
+                    "spam": spam,
+                    "trained_at": mongodb::bson::DateTime::now(),
+                } },
+            )
+            .upsert(true)
+            .await?;
+
+        // Rebuilt on the next score
+        *self
+            .model
+            .write()
+            .map_err(|_| anyhow::anyhow!("Spam model poisoned"))? = None;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input<'a>(message: &'a str, language: Option<&'a str>) -> SpamInput<'a> {
+        SpamInput {
+            subject: "Bonjour",
+            message,
+            language,
+        }
+    }
+
+    #[test]
+    fn test_clean_message() {
+        let rules = SpamRules::default();
+        let report = rules.evaluate(
+            &input(
+                "Bonjour, je voudrais discuter avec vous d'une mission pour notre équipe.",
+                Some("fr"),
+            ),
+            &NaiveBayes::default(),
+        );
+        assert_eq!(report, SpamReport::default());
+    }
+
+    #[test]
+    fn test_keywords_and_links() {
+        let rules = SpamRules::compile(&SpamRulesSpec {
+            trusted_domains: vec!["github.com".to_string()],
+            ..SpamRulesSpec::default()
+        });
+        let report = rules.evaluate(
+            &input(
+                "Best CASINO bonus at https://bit.ly/x, www.promo.example.net and https://github.com/me",
+                None,
+            ),
+            &NaiveBayes::default(),
+        );
+        assert_eq!(
+            report.reasons,
+            vec![
+                "keyword:casino",
+                "blocked_domain:bit.ly",
+                "link:promo.example.net"
+            ]
+        );
+        assert!((report.score - 7.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_language_mismatch() {
+        let rules = SpamRules::default();
+        let english = "Hello, we can help you with the ranking of your website and our team.";
+        let report = rules.evaluate(&input(english, Some("fr-FR")), &NaiveBayes::default());
+        assert_eq!(report.reasons, vec!["language:en≠fr"]);
+        assert_eq!(
+            detect_language("Здравствуйте, у нас есть предложение"),
+            Some("other")
+        );
+        assert_eq!(detect_language("Rust"), None);
+    }
+
+    #[test]
+    fn test_confusables() {
+        // Cyrillic "а" in a Latin word
+        assert!(has_confusables("Click to cl\u{430}im"));
+        assert!(has_confusables("free\u{200B}money"));
+        assert!(!has_confusables("Café crème, Здравствуйте"));
+    }
+
+    #[test]
+    fn test_bayes_signal() {
+        let rules = SpamRules::compile(&SpamRulesSpec {
+            keywords: Vec::new(),
+            ..SpamRulesSpec::default()
+        });
+        let mut model = NaiveBayes::default();
+        for _ in 0..crate::services::bayes::MIN_TRAINING {
+            model.train(&input("cheap pills shipped overnight", None).tokens(), true);
+            model.train(
+                &input("votre portfolio est très réussi", None).tokens(),
+                false,
+            );
+        }
+
+        let spam = rules.evaluate(&input("cheap pills", None), &model);
+        assert!(spam.score > 3.5, "{spam:?}");
+        let ham = rules.evaluate(&input("portfolio réussi", None), &model);
+        assert!(ham.score < -3.5, "{ham:?}");
+    }
+
+    #[test]
+    fn test_invalid_keywords_are_skipped() {
+        let spec: SpamRulesSpec = serde_json::from_str(
+            r#"{ "keywords": [{ "regex": "(" }, { "keyword": "a", "regex": "b" }, { "keyword": "seo", "score": 1 }] }"#,
+        )
+        .unwrap();
+        let rules = SpamRules::compile(&spec);
+        assert_eq!(rules.keywords.len(), 1);
+        assert_eq!(rules.blocked_domains, default_blocked_domains());
+    }
+}
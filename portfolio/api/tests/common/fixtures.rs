@@ -27,6 +27,11 @@ pub fn sample_contact_request() -> ContactRequest {
         message: Sentences(3..8).fake::<Vec<String>>().join(" "),
         is_test: false,
         test_name: None,
+        language: None,
+        token: None,
+        website: None,
+        challenge: None,
+        proof: None,
     }
 }
 
@@ -54,6 +59,11 @@ pub struct ContactRequestBuilder {
     message: String,
     is_test: bool,
     test_name: Option<String>,
+    language: Option<String>,
+    token: Option<String>,
+    website: Option<String>,
+    challenge: Option<String>,
+    proof: Option<String>,
 }
 
 impl ContactRequestBuilder {
@@ -65,6 +75,11 @@ impl ContactRequestBuilder {
             message: Sentences(3..8).fake::<Vec<String>>().join(" "),
             is_test: false,
             test_name: None,
+            language: None,
+            token: None,
+            website: None,
+            challenge: None,
+            proof: None,
         }
     }
 
@@ -101,6 +116,31 @@ impl ContactRequestBuilder {
         self
     }
 
+    #[allow(dead_code)]
+    pub fn language(mut self, language: &str) -> Self {
+        self.language = Some(language.to_string());
+        self
+    }
+
+    #[allow(dead_code)]
+    pub fn token(mut self, token: &str) -> Self {
+        self.token = Some(token.to_string());
+        self
+    }
+
+    #[allow(dead_code)]
+    pub fn website(mut self, website: &str) -> Self {
+        self.website = Some(website.to_string());
+        self
+    }
+
+    #[allow(dead_code)]
+    pub fn proof_of_work(mut self, challenge: &str, proof: &str) -> Self {
+        self.challenge = Some(challenge.to_string());
+        self.proof = Some(proof.to_string());
+        self
+    }
+
     pub fn build(self) -> ContactRequest {
         ContactRequest {
             name: self.name,
@@ -109,6 +149,11 @@ impl ContactRequestBuilder {
             message: self.message,
             is_test: self.is_test,
             test_name: self.test_name,
+            language: self.language,
+            token: self.token,
+            website: self.website,
+            challenge: self.challenge,
+            proof: self.proof,
         }
     }
 }
@@ -145,6 +190,7 @@ pub fn sample_rss_items(count: usize) -> Vec<RssItem> {
             pub_date: Utc::now() - Duration::days(i as i64),
             description: Sentences(2..4).fake::<Vec<String>>().join(" "),
             image_url: format!("https://example.com/images/{}.jpg", i),
+            ..Default::default()
         })
         .collect()
 }
@@ -164,6 +210,7 @@ pub fn sample_rss_item() -> RssItem {
             "https://example.com/image/{}.jpg",
             SafeEmail().fake::<String>()
         ),
+        ..Default::default()
     }
 }
 
@@ -0,0 +1,189 @@
+//! Content source tests against local mock servers
+//!
+//! Each `ContentSource` implementation is fetched from a WireMock server and
+//! checked to produce the same normalized `SourceItem` model:
+//! 1. RSS and Atom feeds
+//! 2. Mastodon outbox (`ActivityPub` JSON)
+//! 3. GitHub repository releases
+
+use anyhow::Result;
+use portfolio_api::services::sources::{
+    ContentSource, FeedSource, GitHubReleasesSource, MastodonSource,
+};
+use serde_json::json;
+use wiremock::matchers::{header, method, path, query_param};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+fn client() -> reqwest::Client {
+    reqwest::Client::new()
+}
+
+// ============================================================================
+// RSS / ATOM
+// ============================================================================
+
+#[tokio::test]
+async fn test_feed_source_reads_rss_and_atom() -> Result<()> {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/rss.xml"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(
+            r#"<rss version="2.0"><channel><title>t</title><link>l</link><description>d</description>
+            <item><title>RSS post</title><link>https://example.com/rss-post</link>
+            <pubDate>Wed, 01 May 2024 08:00:00 GMT</pubDate>
+            <enclosure url="https://example.com/rss.png" length="1" type="image/png"/>
+            <category>rust</category></item>
+            </channel></rss>"#,
+        ))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/atom.xml"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(
+            r#"<feed xmlns="http://www.w3.org/2005/Atom"><title>t</title><id>urn:t</id>
+            <updated>2024-05-01T08:00:00Z</updated>
+            <entry><title>Atom post</title><id>urn:1</id><updated>2024-05-01T08:00:00Z</updated>
+            <link href="https://example.com/atom-post"/><summary>Summary</summary></entry>
+            </feed>"#,
+        ))
+        .mount(&server)
+        .await;
+
+    let rss = FeedSource
+        .fetch(&client(), &format!("{}/rss.xml", server.uri()))
+        .await?;
+    let atom = FeedSource
+        .fetch(&client(), &format!("{}/atom.xml", server.uri()))
+        .await?;
+
+    assert_eq!(rss.len(), 1);
+    assert_eq!(rss[0].title, "RSS post");
+    assert_eq!(rss[0].url, "https://example.com/rss-post");
+    assert_eq!(
+        rss[0].image_url.as_deref(),
+        Some("https://example.com/rss.png")
+    );
+    assert_eq!(rss[0].categories, vec!["rust"]);
+    assert!(rss[0].pub_date.is_some());
+
+    assert_eq!(atom.len(), 1);
+    assert_eq!(atom[0].title, "Atom post");
+    assert_eq!(atom[0].url, "https://example.com/atom-post");
+    assert_eq!(atom[0].description, "Summary");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_feed_source_reports_http_errors() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(404))
+        .mount(&server)
+        .await;
+
+    let result = FeedSource
+        .fetch(&client(), &format!("{}/missing.xml", server.uri()))
+        .await;
+
+    assert!(result.is_err());
+}
+
+// ============================================================================
+// MASTODON
+// ============================================================================
+
+#[tokio::test]
+async fn test_mastodon_source_follows_first_page() -> Result<()> {
+    let server = MockServer::start().await;
+    let outbox = format!("{}/users/alice/outbox", server.uri());
+
+    Mock::given(method("GET"))
+        .and(path("/users/alice/outbox"))
+        .and(query_param("page", "true"))
+        .and(header("accept", "application/activity+json"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "type": "OrderedCollectionPage",
+            "orderedItems": [{
+                "type": "Create",
+                "object": {
+                    "type": "Note",
+                    "url": "https://social.example/@alice/1",
+                    "published": "2024-05-01T08:00:00Z",
+                    "content": "<p>Hello from the fediverse</p>",
+                    "inReplyTo": null,
+                    "tag": [{ "type": "Hashtag", "name": "#intro" }]
+                }
+            }]
+        })))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/users/alice/outbox"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "type": "OrderedCollection",
+            "first": format!("{outbox}?page=true")
+        })))
+        .mount(&server)
+        .await;
+
+    let items = MastodonSource
+        .fetch(&client(), &format!("{}/@alice", server.uri()))
+        .await?;
+
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0].title, "Hello from the fediverse");
+    assert_eq!(items[0].url, "https://social.example/@alice/1");
+    assert_eq!(items[0].categories, vec!["intro"]);
+    assert_eq!(items[0].description, "<p>Hello from the fediverse</p>");
+
+    Ok(())
+}
+
+// ============================================================================
+// GITHUB RELEASES
+// ============================================================================
+
+#[tokio::test]
+async fn test_github_releases_source_skips_drafts() -> Result<()> {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/repos/mpiton/folio/releases"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+            {
+                "name": "v1.2.0",
+                "tag_name": "v1.2.0",
+                "html_url": "https://github.com/mpiton/folio/releases/tag/v1.2.0",
+                "body": "Bug fixes",
+                "draft": false,
+                "prerelease": false,
+                "published_at": "2024-05-01T08:00:00Z"
+            },
+            {
+                "name": null,
+                "tag_name": "v2.0.0-rc1",
+                "html_url": "https://github.com/mpiton/folio/releases/tag/v2.0.0-rc1",
+                "body": null,
+                "draft": true,
+                "prerelease": true,
+                "published_at": null
+            }
+        ])))
+        .mount(&server)
+        .await;
+
+    let items = GitHubReleasesSource::new(server.uri())
+        .fetch(&client(), "https://github.com/mpiton/folio")
+        .await?;
+
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0].title, "folio v1.2.0");
+    assert_eq!(
+        items[0].url,
+        "https://github.com/mpiton/folio/releases/tag/v1.2.0"
+    );
+    assert_eq!(items[0].description, "Bug fixes");
+    assert_eq!(items[0].categories, vec!["release"]);
+
+    Ok(())
+}
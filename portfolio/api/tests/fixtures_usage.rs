@@ -20,6 +20,11 @@ mod fixtures_tests {
                 message: "This is a valid test message for the contact form".to_string(),
                 is_test: false,
                 test_name: None,
+                language: None,
+                token: None,
+                website: None,
+                challenge: None,
+                proof: None,
             };
 
             assert_eq!(contact.name, "Test User");
@@ -37,6 +42,11 @@ mod fixtures_tests {
                 message: "This is a valid message for testing purposes".to_string(),
                 is_test: false,
                 test_name: None,
+                language: None,
+                token: None,
+                website: None,
+                challenge: None,
+                proof: None,
             };
 
             assert!(valid_contact.validate().is_ok());
@@ -53,6 +63,11 @@ mod fixtures_tests {
                 message: "This is a valid message for testing purposes".to_string(),
                 is_test: false,
                 test_name: None,
+                language: None,
+                token: None,
+                website: None,
+                challenge: None,
+                proof: None,
             };
 
             assert!(invalid_contact.validate().is_err());
@@ -72,6 +87,7 @@ mod fixtures_tests {
                 pub_date: Utc::now(),
                 description: "Test description".to_string(),
                 image_url: "https://example.com/image.jpg".to_string(),
+                ..Default::default()
             };
 
             assert!(!item.title.is_empty());
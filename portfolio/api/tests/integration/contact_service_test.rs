@@ -9,8 +9,12 @@
 use anyhow::Result;
 use mongodb::bson::doc;
 use portfolio_api::services::contact::MessageService;
+use portfolio_api::services::contact_throttle::ThrottleScope;
+use portfolio_api::services::email_transports;
 use portfolio_api::Config;
+use std::net::IpAddr;
 use std::sync::Arc;
+use std::time::Duration;
 use wiremock::matchers::{header, method, path};
 use wiremock::{Mock, MockServer, ResponseTemplate};
 
@@ -35,9 +39,61 @@ fn create_test_config() -> Config {
         sender_name: "Test Sender".to_string(),
         sender_email: "test@sender.com".to_string(),
         frontend_url: "http://localhost:3000".to_string(),
+        public_api_url: "http://127.0.0.1:3001".to_string(),
         rss_source_url: "http://example.com/rss".to_string(),
         rss_source_db: "rss_source".to_string(),
         rss_source_collection: "rss_items".to_string(),
+        rss_global_rules_path: None,
+        rss_og_enrichment: false,
+        rss_image_metadata: false,
+        image_proxy_cache_dir: None,
+        image_proxy_cache_max_mb: 16,
+        link_check_interval_hours: 0,
+        link_check_host_delay_ms: 0,
+        github_api_url: "https://api.github.com".to_string(),
+        github_username: None,
+        github_token: None,
+        github_sync_interval_hours: 6,
+        i18n_locales: vec!["fr".to_string(), "en".to_string()],
+        email_max_attempts: 8,
+        email_retry_base_secs: 30,
+        email_transport: "brevo".to_string(),
+        brevo_api_url: "https://api.brevo.com".to_string(),
+        smtp_host: None,
+        smtp_port: None,
+        smtp_tls: "starttls".to_string(),
+        smtp_username: None,
+        smtp_password: None,
+        email_file_dir: "mail".to_string(),
+        email_templates_dir: None,
+        email_fallback_locale: "en".to_string(),
+        email_templates_strict: true,
+        email_templates_hot_reload: false,
+        contact_auto_reply: false,
+        contact_auto_reply_window_hours: 24,
+        contact_token_secret: Some("test_contact_secret".to_string()),
+        contact_min_fill_secs: 3,
+        contact_token_max_age_secs: 7200,
+        contact_spam_rules_path: None,
+        contact_spam_threshold: 5.0,
+        contact_pow_difficulty: 4,
+        contact_pow_max_difficulty: 8,
+        contact_pow_spike_threshold: 20,
+        contact_pow_window_secs: 600,
+        contact_pow_ttl_secs: 600,
+        contact_disposable_domains_path: None,
+        contact_mx_check: false,
+        contact_dns_servers: None,
+        contact_dns_timeout_ms: 2000,
+        contact_dns_cache_secs: 3600,
+        contact_limit_per_ip: 3,
+        contact_limit_ip_window_secs: 3600,
+        contact_limit_per_email: 5,
+        contact_limit_email_window_secs: 86400,
+        contact_limit_global: 200,
+        contact_limit_global_window_secs: 86400,
+        admin_api_token: Some("test_admin_token".to_string()),
+        ranking: Default::default(),
     }
 }
 
@@ -526,6 +582,193 @@ async fn g3_3_validate_email_request_format() -> Result<()> {
     Ok(())
 }
 
+/// G3.4: Test email transport selection from the configuration
+#[tokio::test]
+async fn g3_4_select_email_transport() -> Result<()> {
+    let mut config = create_test_config();
+
+    // The placeholder Brevo key only logs the emails
+    assert_eq!(email_transports::from_config(&config)?.name(), "log");
+
+    config.brevo_api_key = "real_key".to_string();
+    assert_eq!(email_transports::from_config(&config)?.name(), "brevo");
+
+    config.email_transport = "smtp".to_string();
+    assert!(
+        email_transports::from_config(&config).is_err(),
+        "SMTP transport requires SMTP_HOST"
+    );
+    config.smtp_host = Some("smtp.example.com".to_string());
+    assert_eq!(email_transports::from_config(&config)?.name(), "smtp");
+
+    config.email_transport = "file".to_string();
+    assert_eq!(email_transports::from_config(&config)?.name(), "file");
+
+    config.email_transport = "carrier_pigeon".to_string();
+    assert!(email_transports::from_config(&config).is_err());
+    Ok(())
+}
+
+/// G3.5: Test the acknowledgment email, sent once per address and window
+#[tokio::test]
+async fn g3_5_auto_reply_once_per_window() -> Result<()> {
+    let (_client, db) = setup_mongodb().await?;
+    let outbox = db.collection::<mongodb::bson::Document>("email_outbox");
+    let recipients =
+        doc! { "message.to": { "$in": ["visitor@example.com", "bot@mailinator.com"] } };
+    cleanup_db(&db, &["contact_auto_replies"]).await?;
+    outbox.delete_many(recipients.clone()).await?;
+
+    // Unique index created by db::initialize in production
+    db.collection::<mongodb::bson::Document>("contact_auto_replies")
+        .create_index(
+            mongodb::IndexModel::builder()
+                .keys(doc! { "email": 1 })
+                .options(
+                    mongodb::options::IndexOptions::builder()
+                        .unique(true)
+                        .build(),
+                )
+                .build(),
+        )
+        .await?;
+
+    let mut config = create_test_config();
+    config.contact_auto_reply = true;
+    let service = MessageService::new(db.clone(), config);
+
+    let contact = ContactRequestBuilder::new()
+        .email("visitor@example.com")
+        .language("en-GB")
+        .build();
+    assert!(
+        service
+            .send_auto_reply(&contact, "C-20261018-000001")
+            .await?
+    );
+    assert!(
+        !service
+            .send_auto_reply(&contact, "C-20261018-000002")
+            .await?,
+        "A second acknowledgment within the window should be skipped"
+    );
+
+    let disposable = ContactRequestBuilder::new()
+        .email("bot@mailinator.com")
+        .build();
+    assert!(
+        !service
+            .send_auto_reply(&disposable, "C-20261018-000003")
+            .await?
+    );
+
+    let job = outbox
+        .find_one(doc! { "message.to": "visitor@example.com" })
+        .await?
+        .expect("acknowledgment queued");
+    let message = job.get_document("message")?;
+    assert_eq!(
+        message.get_str("subject")?,
+        "Your message was received (C-20261018-000001)"
+    );
+    assert_eq!(message.get_str("reply_to")?, "test@example.com");
+    assert_eq!(outbox.count_documents(recipients.clone()).await?, 1);
+
+    cleanup_db(&db, &["contact_auto_replies"]).await?;
+    outbox.delete_many(recipients).await?;
+    Ok(())
+}
+
+/// G3.6: Spam scored above the threshold is quarantined, then released on review
+#[tokio::test]
+async fn g3_6_quarantine_spam_until_released() -> Result<()> {
+    let (_client, db) = setup_mongodb().await?;
+    let outbox = db.collection::<mongodb::bson::Document>("email_outbox");
+    let recipient = doc! { "message.reply_to": "spammer@example.com" };
+    cleanup_db(&db, &["contact_quarantine", "spam_training"]).await?;
+    outbox.delete_many(recipient.clone()).await?;
+
+    let service = MessageService::new(db.clone(), create_test_config());
+    let spam = ContactRequestBuilder::new()
+        .email("spammer@example.com")
+        .message("Casino bonus, crypto and bitcoin forex signals for your website")
+        .build();
+    service.submit_contact(spam).await?;
+
+    assert_eq!(outbox.count_documents(recipient.clone()).await?, 0);
+    let quarantined = service.list_quarantine(10).await?;
+    assert_eq!(quarantined.len(), 1);
+    assert!(quarantined[0].spam_score >= 5.0);
+    assert!(quarantined[0]
+        .spam_reasons
+        .contains(&"keyword:casino".to_string()));
+
+    let id = mongodb::bson::oid::ObjectId::parse_str(&quarantined[0].id)?;
+    assert!(service.release_quarantined(id).await?);
+    assert!(!service.release_quarantined(id).await?);
+    assert!(service.list_quarantine(10).await?.is_empty());
+    assert_eq!(outbox.count_documents(recipient.clone()).await?, 1);
+    assert_eq!(
+        db.collection::<mongodb::bson::Document>("spam_training")
+            .count_documents(doc! { "contact_id": id, "spam": false })
+            .await?,
+        1
+    );
+
+    db.collection::<mongodb::bson::Document>("contacts")
+        .delete_one(doc! { "_id": id })
+        .await?;
+    cleanup_db(&db, &["contact_quarantine", "spam_training"]).await?;
+    outbox.delete_many(recipient).await?;
+    Ok(())
+}
+
+/// G3.7: Test the contact limits per IP, per address and overall
+#[tokio::test]
+async fn g3_7_throttle_contact_submissions() -> Result<()> {
+    let (_client, db) = setup_mongodb().await?;
+    cleanup_db(&db, &["contact_throttles"]).await?;
+
+    let mut config = create_test_config();
+    config.contact_limit_per_ip = 2;
+    config.contact_limit_per_email = 2;
+    config.contact_limit_global = 4;
+    let service = MessageService::new(db.clone(), config);
+    let throttle = service.throttle();
+    let ip = |last: u8| IpAddr::from([192, 0, 2, last]);
+
+    assert!(throttle.check(ip(1), "a@example.com").await?.is_none());
+    assert!(throttle.check(ip(1), "b@example.com").await?.is_none());
+    let throttled = throttle
+        .check(ip(1), "c@example.com")
+        .await?
+        .expect("IP limit reached");
+    assert_eq!(throttled.scope, ThrottleScope::Ip);
+    assert!(throttled.retry_after <= Duration::from_secs(3600));
+
+    // The address is normalized before being counted
+    assert!(throttle.check(ip(2), "a@example.com").await?.is_none());
+    let throttled = throttle
+        .check(ip(3), " A@EXAMPLE.com ")
+        .await?
+        .expect("address limit reached");
+    assert_eq!(throttled.scope, ThrottleScope::Email);
+
+    assert!(throttle.check(ip(4), "d@example.com").await?.is_none());
+    let throttled = throttle
+        .check(ip(5), "e@example.com")
+        .await?
+        .expect("global limit reached");
+    assert_eq!(throttled.scope, ThrottleScope::Global);
+
+    assert_eq!(throttle.locale(Some("en-GB"), None), "en");
+    assert_eq!(throttle.locale(None, Some("de, en;q=0.5")), "en");
+    assert_eq!(throttle.locale(None, None), "fr");
+
+    cleanup_db(&db, &["contact_throttles"]).await?;
+    Ok(())
+}
+
 // ============================================================================
 // BONUS: Edge Cases and Integration Scenarios
 // ============================================================================
@@ -0,0 +1,130 @@
+//! GitHub projects tests against a stand-in GitHub API
+//!
+//! Verifies the repository listing used by the projects showcase:
+//! 1. Pagination over full pages
+//! 2. Forks, archived and private repositories are skipped
+//! 3. The optional token is sent as a bearer token
+//! 4. A listing cut by the page cap is reported as incomplete
+
+use anyhow::Result;
+use portfolio_api::services::projects::fetch_repositories;
+use serde_json::{json, Value};
+use wiremock::matchers::{header, method, path, query_param};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+fn repository(name: &str, fork: bool, archived: bool) -> Value {
+    json!({
+        "name": name,
+        "description": format!("{name} description"),
+        "html_url": format!("https://github.com/octocat/{name}"),
+        "homepage": null,
+        "stargazers_count": 3,
+        "language": "Rust",
+        "topics": ["rust"],
+        "pushed_at": "2024-05-01T08:00:00Z",
+        "fork": fork,
+        "archived": archived,
+        "private": false
+    })
+}
+
+#[tokio::test]
+async fn test_fetch_repositories_follows_pages_and_filters() -> Result<()> {
+    let server = MockServer::start().await;
+    let first_page: Vec<Value> = (0..100)
+        .map(|i| repository(&format!("repo-{i}"), i % 10 == 0, false))
+        .collect();
+
+    Mock::given(method("GET"))
+        .and(path("/users/octocat/repos"))
+        .and(query_param("page", "1"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(Value::Array(first_page)))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/users/octocat/repos"))
+        .and(query_param("page", "2"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+            repository("last", false, false),
+            repository("old", false, true)
+        ])))
+        .mount(&server)
+        .await;
+
+    let listing =
+        fetch_repositories(&reqwest::Client::new(), &server.uri(), "octocat", None).await?;
+    let repositories = listing.repositories;
+
+    // 10 forks and 1 archived repository are skipped
+    assert_eq!(repositories.len(), 91);
+    assert!(repositories.iter().any(|repo| repo.name == "last"));
+    assert!(repositories.iter().all(|repo| !repo.fork && !repo.archived));
+    assert_eq!(repositories[1].language.as_deref(), Some("Rust"));
+    assert_eq!(repositories[1].topics, vec!["rust"]);
+    assert!(listing.complete);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_fetch_repositories_sends_token() -> Result<()> {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/users/octocat/repos"))
+        .and(header("authorization", "Bearer secret"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_json(json!([repository("folio", false, false)])),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let repositories = fetch_repositories(
+        &reqwest::Client::new(),
+        &server.uri(),
+        "octocat",
+        Some("secret"),
+    )
+    .await?
+    .repositories;
+
+    assert_eq!(repositories.len(), 1);
+    assert_eq!(repositories[0].stargazers_count, 3);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_fetch_repositories_reports_truncated_listing() -> Result<()> {
+    let server = MockServer::start().await;
+    let full_page: Vec<Value> = (0..100)
+        .map(|i| repository(&format!("repo-{i}"), false, false))
+        .collect();
+    Mock::given(method("GET"))
+        .and(path("/users/octocat/repos"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(Value::Array(full_page)))
+        .expect(10)
+        .mount(&server)
+        .await;
+
+    let listing =
+        fetch_repositories(&reqwest::Client::new(), &server.uri(), "octocat", None).await?;
+
+    assert!(!listing.complete);
+    assert_eq!(listing.repositories.len(), 1000);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_fetch_repositories_reports_api_errors() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(403))
+        .mount(&server)
+        .await;
+
+    let result = fetch_repositories(&reqwest::Client::new(), &server.uri(), "octocat", None).await;
+
+    assert!(result.is_err());
+}
@@ -12,6 +12,8 @@
 /// Framework: tokio async runtime with wiremock HTTP mocking and MongoDB testcontainers
 use anyhow::Result;
 use mongodb::bson::doc;
+use portfolio_api::models::rss::ModerationUpdate;
+use portfolio_api::services::opengraph::OpenGraphScraper;
 use portfolio_api::services::rss::FeedService;
 use std::sync::Arc;
 use wiremock::matchers::{method, path};
@@ -113,9 +115,61 @@ mod test_helpers {
             sender_name: "Test Sender".to_string(),
             sender_email: "test@sender.com".to_string(),
             frontend_url: "http://localhost:3000".to_string(),
+            public_api_url: "http://127.0.0.1:3001".to_string(),
             rss_source_url: "http://example.com/rss".to_string(),
             rss_source_db: "rss_source".to_string(),
             rss_source_collection: "rss_items".to_string(),
+            rss_global_rules_path: None,
+            rss_og_enrichment: false,
+            rss_image_metadata: false,
+            image_proxy_cache_dir: None,
+            image_proxy_cache_max_mb: 16,
+            link_check_interval_hours: 0,
+            link_check_host_delay_ms: 0,
+            github_api_url: "https://api.github.com".to_string(),
+            github_username: None,
+            github_token: None,
+            github_sync_interval_hours: 6,
+            i18n_locales: vec!["fr".to_string(), "en".to_string()],
+            email_max_attempts: 8,
+            email_retry_base_secs: 30,
+            email_transport: "brevo".to_string(),
+            brevo_api_url: "https://api.brevo.com".to_string(),
+            smtp_host: None,
+            smtp_port: None,
+            smtp_tls: "starttls".to_string(),
+            smtp_username: None,
+            smtp_password: None,
+            email_file_dir: "mail".to_string(),
+            email_templates_dir: None,
+            email_fallback_locale: "en".to_string(),
+            email_templates_strict: true,
+            email_templates_hot_reload: false,
+            contact_auto_reply: false,
+            contact_auto_reply_window_hours: 24,
+            contact_token_secret: Some("test_contact_secret".to_string()),
+            contact_min_fill_secs: 3,
+            contact_token_max_age_secs: 7200,
+            contact_spam_rules_path: None,
+            contact_spam_threshold: 5.0,
+            contact_pow_difficulty: 4,
+            contact_pow_max_difficulty: 8,
+            contact_pow_spike_threshold: 20,
+            contact_pow_window_secs: 600,
+            contact_pow_ttl_secs: 600,
+            contact_disposable_domains_path: None,
+            contact_mx_check: false,
+            contact_dns_servers: None,
+            contact_dns_timeout_ms: 2000,
+            contact_dns_cache_secs: 3600,
+            contact_limit_per_ip: 3,
+            contact_limit_ip_window_secs: 3600,
+            contact_limit_per_email: 5,
+            contact_limit_email_window_secs: 86400,
+            contact_limit_global: 200,
+            contact_limit_global_window_secs: 86400,
+            admin_api_token: Some("test_admin_token".to_string()),
+            ranking: Default::default(),
         }
     }
 }
@@ -924,11 +978,11 @@ async fn test_get_feeds_default_image_placeholder() -> Result<()> {
     // Act: Get feeds
     let feeds = feed_service.get_feeds(1, 10).await;
 
-    // Assert: Default placeholder image is used
+    // Assert: Self-hosted placeholder image is used
     assert_eq!(feeds.len(), 1);
     assert_eq!(
         feeds[0].image_url,
-        "https://placehold.co/600x400/grey/white/png?text=Article"
+        "http://127.0.0.1:3001/api/placeholder?title=Article%20without%20image&source="
     );
 
     Ok(())
@@ -1286,3 +1340,147 @@ async fn test_get_feeds_high_page_numbers() -> Result<()> {
 
     Ok(())
 }
+
+#[tokio::test]
+async fn test_moderation_flags_respected_by_listings() -> Result<()> {
+    // Arrange: Setup database with three items
+    let (_client, db) = test_helpers::setup_mongodb().await?;
+    test_helpers::cleanup_db(&db, &["portfolio", "rss_moderation"]).await?;
+
+    let config = test_helpers::test_config();
+    let feed_service = FeedService::new(db.clone(), config);
+
+    let collection = db.collection::<mongodb::bson::Document>("portfolio");
+    let items = vec![
+        doc! { "item_id": "old", "title": "Old", "url": "https://example.com/old", "pub_date": "2024-10-20T10:00:00Z", "description": "Test", "image_url": "https://example.com/1.jpg" },
+        doc! { "item_id": "mid", "title": "Mid", "url": "https://example.com/mid", "pub_date": "2024-10-22T10:00:00Z", "description": "Test", "image_url": "https://example.com/2.jpg" },
+        doc! { "item_id": "new", "title": "New", "url": "https://example.com/new", "pub_date": "2024-10-24T10:00:00Z", "description": "Test", "image_url": "https://example.com/3.jpg" },
+    ];
+    collection.insert_many(&items).await?;
+
+    // Act: Pin the oldest item, hide the newest and feature the middle one
+    let pin = ModerationUpdate {
+        pinned: Some(true),
+        ..Default::default()
+    };
+    let hide = ModerationUpdate {
+        hidden: Some(true),
+        ..Default::default()
+    };
+    let feature = ModerationUpdate {
+        featured: Some(true),
+        ..Default::default()
+    };
+    assert!(feed_service.moderate_item("old", &pin).await?);
+    assert!(feed_service.moderate_item("new", &hide).await?);
+    assert!(feed_service.moderate_item("mid", &feature).await?);
+    assert!(!feed_service.moderate_item("missing", &pin).await?);
+
+    // Assert: Hidden item is gone, pinned item comes first
+    let feeds = feed_service.get_feeds(1, 10).await;
+    let titles: Vec<&str> = feeds.iter().map(|item| item.title.as_str()).collect();
+    assert_eq!(titles, vec!["Old", "Mid"]);
+    assert!(feeds[0].pinned);
+
+    let featured = feed_service.get_featured(10).await;
+    assert_eq!(featured.len(), 1);
+    assert_eq!(featured[0].id, "mid");
+
+    // Assert: Flags are persisted for the next sync
+    let moderation = db.collection::<mongodb::bson::Document>("rss_moderation");
+    assert_eq!(moderation.count_documents(doc! {}).await?, 3);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_get_ranked_feeds_diversity_and_pagination() -> Result<()> {
+    // Arrange: One prolific source and one quieter source
+    let (_client, db) = test_helpers::setup_mongodb().await?;
+    test_helpers::cleanup_db(&db, &["portfolio"]).await?;
+
+    let config = test_helpers::test_config();
+    let feed_service = FeedService::new(db.clone(), config);
+
+    let collection = db.collection::<mongodb::bson::Document>("portfolio");
+    let base = chrono::DateTime::parse_from_rfc3339("2024-10-24T10:00:00Z")?.timestamp_millis();
+    let items: Vec<mongodb::bson::Document> = (0..6)
+        .map(|i| {
+            let source = if i < 4 {
+                "https://a.example.com/feed"
+            } else {
+                "https://b.example.com/feed"
+            };
+            doc! {
+                "item_id": format!("item-{i}"),
+                "source": source,
+                "title": format!("Item {i}"),
+                "url": format!("https://example.com/{i}"),
+                "pub_date": mongodb::bson::DateTime::from_millis(base - i * 3_600_000),
+                "description": "Test",
+                "image_url": "https://example.com/image.jpg"
+            }
+        })
+        .collect();
+    collection.insert_many(&items).await?;
+
+    // Act: Fetch the ranked listing in two pages
+    let page_1 = feed_service.get_ranked_feeds(1, 3).await;
+    let page_2 = feed_service.get_ranked_feeds(2, 3).await;
+
+    // Assert: At most two consecutive items from source "a", no duplicates across pages
+    let ids: Vec<&str> = page_1
+        .iter()
+        .chain(&page_2)
+        .map(|item| item.id.as_str())
+        .collect();
+    assert_eq!(
+        ids,
+        vec!["item-0", "item-1", "item-4", "item-2", "item-3", "item-5"]
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_opengraph_lookup_refuses_private_hosts() -> Result<()> {
+    // Arrange: Article page served on the loopback address, which must not be fetched
+    let mock_server = test_helpers::mock_rss_feed_server().await?;
+    let page = r#"<html><head>
+        <meta property="og:image" content="/cover.jpg">
+        <meta property="og:description" content="Scraped description">
+        <link rel="icon" href="/favicon.png">
+    </head><body></body></html>"#;
+
+    Mock::given(method("GET"))
+        .and(path("/article"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("Content-Type", "text/html; charset=utf-8")
+                .set_body_string(page),
+        )
+        .expect(0)
+        .mount(&mock_server)
+        .await;
+
+    let (_client, db) = test_helpers::setup_mongodb().await?;
+    test_helpers::cleanup_db(&db, &["og_cache"]).await?;
+    let scraper = OpenGraphScraper::new(db.clone());
+    let url = format!("{}/article", mock_server.uri());
+
+    // Act: Look the page up twice
+    let first = scraper.lookup(&url).await?;
+    let second = scraper.lookup(&url).await?;
+
+    // Assert: The refusal is cached as empty metadata
+    assert_eq!(first, Default::default());
+    assert_eq!(first, second);
+    assert_eq!(
+        db.collection::<mongodb::bson::Document>("og_cache")
+            .count_documents(doc! { "url": &url })
+            .await?,
+        1
+    );
+
+    Ok(())
+}
@@ -1,6 +1,24 @@
-import { test, expect } from '@playwright/test';
+import { test, expect, type Page } from '@playwright/test';
 import { AxeBuilder } from '@axe-core/playwright';
 
+/**
+ * Défi trivial (difficulté 0) : toute solution est acceptée
+ */
+async function mockContactChallenge(page: Page) {
+  await page.route('**/api/contact/challenge', async route => {
+    await route.fulfill({
+      status: 200,
+      contentType: 'application/json',
+      body: JSON.stringify({
+        challenge: 'test-challenge',
+        algorithm: 'sha256',
+        difficulty: 0,
+        expires_in: 600,
+      }),
+    });
+  });
+}
+
 // ============================================================================
 // GROUPE 1: FORM RENDERING (1.1-1.3)
 // ============================================================================
@@ -35,6 +53,7 @@ test.describe('ContactForm - Rendering', () => {
       }
     });
 
+    await mockContactChallenge(page);
     await page.goto('/contact');
     await page.waitForLoadState('domcontentloaded');
   });
@@ -136,6 +155,7 @@ test.describe('ContactForm - Validation', () => {
       }
     });
 
+    await mockContactChallenge(page);
     await page.goto('/contact');
     await page.waitForLoadState('domcontentloaded');
   });
@@ -258,6 +278,16 @@ test.describe('ContactForm - API Integration', () => {
       };
     });
 
+    // Jeton servi au chargement du formulaire
+    await page.route('**/api/contact/token', async route => {
+      await route.fulfill({
+        status: 200,
+        contentType: 'application/json',
+        body: JSON.stringify({ token: 'test-token', min_fill_secs: 3, expires_in: 3600 }),
+      });
+    });
+
+    await mockContactChallenge(page);
     await page.goto('/contact');
     await page.waitForLoadState('domcontentloaded');
   });
@@ -310,6 +340,10 @@ test.describe('ContactForm - API Integration', () => {
       email: 'test@example.com',
       subject: 'Test Subject',
       message: 'This is a test message for validation',
+      website: '',
+      token: 'test-token',
+      challenge: 'test-challenge',
+      proof: '0',
     });
   });
 
@@ -475,6 +509,7 @@ test.describe('ContactForm - Success Feedback', () => {
       }
     });
 
+    await mockContactChallenge(page);
     await page.goto('/contact');
     await page.waitForLoadState('domcontentloaded');
   });
@@ -578,6 +613,7 @@ test.describe('ContactForm - Rate Limiting', () => {
       }
     });
 
+    await mockContactChallenge(page);
     await page.goto('/contact');
     await page.waitForLoadState('domcontentloaded');
   });
@@ -656,6 +692,7 @@ test.describe('ContactForm - Accessibility', () => {
       }
     });
 
+    await mockContactChallenge(page);
     await page.goto('/contact');
     await page.waitForLoadState('domcontentloaded');
   });
@@ -750,6 +787,7 @@ test.describe('ContactForm - Edge Cases', () => {
       }
     });
 
+    await mockContactChallenge(page);
     await page.goto('/contact');
     await page.waitForLoadState('domcontentloaded');
   });
@@ -57,6 +57,20 @@ test.describe('Contact Form', () => {
       }
     });
 
+    // Mock du défi de preuve de travail (difficulté 0 : toute solution est acceptée)
+    await page.route('**/api/contact/challenge', async route => {
+      await route.fulfill({
+        status: 200,
+        contentType: 'application/json',
+        body: JSON.stringify({
+          challenge: 'test-challenge',
+          algorithm: 'sha256',
+          difficulty: 0,
+          expires_in: 600,
+        }),
+      });
+    });
+
     await page.goto('/contact');
 
     // Attendre que la page soit chargée
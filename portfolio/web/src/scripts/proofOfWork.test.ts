@@ -0,0 +1,30 @@
+// @vitest-environment node
+import { createHash } from 'node:crypto';
+import { describe, it, expect } from 'vitest';
+import { leadingZeroBits, solveChallenge } from './proofOfWork';
+
+/**
+ * Proof of work Unit Tests
+ *
+ * Runs in the node environment, which provides `crypto.subtle`.
+ */
+
+describe('proofOfWork - leadingZeroBits', () => {
+  it('counts the zero bits before the first set bit', () => {
+    expect(leadingZeroBits(new Uint8Array([0x00, 0x00, 0xff]))).toBe(16);
+    expect(leadingZeroBits(new Uint8Array([0x00, 0x1f]))).toBe(11);
+    expect(leadingZeroBits(new Uint8Array([0x80]))).toBe(0);
+    expect(leadingZeroBits(new Uint8Array([0x00]))).toBe(8);
+  });
+});
+
+describe('proofOfWork - solveChallenge', () => {
+  it('finds a solution accepted by the API check', async () => {
+    const challenge = '1700000000.8.abcdef.signature';
+    const solution = await solveChallenge(challenge, 8);
+
+    const hash = createHash('sha256').update(`${challenge}:${solution}`).digest();
+    expect(leadingZeroBits(new Uint8Array(hash))).toBeGreaterThanOrEqual(8);
+    expect(solution.length).toBeLessThanOrEqual(64);
+  });
+});
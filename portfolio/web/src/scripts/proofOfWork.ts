@@ -0,0 +1,39 @@
+export interface ContactChallenge {
+  challenge: string;
+  algorithm: string;
+  difficulty: number;
+  expires_in: number;
+}
+
+/**
+ * Nombre de bits à zéro en tête d'un hash
+ */
+export function leadingZeroBits(hash: Uint8Array): number {
+  let bits = 0;
+  for (const byte of hash) {
+    if (byte === 0) {
+      bits += 8;
+      continue;
+    }
+    bits += Math.clz32(byte) - 24;
+    break;
+  }
+  return bits;
+}
+
+/**
+ * Cherche une solution au défi du formulaire de contact
+ *
+ * Le SHA-256 de `{challenge}:{solution}` doit commencer par `difficulty`
+ * bits à zéro, comme le vérifie l'API.
+ */
+export async function solveChallenge(challenge: string, difficulty: number): Promise<string> {
+  const encoder = new TextEncoder();
+  for (let counter = 0; ; counter++) {
+    const solution = counter.toString();
+    const hash = await crypto.subtle.digest('SHA-256', encoder.encode(`${challenge}:${solution}`));
+    if (leadingZeroBits(new Uint8Array(hash)) >= difficulty) {
+      return solution;
+    }
+  }
+}
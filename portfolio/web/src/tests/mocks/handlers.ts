@@ -37,6 +37,22 @@ export const handlers = [
     );
   }),
 
+  // GET /api/contact/token - Mock contact form token
+  http.get(`${API_BASE_URL}/api/contact/token`, () => {
+    return HttpResponse.json(
+      { token: 'test-token', min_fill_secs: 3, expires_in: 3600 },
+      { status: 200 }
+    );
+  }),
+
+  // GET /api/contact/challenge - Mock proof of work challenge
+  http.get(`${API_BASE_URL}/api/contact/challenge`, () => {
+    return HttpResponse.json(
+      { challenge: 'test-challenge', algorithm: 'sha256', difficulty: 0, expires_in: 600 },
+      { status: 200 }
+    );
+  }),
+
   // GET /api/rss - Mock RSS feed fetching
   http.get(`${API_BASE_URL}/api/rss`, () => {
     return HttpResponse.json(
@@ -1,7 +1,25 @@
-import { test, expect } from '@playwright/test';
+import { test, expect, type Page } from '@playwright/test';
 import { AxeBuilder } from '@axe-core/playwright';
 import './test-types';
 
+/**
+ * Défi trivial (difficulté 0) : toute solution est acceptée
+ */
+async function mockContactChallenge(page: Page) {
+  await page.route('**/api/contact/challenge', async route => {
+    await route.fulfill({
+      status: 200,
+      contentType: 'application/json',
+      body: JSON.stringify({
+        challenge: 'test-challenge',
+        algorithm: 'sha256',
+        difficulty: 0,
+        expires_in: 600,
+      }),
+    });
+  });
+}
+
 // ============================================================================
 // GROUPE 1: FORM RENDERING (1.1-1.3)
 // ============================================================================
@@ -36,6 +54,7 @@ test.describe('ContactForm - Rendering', () => {
       }
     });
 
+    await mockContactChallenge(page);
     await page.goto('/contact');
     await page.waitForLoadState('domcontentloaded');
   });
@@ -137,6 +156,7 @@ test.describe('ContactForm - Validation', () => {
       }
     });
 
+    await mockContactChallenge(page);
     await page.goto('/contact');
     await page.waitForLoadState('domcontentloaded');
   });
@@ -285,6 +305,16 @@ test.describe('ContactForm - API Integration', () => {
       };
     });
 
+    // Jeton servi au chargement du formulaire
+    await page.route('**/api/contact/token', async route => {
+      await route.fulfill({
+        status: 200,
+        contentType: 'application/json',
+        body: JSON.stringify({ token: 'test-token', min_fill_secs: 3, expires_in: 3600 }),
+      });
+    });
+
+    await mockContactChallenge(page);
     await page.goto('/contact');
     await page.waitForLoadState('domcontentloaded');
   });
@@ -336,6 +366,10 @@ test.describe('ContactForm - API Integration', () => {
       email: 'test@example.com',
       subject: 'Test Subject',
       message: 'This is a test message for validation',
+      website: '',
+      token: 'test-token',
+      challenge: 'test-challenge',
+      proof: '0',
     });
   });
 
@@ -501,6 +535,7 @@ test.describe('ContactForm - Success Feedback', () => {
       }
     });
 
+    await mockContactChallenge(page);
     await page.goto('/contact');
     await page.waitForLoadState('domcontentloaded');
   });
@@ -595,6 +630,7 @@ test.describe('ContactForm - Rate Limiting', () => {
       }
     });
 
+    await mockContactChallenge(page);
     await page.goto('/contact');
     await page.waitForLoadState('domcontentloaded');
   });
@@ -673,6 +709,7 @@ test.describe('ContactForm - Accessibility', () => {
       }
     });
 
+    await mockContactChallenge(page);
     await page.goto('/contact');
     await page.waitForLoadState('domcontentloaded');
   });
@@ -762,6 +799,7 @@ test.describe('ContactForm - Edge Cases', () => {
       }
     });
 
+    await mockContactChallenge(page);
     await page.goto('/contact');
     await page.waitForLoadState('domcontentloaded');
   });